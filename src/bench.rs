@@ -0,0 +1,87 @@
+use std::time::{Duration,Instant};
+
+use ark_ff::PrimeField;
+
+use crate::polynomials as polys;
+use crate::sum_check::SumCheckPolynomial;
+
+// Helpers for the binaries which compare the running times of the prover and
+// the verifier, running both parties in a single thread instead of over a
+// channel.
+
+/// Outcome of [`time_rounds`].
+pub struct TimedRounds<F> {
+    /// Random point chosen by the verifier.
+    pub point: Vec<F>,
+    /// Value to compare with the evaluation of the polynomial at `point`.
+    pub check_value: F,
+    pub prover_time: Duration,
+    pub verifier_time: Duration,
+    /// Number of field elements sent in either direction.
+    pub field_elements: usize,
+}
+
+/// Run the rounds of sum-check for `claimed_sum` on `poly`, timing the work
+/// of each party separately.  The final check against an evaluation of the
+/// polynomial is left to the caller, who times it as the verifier's work.
+/// Returns `None` if g_j(0) + g_j(1) differs from the check value in some
+/// round.
+pub fn time_rounds<F: PrimeField, P: SumCheckPolynomial<F>>(mut poly: P, claimed_sum: F) -> Option<TimedRounds<F>> {
+    let mut rng = rand::thread_rng();
+    let mut prover_time = Duration::ZERO;
+    let mut verifier_time = Duration::ZERO;
+    let mut field_elements = 0;
+
+    let mut check_value = claimed_sum;
+    let mut point = vec![F::zero(); poly.num_vars()];
+    for j in (0..poly.num_vars()).rev() {
+        let start = Instant::now();
+        let message = poly.round_evaluations();
+        prover_time += start.elapsed();
+        field_elements += message.len() + usize::from(j > 0);
+
+        let start = Instant::now();
+        // g_j(1) is not among the evaluations if g_j is constant
+        let sum = polys::evaluate_from_evaluations(&message, F::zero())
+            + polys::evaluate_from_evaluations(&message, F::one());
+        if sum != check_value {
+            return None;
+        }
+        point[j] = F::rand(&mut rng);
+        check_value = polys::evaluate_from_evaluations(&message, point[j]);
+        verifier_time += start.elapsed();
+
+        let start = Instant::now();
+        poly.fix_last_variable(point[j]);
+        prover_time += start.elapsed();
+    }
+
+    Some(TimedRounds { point, check_value, prover_time, verifier_time, field_elements })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::polynomial::multivariate::{SparsePolynomial as MultiPoly, SparseTerm};
+    use crate::multilinear::DenseMLE;
+    use crate::small_fields::F13 as F;
+
+    #[test]
+    fn test_time_rounds() {
+        let mut rng = rand::thread_rng();
+        let poly = DenseMLE::<F>::rand(3, &mut rng);
+        let rounds = time_rounds(poly.clone(), poly.hypercube_sum()).unwrap();
+        assert_eq!(poly.oracle_evaluation(&rounds.point), rounds.check_value);
+        // two evaluations per round and a challenge after each but the last
+        assert_eq!(rounds.field_elements, 3 * 2 + 2);
+
+        assert!(time_rounds(poly.clone(), poly.hypercube_sum() + F::from(1u64)).is_none());
+
+        // constant in x_1, so g_1 is sent as a single evaluation
+        let poly: MultiPoly<F, SparseTerm> = polys::construct_poly(2, vec![(3, vec![(0, 1)])]);
+        let rounds = time_rounds(poly.clone(), F::from(6u64)).unwrap();
+        assert_eq!(poly.oracle_evaluation(&rounds.point), rounds.check_value);
+        assert_eq!(rounds.field_elements, 1 + 1 + 2);
+    }
+}
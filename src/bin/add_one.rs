@@ -1,5 +1,3 @@
-use rand;
-
 use std::fmt;
 use pazk::ip;
 use pazk::ip::{IP,Channel,Log};
@@ -28,12 +26,12 @@ enum Data {
 }
 
 impl Data {
-    fn to_number(self) -> Option<u8> {
-        if let Data::Number(n) = self { Some(n) } else { None }
+    fn to_number(&self) -> Option<u8> {
+        if let Data::Number(n) = self { Some(*n) } else { None }
     }
 
-    fn _to_decision(self) -> Option<bool> {
-        if let Data::Decision(d) = self { Some(d) } else { None }
+    fn _to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
     }
 }
 
@@ -79,7 +77,7 @@ impl IP<Data> for Add1Verifier {
 
         let m = ch.receive().to_number().unwrap();
 
-        log.write(format!("V checks m == n+1"));
+        log.write("V checks m == n+1".to_string());
         let decision = Data::Decision(m == self.n.wrapping_add(1));
 
         log.write(format!("V --> ({})", decision));
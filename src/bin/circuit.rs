@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use ark_test_curves::bls12_381::Fr as F;
+
+use pazk::circuit::{Circuit,Operation};
+use pazk::circuit_parser;
+use pazk::polynomials as polys;
+use pazk::r1cs::{self,R1CS};
+
+const USAGE: &str = "usage: circuit FILE [--dot] [INPUT...]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(path) = args.first().filter(|arg| !arg.starts_with("--")) else {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    };
+    let dot = args[1..].iter().any(|arg| arg == "--dot");
+    let inputs: Vec<F> = args[1..].iter()
+        .filter(|arg| *arg != "--dot")
+        .map(|arg| arg.parse::<i64>().map(F::from).unwrap_or_else(|_| {
+            eprintln!("invalid input '{}'\n{}", arg, USAGE);
+            process::exit(1);
+        }))
+        .collect();
+
+    let circuit = load(path);
+    if dot {
+        println!("{}", circuit.to_dot());
+        return;
+    }
+
+    let count = |f: fn(&Operation<F>) -> bool| circuit.gates.iter().filter(|op| f(op)).count();
+    let fan_in = circuit.gates.iter()
+        .map(|op| match op {
+            Operation::Add(operands) | Operation::Mul(operands) => operands.len(),
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    println!("Circuit {}", path);
+    println!("  inputs:     {}", circuit.num_inputs());
+    println!("  constants:  {}", count(|op| matches!(op, Operation::Const(_))));
+    println!("  sums:       {}", count(|op| matches!(op, Operation::Add(_))));
+    println!("  products:   {}", count(|op| matches!(op, Operation::Mul(_))));
+    println!("  outputs:    {}", circuit.outputs.len());
+    println!("  depth:      {}", circuit.depths().into_iter().max().unwrap_or(0));
+    println!("  max fan-in: {}", fan_in);
+    match circuit.to_layered() {
+        Ok(layered) => println!("  layered:    yes, with layer sizes {:?}", (0..=layered.depth()).map(|i| layered.layer_size(i)).collect::<Vec<_>>()),
+        Err(error) => println!("  layered:    no, {}", error),
+    }
+    let r1cs = R1CS::from_circuit(&circuit);
+    println!("  R1CS:       {} constraints, {} public inputs, {} witness entries",
+        r1cs.num_constraints(), r1cs.num_public, r1cs.num_witness);
+
+    if inputs.is_empty() {
+        return;
+    }
+    if inputs.len() != circuit.num_inputs() {
+        eprintln!("circuit has {} inputs but {} were given", circuit.num_inputs(), inputs.len());
+        process::exit(1);
+    }
+    println!();
+    let values = circuit.evaluate(&inputs);
+    for output in &circuit.outputs {
+        println!("{} = {}", circuit.names[*output], polys::format_field(values[*output], true));
+    }
+    let (public, witness) = r1cs::circuit_assignment(&circuit, &inputs);
+    match r1cs.is_satisfied(&public, &witness) {
+        Ok(()) => println!("R1CS is satisfied by the inputs, outputs and {} partial products", witness.len()),
+        Err(failure) => println!("R1CS is not satisfied: {}", failure),
+    }
+}
+
+fn load(path: &str) -> Circuit<F> {
+    let input = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+    circuit_parser::parse_circuit(&input).unwrap_or_else(|error| {
+        let (line, column) = error.location(&input);
+        eprintln!("{}:{}:{}: {}", path, line, column, error.message);
+        eprintln!("{}", error.show(&input));
+        process::exit(1);
+    })
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+
+use ark_ff::PrimeField;
+use rand::Rng;
+use rand::seq::index;
+
+use pazk::small_fields::{self,F5,F13,F17,F67,F251,F257};
+use pazk::fingerprint::{self,Alice,Bob};
+use pazk::sum_check::Data;
+use pazk::ip;
+
+const USAGE: &str = "usage: fingerprint [--field F5|F13|F17|F67|F251|F257] [FILE_A FILE_B]";
+
+const TRIALS: usize = 10000;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let field = if args.first().is_some_and(|arg| arg == "--field") {
+        if args.len() < 2 {
+            usage();
+        }
+        let field = args[1].clone();
+        args.drain(..2);
+        field
+    } else {
+        String::from("F257")
+    };
+    let (a, b) = match args.as_slice() {
+        [] => (b"The quick brown fox jumps over the lazy dog".to_vec(), b"The quick brown fox jumps over the lazy cog".to_vec()),
+        [path_a, path_b] => (read(path_a), read(path_b)),
+        _ => usage(),
+    };
+
+    println!("Reed-Solomon Fingerprinting");
+    println!("===========================");
+    match field.as_str() {
+        "F5" => run::<F5>(&field, &a, &b),
+        "F13" => run::<F13>(&field, &a, &b),
+        "F17" => run::<F17>(&field, &a, &b),
+        "F67" => run::<F67>(&field, &a, &b),
+        "F251" => run::<F251>(&field, &a, &b),
+        "F257" => run::<F257>(&field, &a, &b),
+        _ => usage(),
+    }
+
+    // how often Bob is fooled by files differing in a few bytes
+    println!();
+    println!("Fraction of {} runs on nearly equal files of {} bytes in which Bob outputs EQUAL:", TRIALS, a.len());
+    println!("{:>6}  {:>12}  {:>12}  {:>12}  {:>12}", "field", "(n - 1)/|F|", "1 change", "2 changes", "4 changes");
+    for (name, (bound, rates)) in [
+        ("F5", fooled_rates::<F5>(&a)),
+        ("F13", fooled_rates::<F13>(&a)),
+        ("F17", fooled_rates::<F17>(&a)),
+        ("F67", fooled_rates::<F67>(&a)),
+        ("F251", fooled_rates::<F251>(&a)),
+        ("F257", fooled_rates::<F257>(&a)),
+    ] {
+        println!("{:>6}  {:>12.4}  {:>12.4}  {:>12.4}  {:>12.4}", name, bound, rates[0], rates[1], rates[2]);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+fn read(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    })
+}
+
+// bound (n - 1)/|F| on the probability of being fooled, which is at most 1
+fn collision_bound<F: PrimeField>(n: usize) -> f64 {
+    (n.saturating_sub(1) as f64 / small_fields::field_size::<F>() as f64).min(1.0)
+}
+
+fn run<F: PrimeField>(field: &str, a: &[u8], b: &[u8]) {
+    println!("Field: {} with |F| = {}", field, small_fields::field_size::<F>());
+    println!("Alice holds a file of {} bytes and Bob one of {} bytes", a.len(), b.len());
+    if a.len() != b.len() {
+        println!("The files have different lengths, so they are not equal");
+        return;
+    }
+    let digits = fingerprint::symbols_per_byte::<F>();
+    if digits > 1 {
+        println!("Each byte is split into {} base {} digits", digits, small_fields::field_size::<F>());
+    }
+    let n = a.len() * digits;
+    println!("If the files differ, Bob outputs EQUAL with probability at most (n - 1)/|F| = {:.4} for n = {} symbols",
+        collision_bound::<F>(n), n);
+    println!();
+    let alice = Alice { symbols: Arc::new(fingerprint::to_symbols::<F>(a)) };
+    let bob = Bob { symbols: Arc::new(fingerprint::to_symbols::<F>(b)) };
+    ip::execute::<Data<F>>(alice, bob);
+}
+
+// fraction of random r for which the file agrees in fingerprint with a copy
+// modified in 1, 2 and 4 random symbols
+fn fooled_rates<F: PrimeField>(a: &[u8]) -> (f64, Vec<f64>) {
+    let mut rng = rand::thread_rng();
+    let symbols = fingerprint::to_symbols::<F>(a);
+    let rates = [1, 2, 4]
+        .iter()
+        .map(|&changes| {
+            let fooled = (0..TRIALS)
+                .filter(|_| {
+                    let mut other = symbols.clone();
+                    for pos in index::sample(&mut rng, symbols.len(), changes.min(symbols.len())) {
+                        other[pos] += F::from(rng.gen_range(1..small_fields::field_size::<F>()));
+                    }
+                    let r = F::rand(&mut rng);
+                    fingerprint::fingerprint(&symbols, r) == fingerprint::fingerprint(&other, r)
+                })
+                .count();
+            fooled as f64 / TRIALS as f64
+        })
+        .collect();
+    (collision_bound::<F>(symbols.len()), rates)
+}
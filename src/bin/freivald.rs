@@ -1,90 +1,145 @@
-use std::fmt;
-use std::io;
-use std::io::Write;
+use std::env;
+use std::process;
+use std::sync::Arc;
 
-use rand;
+use ark_ff::{Field,PrimeField};
+use ndarray::Array2;
 use rand::Rng;
-use rand::rngs::ThreadRng;
 
-use ark_std::UniformRand;
-use ark_ff::{Field,One};
+use pazk::small_fields::{self,F5,F13,F17,F67,F251,F257};
+use pazk::freivalds::{self,Data,TestVector,FreivaldsProver,FreivaldsVerifier};
+use pazk::ip;
 
-use ndarray::{Array,Array1,Array2,Dimension};
+const USAGE: &str = "usage: freivald [--field F5|F13|F17|F67|F251|F257] [--size N] [--vector powers|random] [--trials T]";
 
-// Underlying finite field
-use pazk::small_fields::F5 as F;
-const FIELD_NAME: &str = "F5";
-
-// Dimension of vectors and matrices
-const N: usize = 2;
+struct Config {
+    field: String,
+    size: usize,
+    test_vector: TestVector,
+    trials: usize,
+}
 
 fn main() {
-    let mut rng = rand::thread_rng();
-    let mut r: F;
-
-    println!("Running Freivald's algorithm over field {}", FIELD_NAME);
-
-    // generate random A and B
-    print!("Generating random {}x{} matrices A and B ... ", N, N);
-    io::stdout().flush().unwrap();
-    let mut a = Array2::<F>::default((N, N));
-    let mut b = Array2::<F>::default((N, N));
-    fill_random(&mut a, &mut rng);
-    fill_random(&mut b, &mut rng);
-    println!("Done");
-
-    // compute matrix product
-    print!("Computing product matrix A*B ... ");
-    io::stdout().flush().unwrap();
-    let mut c = a.dot(&b);
-    println!("Done");
-
-    // test with C = A*B
-    print!("Testing with C = A*B ... ");
-    io::stdout().flush().unwrap();
-    r = F::rand(&mut rng);
-    println!("{}", freivald_check(&a, &b, &c, &r));
-
-    // test with C = A*B modified by a single entry
-    print!("Testing with C = A*B but one entry modified ... ");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut config = Config { field: String::from("F5"), size: 2, test_vector: TestVector::Powers, trials: 10000 };
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, value] if flag == "--field" => config.field = value.clone(),
+            [flag, value] if flag == "--size" => config.size = value.parse().unwrap_or_else(|_| usage()),
+            [flag, value] if flag == "--trials" => config.trials = value.parse().unwrap_or_else(|_| usage()),
+            [flag, value] if flag == "--vector" => config.test_vector = match value.as_str() {
+                "powers" => TestVector::Powers,
+                "random" => TestVector::Random,
+                _ => usage(),
+            },
+            _ => usage(),
+        }
+    }
+    if config.size == 0 || config.trials == 0 {
+        usage();
+    }
 
-    let (u, v): (usize, usize) = (rng.gen_range(0..N), rng.gen_range(0..N));
-    c[(u,v)] = c[(u,v)] + F::one();
-    r = F::rand(&mut rng);
-    println!("{}", freivald_check(&a, &b, &c, &r));
+    match config.field.as_str() {
+        "F5" => run::<F5>(&config),
+        "F13" => run::<F13>(&config),
+        "F17" => run::<F17>(&config),
+        "F67" => run::<F67>(&config),
+        "F251" => run::<F251>(&config),
+        "F257" => run::<F257>(&config),
+        _ => usage(),
+    }
+}
 
-    // test with C a different random matrix
-    print!("Testing with C uniform random ... ");
-    io::stdout().flush().unwrap();
-    fill_random(&mut c, &mut rng);
-    r = F::rand(&mut rng);
-    println!("{}", freivald_check(&a, &b, &c, &r));
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
 }
 
-fn freivald_check<T: Field> (a: &Array2<T>, b: &Array2<T>, c: &Array2<T>, r: &T) -> Decision {
-    let mut test_vector = Array1::<T>::default(N);
-    let mut r_power = T::one();
-    for d in test_vector.iter_mut() {
-        *d = r_power;
-        r_power = r_power * r;
+fn run<F: PrimeField>(config: &Config) {
+    let n = config.size;
+    println!("Freivalds' Algorithm");
+    println!("====================");
+    println!("Random {}x{} matrices A and B over {}, with {}", n, n, config.field, config.test_vector);
+    let a = Arc::new(random_matrix::<F>(n, n));
+    let b = Arc::new(random_matrix::<F>(n, n));
+    let c = a.dot(&*b);
+    println!("A =\n{}", Data::Matrix((*a).clone()));
+    println!("B =\n{}", Data::Matrix((*b).clone()));
+
+    println!("\nProver sends C = A*B");
+    let prover = FreivaldsProver { c: Arc::new(c.clone()) };
+    let verifier = FreivaldsVerifier { a: a.clone(), b: b.clone(), test_vector: config.test_vector };
+    ip::execute::<Data<F>>(prover, verifier);
+
+    let mut rng = rand::thread_rng();
+    let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+    println!("\nProver sends C = A*B with entry ({}, {}) modified", u, v);
+    let prover = FreivaldsProver { c: Arc::new(modify_entry(&c, u, v)) };
+    let verifier = FreivaldsVerifier { a: a.clone(), b: b.clone(), test_vector: config.test_vector };
+    ip::execute::<Data<F>>(prover, verifier);
+
+    // false-accept rates for wrong products, with a fresh test vector each
+    // trial; the last claim adds to C a row of coefficients of a polynomial
+    // with as many roots as possible, which fools the powers test most often
+    let claims = [
+        ("one entry modified", modify_entry(&c, u, v)),
+        ("uniformly random C", loop {
+            let random = random_matrix::<F>(n, n);
+            if random != c { break random; }
+        }),
+        ("worst case for powers", worst_case(&c)),
+    ];
+    println!("\nFraction of {} trials accepting a wrong C = A*B + D, for n = {} and |F| = {}:", config.trials, n, small_fields::field_size::<F>());
+    println!("{:>22}  {:>16}  {:>16}", "", "powers of r", "random x");
+    for (name, wrong) in &claims {
+        let rates: Vec<f64> = [TestVector::Powers, TestVector::Random].iter()
+            .map(|test_vector| false_accept_rate(&a, &b, wrong, *test_vector, config.trials))
+            .collect();
+        println!("{:>22}  {:>16.4}  {:>16.4}", name, rates[0], rates[1]);
     }
+    println!("{:>22}  {:>16.4}  {:>16.4}", "bound",
+        TestVector::Powers.error_bound(n, small_fields::field_size::<F>()),
+        TestVector::Random.error_bound(n, small_fields::field_size::<F>()));
+}
 
-    let test_product = c.dot(&test_vector);
-    let actual_product = a.dot(&b.dot(&test_vector));
+fn random_matrix<F: Field>(rows: usize, cols: usize) -> Array2<F> {
+    let mut rng = rand::thread_rng();
+    Array2::from_shape_simple_fn((rows, cols), || F::rand(&mut rng))
+}
 
-    Decision(test_product == actual_product)
+fn modify_entry<F: Field>(c: &Array2<F>, u: usize, v: usize) -> Array2<F> {
+    let mut wrong = c.clone();
+    wrong[(u, v)] += F::one();
+    wrong
 }
 
-fn fill_random<T: Field, D: Dimension> (arr: &mut Array<T, D>, rng: &mut ThreadRng) {
-    for d in arr.iter_mut() {
-        *d = T::rand(rng);
+// C + D with the first row of D the coefficients of prod_s (X - s) over
+// min(n - 1, |F|) distinct s, so that <d, (1, r, ..., r^(n-1))> vanishes at
+// each s
+fn worst_case<F: PrimeField>(c: &Array2<F>) -> Array2<F> {
+    let n = c.ncols();
+    let num_roots = (n as u64 - 1).min(small_fields::field_size::<F>());
+    let mut coeffs = vec![F::one()];
+    for s in 0..num_roots {
+        // multiply by (X - s)
+        let mut next = vec![F::zero(); coeffs.len() + 1];
+        for (k, coeff) in coeffs.iter().enumerate() {
+            next[k + 1] += coeff;
+            next[k] -= *coeff * F::from(s);
+        }
+        coeffs = next;
+    }
+    let mut wrong = c.clone();
+    for (j, coeff) in coeffs.into_iter().enumerate() {
+        wrong[(0, j)] += coeff;
     }
+    wrong
 }
 
-struct Decision(bool);
-
-impl fmt::Display for Decision {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.0 { write!(f, "Accept") } else { write!(f, "Reject") }
-    }
+fn false_accept_rate<F: PrimeField>(a: &Array2<F>, b: &Array2<F>, c: &Array2<F>, test_vector: TestVector, trials: usize) -> f64 {
+    let mut rng = rand::thread_rng();
+    let accepted = (0..trials)
+        .filter(|_| freivalds::check(a, b, c, &test_vector.sample(c.ncols(), &mut rng)))
+        .count();
+    accepted as f64 / trials as f64
 }
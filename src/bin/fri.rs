@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use ark_poly::DenseUVPolynomial;
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use pazk::small_fields::{self, F257 as F};
+use pazk::fri::{self,FriParams,FriProver,FriVerifier,Data};
+use pazk::merkle::{ToyHash,Sha256Hash};
+use pazk::ip;
+use pazk::group_utils;
+
+fn main() {
+    let mut rng = rand::thread_rng();
+
+    let params = FriParams {
+        log_domain_size: 5,
+        log_blowup: 2,
+        num_queries: 2,
+    };
+
+    println!("FRI low-degree test over GF(257)");
+
+    println!();
+
+    println!("Evaluation domain: subgroup of order {} in GF(257)*", params.domain_size());
+    println!("Blowup factor: {}", 1 << params.log_blowup);
+    println!("Degree bound: deg(p) < {}", params.degree_bound());
+    println!("Folding rounds: {}, queries: {}", params.num_rounds(), params.num_queries);
+    println!("Merkle commitments use toy hash with 1 byte digests");
+
+    println!();
+
+    let poly = UniPoly::<F>::rand(params.degree_bound() - 1, &mut rng);
+    println!("Picking random polynomial p of degree {} with coefficients:", params.degree_bound() - 1);
+    let coeffs: Vec<u64> = poly.coeffs.iter().map(|c| small_fields::to_u64(*c)).collect();
+    println!("  {}", group_utils::list_vec(&coeffs, ", "));
+
+    println!();
+    println!("Honest prover");
+    println!("=============");
+    let prover = FriProver { params, poly: Arc::new(poly.clone()) };
+    let verifier = FriVerifier { params };
+    ip::execute::<Data<F, ToyHash>>(prover, verifier);
+
+    let high_degree = 2 * params.degree_bound() - 1;
+    let bad_poly = UniPoly::<F>::rand(high_degree, &mut rng);
+
+    println!();
+    println!("Prover with polynomial of degree {high_degree}");
+    println!("=====================================");
+    let prover = FriProver { params, poly: Arc::new(bad_poly) };
+    let verifier = FriVerifier { params };
+    ip::execute::<Data<F, ToyHash>>(prover, verifier);
+
+    println!();
+    println!("Non-interactive proof using Fiat-Shamir with SHA-256");
+    println!("====================================================");
+    let params = FriParams { num_queries: 8, ..params };
+    println!("Prover derives challenges alpha_i and {} query positions from transcript hash", params.num_queries);
+    let proof = fri::prove::<F, Sha256Hash>(&params, &poly);
+    let (n_scalars, n_digests) = proof.size();
+    println!("  proof size: {n_scalars} field elements and {n_digests} digests");
+    let decision = match fri::verify(&params, &proof) {
+        Ok(()) => String::from("Accept"),
+        Err(message) => format!("Reject ({message})"),
+    };
+    println!("Verifier recomputes challenges and checks proof: {decision}");
+}
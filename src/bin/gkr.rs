@@ -0,0 +1,160 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use ark_ff::PrimeField;
+use ark_std::UniformRand;
+use ark_test_curves::bls12_381::Fr;
+use rand::Rng;
+
+use pazk::small_fields::{F13,F17};
+use pazk::circuit::{Gate,LayeredCircuit};
+use pazk::circuit_parser;
+use pazk::gkr::{self,GkrProver,GkrVerifier};
+use pazk::polynomials as polys;
+use pazk::sum_check::Data;
+use pazk::ip::{self,IP,Channel,Log};
+
+const USAGE: &str = "usage: gkr [--f17] [--circuit FILE INPUT...] [--random DEPTH LOG_WIDTH]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut f17 = false;
+    let mut random = None;
+    let mut file = None;
+    let mut inputs: Vec<i64> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--f17" => f17 = true,
+            "--random" if i + 2 < args.len() => {
+                match (args[i + 1].parse(), args[i + 2].parse()) {
+                    (Ok(depth), Ok(log_width)) if depth > 0 => random = Some((depth, log_width)),
+                    _ => usage(),
+                }
+                i += 2;
+            }
+            "--circuit" if i + 1 < args.len() => {
+                file = Some(args[i + 1].clone());
+                i += 1;
+                while i + 1 < args.len() && !args[i + 1].starts_with("--") {
+                    inputs.push(args[i + 1].parse().unwrap_or_else(|_| usage()));
+                    i += 1;
+                }
+            }
+            _ => usage(),
+        }
+        i += 1;
+    }
+
+    println!("GKR Protocol");
+    println!("============");
+    let circuit = match &file {
+        Some(path) => {
+            let circuit = load(path);
+            println!("Circuit from {}:", path);
+            circuit
+        }
+        None => {
+            inputs = vec![1, 2, 3, 4];
+            println!("Circuit with outputs (x_0 x_1)^2 and (x_1 + x_2) x_3^2:");
+            LayeredCircuit::new(vec![
+                vec![Gate::mul(0, 0), Gate::mul(1, 2)],
+                vec![Gate::mul(0, 1), Gate::add(1, 2), Gate::mul(3, 3)],
+            ], 4)
+        }
+    };
+    println!("{}", circuit);
+    if inputs.len() != circuit.num_inputs {
+        eprintln!("circuit has {} inputs but {} were given", circuit.num_inputs, inputs.len());
+        process::exit(1);
+    }
+    let circuit = Arc::new(circuit);
+    if f17 {
+        println!("Working over GF(17) with inputs {:?}\n", inputs);
+        run_tiny::<F17>(circuit, &inputs);
+    } else {
+        println!("Working over GF(13) with inputs {:?}\n", inputs);
+        run_tiny::<F13>(circuit, &inputs);
+    }
+
+    if let Some((depth, log_width)) = random {
+        run_random(depth, log_width);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+// load a circuit file, which must describe a layered circuit
+fn load(path: &str) -> LayeredCircuit {
+    let input = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        process::exit(1);
+    });
+    let circuit = circuit_parser::parse_circuit::<Fr>(&input).unwrap_or_else(|error| {
+        let (line, column) = error.location(&input);
+        eprintln!("{}:{}:{}: {}", path, line, column, error.message);
+        eprintln!("{}", error.show(&input));
+        process::exit(1);
+    });
+    circuit.to_layered().unwrap_or_else(|error| {
+        eprintln!("{}: circuit is not layered: {}", path, error);
+        process::exit(1);
+    })
+}
+
+fn run_tiny<F: PrimeField + From<i64>>(circuit: Arc<LayeredCircuit>, inputs: &[i64]) {
+    let inputs: Arc<Vec<F>> = Arc::new(inputs.iter()
+        .map(|x| F::from(*x))
+        .collect());
+    let prover = GkrProver { circuit: circuit.clone(), inputs: inputs.clone() };
+    let verifier = GkrVerifier { circuit, inputs };
+    ip::execute::<Data<F>>(prover, verifier);
+}
+
+// random layered circuit of the given depth with 2^log_width gates per layer,
+// run over the BLS12-381 scalar field without printing the transcript
+fn run_random(depth: usize, log_width: usize) {
+    let mut rng = rand::thread_rng();
+    let width = 1 << log_width;
+    let layers = (0..depth)
+        .map(|_| (0..width)
+            .map(|_| {
+                let (left, right) = (rng.gen_range(0..width), rng.gen_range(0..width));
+                if rng.gen() { Gate::add(left, right) } else { Gate::mul(left, right) }
+            })
+            .collect())
+        .collect();
+    let circuit = Arc::new(LayeredCircuit::new(layers, width));
+    let inputs: Arc<Vec<Fr>> = Arc::new((0..width).map(|_| Fr::rand(&mut rng)).collect());
+
+    let title = format!("Random circuit of depth {} and width {} over the BLS12-381 scalar field", depth, width);
+    println!("\n{}", title);
+    println!("{}", "=".repeat(title.len()));
+
+    let start = Instant::now();
+    let values = circuit.evaluate(&inputs);
+    println!("Evaluating the circuit:   {:>12.3?}", start.elapsed());
+
+    let start = Instant::now();
+    let (ch_p, ch_v) = Channel::gen();
+    let prover = GkrProver { circuit: circuit.clone(), inputs: inputs.clone() };
+    let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+    let result = gkr::verify(&circuit, &inputs, &ch_v, &Log::new());
+    handle.join().unwrap();
+    println!("Running the GKR protocol: {:>12.3?}", start.elapsed());
+
+    match result {
+        Ok(outputs) => {
+            assert_eq!(outputs, values[0]);
+            println!("Verifier accepts outputs, of which the first is {}", polys::format_field(outputs[0], false));
+        }
+        Err(failure) => println!("Verifier rejects: {}", failure),
+    }
+}
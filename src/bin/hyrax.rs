@@ -0,0 +1,113 @@
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use ark_std::UniformRand;
+use ark_test_curves::bls12_381::{Fr,G1Projective};
+
+use pazk::small_curves::C17Projective as G;
+use pazk::small_fields::F17 as F;
+use pazk::hyrax::{self,HyraxProver,HyraxVerifier};
+use pazk::multilinear::DenseMLE;
+use pazk::polynomials as polys;
+use pazk::ip::{self,IP,Channel,Log};
+use pazk::group_utils;
+
+const USAGE: &str = "usage: hyrax [--bls12-381 NUM_VARS]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let large = match args.as_slice() {
+        [] => None,
+        [flag, n] if flag == "--bls12-381" => match n.parse() {
+            Ok(n) => Some(n),
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    };
+
+    let mut rng = rand::thread_rng();
+    let num_vars = 4;
+
+    println!("Hyrax commitment to a multilinear polynomial, opened with the Bulletproofs inner product argument");
+    println!();
+    println!("Cryptographic group: y^2 = x^3 + 2x + 4 over GF(13)");
+    println!("Scalar field: GF(17)");
+    println!("Number of variables: {num_vars}");
+    println!();
+
+    println!("Begin setup...");
+    let params = hyrax::setup::<G>(num_vars, &mut rng);
+    println!("Picking random generators (g_j) for the {} columns:", params.gens.len());
+    println!("  {}", group_utils::list_vec(&params.gens, ", "));
+
+    let mle = DenseMLE::<F>::rand(num_vars, &mut rng);
+    println!("Picking random evaluation table, as a {} x {} matrix M:", 1 << params.row_bits(), params.gens.len());
+    for row in mle.evals.chunks(params.gens.len()) {
+        let row: Vec<String> = row.iter().map(|x| format!("{:>2}", polys::format_field(*x, false))).collect();
+        println!("  {}", row.join(" "));
+    }
+    let commitment = hyrax::commit(&params, &mle);
+    println!("Computing commitments C_i = <M_i, g> to the rows:");
+    println!("  {}", group_utils::list_vec(&commitment.rows, ", "));
+
+    let point: Vec<F> = (0..num_vars).map(|_| F::rand(&mut rng)).collect();
+    let value = mle.evaluate(&point);
+    println!("Picking random evaluation point r = {}", polys::format_point(&point));
+    println!("Computing evaluation v = f~(r) = {}", polys::format_field(value, false));
+
+    let params = Arc::new(params);
+    let point = Arc::new(point);
+    let prover = HyraxProver { params: params.clone(), mle: Arc::new(mle), point: point.clone() };
+    let verifier = HyraxVerifier { params, commitment: Arc::new(commitment), point, value };
+    println!();
+    println!("Begin interactive protocol execution...");
+    ip::execute(prover, verifier);
+
+    println!();
+    println!("Sizes for a polynomial in v variables, in group elements G and scalars F:");
+    println!("{:>4}  {:>12}  {:>12}  {:>20}", "v", "table (F)", "commit (G)", "opening (G + F)");
+    for v in [10usize, 16, 20, 24, 30] {
+        let a = v.div_ceil(2);
+        // two commitments and two scalars per round, and the final coefficient
+        println!("{:>4}  {:>12}  {:>12}  {:>20}", v, 1u64 << v, 1u64 << (v - a), format!("{} + {}", 2 * a, 2 * a + 1));
+    }
+
+    if let Some(num_vars) = large {
+        run_large(num_vars);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+// commit and open over the BLS12-381 G1 group, without printing the transcript
+fn run_large(num_vars: usize) {
+    let mut rng = rand::thread_rng();
+    let title = format!("Random polynomial in {} variables over BLS12-381 G1", num_vars);
+    println!("\n{}", title);
+    println!("{}", "=".repeat(title.len()));
+
+    let params = hyrax::setup::<G1Projective>(num_vars, &mut rng);
+    let mle = DenseMLE::<Fr>::rand(num_vars, &mut rng);
+    let point: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+    let value = mle.evaluate(&point);
+
+    let start = Instant::now();
+    let commitment = hyrax::commit(&params, &mle);
+    println!("Committing to the rows:    {:>12.3?}  ({} commitments)", start.elapsed(), commitment.rows.len());
+
+    let start = Instant::now();
+    let (ch_p, ch_v) = Channel::gen();
+    let params = Arc::new(params);
+    let prover = HyraxProver { params: params.clone(), mle: Arc::new(mle), point: Arc::new(point.clone()) };
+    let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+    let decision = hyrax::verify_evaluation(&params, &commitment, &point, value, &ch_v, &Log::new());
+    handle.join().unwrap();
+    println!("Opening at a random point: {:>12.3?}", start.elapsed());
+    println!("Verifier {}", if decision { "accepts" } else { "rejects" });
+}
@@ -0,0 +1,242 @@
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+
+use ark_std::UniformRand;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_poly::DenseUVPolynomial;
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use ark_test_curves::bls12_381::Bls12_381;
+
+use pazk::small_curves::SS67Pairing;
+use pazk::kzg::{self,KzgParams};
+use pazk::ip::{self,IP,Channel,Log};
+use pazk::group_utils;
+
+fn main() {
+    // the toy curve keeps transcripts small enough to check by hand, while
+    // BLS12-381 is a curve used for KZG commitments in practice
+    if env::args().any(|arg| arg == "--bls12-381") {
+        run::<Bls12_381>(&[
+            "Pairing-friendly curve: BLS12-381",
+            "Scalar field: GF(r), r = 0x73eda753...00000001 (255 bits)",
+        ]);
+    } else {
+        run::<SS67Pairing>(&[
+            "Pairing-friendly curve: y^2 = x^3 + x over GF(67), embedding degree 2",
+            "Groups: G1 = G2 = subgroup of order 17, GT = subgroup of order 17 in GF(67^2)*",
+            "Scalar field: GF(17)",
+        ]);
+    }
+}
+
+fn run<E: Pairing>(description: &[&str]) {
+    let mut rng = rand::thread_rng();
+
+    println!("KZG polynomial commitments with single and batched evaluation proofs");
+
+    println!();
+
+    for line in description {
+        println!("{line}");
+    }
+
+    let deg: usize = 3;
+    let n_points: usize = 2;
+    println!("Polynomial degree bound: deg(p) <= {deg}");
+
+    println!();
+
+    // trusted setup
+
+    println!("Begin trusted setup...");
+    let tau = E::ScalarField::rand(&mut rng);
+    println!("Picking toxic waste tau uniformly at random: {}", tau.into_bigint());
+    println!("Computing powers g^(tau^i) in G1 and h^(tau^i) in G2 for i = 0..{deg}");
+    let params = kzg::setup::<E>(deg, tau);
+    println!("  g = {}", params.powers_of_g[0]);
+    println!("  g^tau = {}", params.powers_of_g[1]);
+    println!("Discarding tau; anyone who knows it can open commitments to any value");
+
+    println!();
+
+    // construct polynomial and commitment
+
+    let poly: UniPoly<E::ScalarField> = UniPoly::rand(deg, &mut rng);
+    println!("Picking vector u of random polynomial coefficients:");
+    println!("  {}", Data::<E>::Scalars(poly.coeffs.clone()));
+
+    println!("Computing KZG commitment C = g^p(tau) = prod_i (g^(tau^i))^(u_i)");
+    let commitment = kzg::commit(&params, &poly);
+    println!("  C = {commitment}");
+
+    let params = Arc::new(params);
+    let poly = Arc::new(poly);
+    let commitment = Arc::new(commitment);
+
+    println!();
+    println!("Constructing prover with: SRS; p");
+    let prover = KzgProver::<E> {
+        params: params.clone(),
+        poly: poly.clone(),
+    };
+
+    println!("Constructing verifier with: SRS; C");
+    let verifier = KzgVerifier::<E> {
+        params: params.clone(),
+        commitment: commitment.clone(),
+        n_points,
+    };
+    println!();
+    println!("Begin interactive protocol execution...");
+    ip::execute(prover, verifier);
+}
+
+// PAZK, Section 15.2
+// Prover convinces verifier that the polynomial committed in C evaluates to
+// claimed values, first at a single point and then at several points at once,
+// with each opening proof consisting of a single G1 element.
+
+struct KzgProver<E: Pairing> {
+    params: Arc< KzgParams<E> >,
+    poly: Arc< UniPoly<E::ScalarField> >,
+}
+
+impl<E: Pairing> IP<Data<E>> for KzgProver<E> {
+    fn execute(&self, ch: Channel<Data<E>>, log: Log) {
+        // single point opening
+
+        let point = ch.receive().to_scalar().unwrap();
+
+        log.write(String::from("P computes evaluation v = p(z)"));
+        log.write(String::from("P computes proof pi = g^q(tau) for quotient q(X) = (p(X) - v)/(X - z)"));
+        let (value, proof) = kzg::open(&self.params, &self.poly, point);
+
+        log.write(format!("P --> (v = {}, pi = {proof})", Data::<E>::Scalar(value)));
+        ch.send(Data::Scalar(value));
+        ch.send(Data::Commitment(proof));
+
+        let data = ch.receive();
+        if let Data::Decision(false) = data { return; }
+
+        // batched opening
+
+        let points = ch.receive().to_scalars().unwrap();
+
+        log.write(String::from("P computes evaluations v_i = p(z_i)"));
+        log.write(String::from("P computes proof pi = g^q(tau) for quotient q(X) = (p(X) - I(X))/Z(X)"));
+        log.write(String::from("  where I interpolates (z_i, v_i) and Z(X) = prod_i (X - z_i)"));
+        let (values, proof) = kzg::batch_open(&self.params, &self.poly, &points);
+
+        let data = Data::Scalars(values);
+        log.write(format!("P --> (v_i = {data}, pi = {proof})"));
+        ch.send(data);
+        ch.send(Data::Commitment(proof));
+    }
+}
+
+
+struct KzgVerifier<E: Pairing> {
+    params: Arc< KzgParams<E> >,
+    commitment: Arc< E::G1 >,
+    n_points: usize,
+}
+
+impl<E: Pairing> IP<Data<E>> for KzgVerifier<E> {
+    fn execute(&self, ch: Channel<Data<E>>, log: Log) {
+        let mut rng = rand::thread_rng();
+
+        // single point opening
+
+        log.write(String::from("V picks evaluation point z uniformly at random"));
+        let point = E::ScalarField::rand(&mut rng);
+        let data = Data::Scalar(point);
+        log.write(format!("V --> (z = {data})"));
+        ch.send(data);
+
+        let value = ch.receive().to_scalar().unwrap();
+        let proof = ch.receive().to_commitment().unwrap();
+
+        log.write(String::from("V checks pairing equation e(C * g^-v, h) == e(pi, h^tau * h^-z)"));
+        let decision = kzg::verify(&self.params, *self.commitment, point, value, proof);
+
+        let data = Data::Decision(decision);
+        log.write(format!("V --> ({data})"));
+        ch.send(data);
+        if !decision { return; }
+
+        // batched opening
+
+        log.write(format!("V picks {} distinct evaluation points z_i uniformly at random", self.n_points));
+        let mut points: Vec<E::ScalarField> = Vec::with_capacity(self.n_points);
+        while points.len() < self.n_points {
+            let z = E::ScalarField::rand(&mut rng);
+            if !points.contains(&z) {
+                points.push(z);
+            }
+        }
+        let data = Data::Scalars(points.clone());
+        log.write(format!("V --> (z_i = {data})"));
+        ch.send(data);
+
+        let values = ch.receive().to_scalars().unwrap();
+        let proof = ch.receive().to_commitment().unwrap();
+
+        log.write(String::from("V computes interpolant I(X) of (z_i, v_i) and vanishing polynomial Z(X)"));
+        log.write(String::from("V checks pairing equation e(C * g^-I(tau), h) == e(pi, h^Z(tau))"));
+        let decision = kzg::batch_verify(&self.params, *self.commitment, &points, &values, proof);
+
+        let data = Data::Decision(decision);
+        log.write(format!("V --> ({data})"));
+        ch.send(data);
+    }
+}
+
+
+#[derive(Clone)]
+enum Data<E: Pairing> {
+    Scalar(E::ScalarField),
+    Scalars(Vec<E::ScalarField>),
+    Commitment(E::G1),
+    Decision(bool),
+}
+
+impl<E: Pairing> Data<E> {
+    fn to_scalar(&self) -> Option<E::ScalarField> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
+    }
+
+    fn to_scalars(&self) -> Option<Vec<E::ScalarField>> {
+        if let Data::Scalars(x) = self { Some(x.clone()) } else { None }
+    }
+
+    fn to_commitment(&self) -> Option<E::G1> {
+        if let Data::Commitment(p) = self { Some(*p) } else { None }
+    }
+
+    fn _to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
+    }
+}
+
+impl<E: Pairing> fmt::Display for Data<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Data::Scalar(x) => {
+                write!(f, "{}", x.into_bigint())
+            }
+            Data::Scalars(v) => {
+                let ints: Vec<_> = v.iter().map(|x| x.into_bigint()).collect();
+                write!(f, "{}", group_utils::list_vec(&ints, ", "))
+            }
+            Data::Commitment(g) => {
+                write!(f, "{}", g)
+            }
+            Data::Decision(b) => {
+                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+            }
+        }
+    }
+}
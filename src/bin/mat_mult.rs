@@ -0,0 +1,139 @@
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration,Instant};
+
+use ark_ff::{Field,PrimeField};
+use ark_std::UniformRand;
+use ark_test_curves::bls12_381::Fr;
+use ndarray::Array2;
+use rand::Rng;
+
+use pazk::small_fields::F251;
+use pazk::mat_mult::{self,MatMultProver,MatMultVerifier};
+use pazk::multilinear;
+use pazk::sum_check::Data;
+use pazk::bench;
+use pazk::ip;
+
+const USAGE: &str = "usage: mat_mult [--max-size N]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let max_size = match args.as_slice() {
+        [] => 256,
+        [flag, n] if flag == "--max-size" => n.parse().unwrap_or_else(|_| {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    let mut rng = rand::thread_rng();
+    let a = Arc::new(random_matrix::<F251>(4, 4));
+    let b = Arc::new(random_matrix::<F251>(4, 4));
+    let c = Arc::new(a.dot(&*b));
+
+    println!("MatMult Protocol");
+    println!("================");
+    println!("Random 4x4 matrices A and B over GF(251)");
+    println!("A =\n{}", format_matrix(&a));
+    println!("B =\n{}", format_matrix(&b));
+    println!("C = A*B =\n{}", format_matrix(&c));
+    println!();
+    let r = F251::rand(&mut rng);
+    println!("Freivalds with r = {}: {}", r.into_bigint(), decision(mat_mult::freivalds_check(&a, &b, &c, r)));
+    println!();
+    let prover = MatMultProver { a: a.clone(), b: b.clone() };
+    let verifier = MatMultVerifier { a: a.clone(), b: b.clone(), c: c.clone() };
+    ip::execute::<Data<F251>>(prover, verifier);
+
+    let mut wrong = (*c).clone();
+    let (u, v) = (rng.gen_range(0..4), rng.gen_range(0..4));
+    wrong[(u, v)] += F251::from(1u64);
+    let wrong = Arc::new(wrong);
+    let title = format!("Claimed product C with entry ({}, {}) modified", u, v);
+    println!("\n{}", title);
+    println!("{}", "=".repeat(title.len()));
+    let r = F251::rand(&mut rng);
+    println!("Freivalds with r = {}: {}", r.into_bigint(), decision(mat_mult::freivalds_check(&a, &b, &wrong, r)));
+    println!();
+    let prover = MatMultProver { a: a.clone(), b: b.clone() };
+    let verifier = MatMultVerifier { a, b, c: wrong };
+    ip::execute::<Data<F251>>(prover, verifier);
+
+    println!("\nComparison with Freivalds over the BLS12-381 scalar field");
+    println!(  "=========================================================");
+    println!("Freivalds: V computes C x and A (B x) for x = (1, r, ..., r^(n-1)), with no interaction");
+    println!("           soundness error at most (n - 1)/|F|");
+    println!("MatMult:   V evaluates C~, A~ and B~ at one point each, with log n rounds of sum-check");
+    println!("           soundness error at most 4 log n/|F|, from C~(r_1, r_2) and the sum-check");
+    println!("Neither verifier time includes computing A*B, which P does once in either case");
+    println!();
+    println!("{:>5}  {:>12}  {:>12}  {:>12}  {:>12}  {:>14}", "n", "A*B", "Freivalds V", "MatMult V", "MatMult P", "MatMult comm.");
+    let mut n = 2;
+    while n <= max_size {
+        let a = random_matrix::<Fr>(n, n);
+        let b = random_matrix::<Fr>(n, n);
+        let start = Instant::now();
+        let c = a.dot(&b);
+        let product_time = start.elapsed();
+
+        let start = Instant::now();
+        assert!(mat_mult::freivalds_check(&a, &b, &c, Fr::rand(&mut rng)));
+        let freivalds_time = start.elapsed();
+
+        let (prover_time, verifier_time, field_elements) = time_mat_mult(&a, &b, &c);
+        println!("{:>5}  {:>12.3?}  {:>12.3?}  {:>12.3?}  {:>12.3?}  {:>14}",
+            n, product_time, freivalds_time, verifier_time, prover_time, format!("{} elements", field_elements));
+        n *= 2;
+    }
+}
+
+fn random_matrix<F: Field>(rows: usize, cols: usize) -> Array2<F> {
+    let mut rng = rand::thread_rng();
+    Array2::from_shape_simple_fn((rows, cols), || F::rand(&mut rng))
+}
+
+fn format_matrix<F: PrimeField>(matrix: &Array2<F>) -> String {
+    let rows: Vec<String> = matrix.rows()
+        .into_iter()
+        .map(|row| row.iter().map(|x| format!("{:>4}", x.into_bigint().to_string())).collect())
+        .collect();
+    rows.join("\n")
+}
+
+fn decision(accept: bool) -> &'static str {
+    if accept { "Accept" } else { "Reject" }
+}
+
+// run both parties of the MatMult protocol in a single thread, timing the
+// work of each separately and counting the field elements exchanged
+fn time_mat_mult(a: &Array2<Fr>, b: &Array2<Fr>, c: &Array2<Fr>) -> (Duration, Duration, usize) {
+    let mut rng = rand::thread_rng();
+
+    let start = Instant::now();
+    let r_1: Vec<Fr> = (0..multilinear::index_bits(c.nrows())).map(|_| Fr::rand(&mut rng)).collect();
+    let r_2: Vec<Fr> = (0..multilinear::index_bits(c.ncols())).map(|_| Fr::rand(&mut rng)).collect();
+    let claimed_value = mat_mult::matrix_mle(c).evaluate(&[r_1.as_slice(), &r_2].concat());
+    let mut verifier_time = start.elapsed();
+
+    let start = Instant::now();
+    let poly = mat_mult::mat_mult_polynomial(a, b, &r_1, &r_2);
+    let mut prover_time = start.elapsed();
+
+    let rounds = bench::time_rounds(poly, claimed_value).expect("honest prover fails a round check");
+    prover_time += rounds.prover_time;
+    verifier_time += rounds.verifier_time;
+
+    let start = Instant::now();
+    let oracle = mat_mult::matrix_mle(a).evaluate(&[r_1.as_slice(), &rounds.point].concat())
+        * mat_mult::matrix_mle(b).evaluate(&[rounds.point.as_slice(), &r_2].concat());
+    assert_eq!(oracle, rounds.check_value);
+    verifier_time += start.elapsed();
+
+    (prover_time, verifier_time, r_1.len() + r_2.len() + rounds.field_elements)
+}
@@ -0,0 +1,67 @@
+use ark_std::UniformRand;
+use ark_ff::One;
+
+use pazk::small_fields::{self, F13 as F};
+use pazk::merkle::{MerkleHash,MerkleTree,ToyHash,Sha256Hash};
+use pazk::group_utils;
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let n: usize = 8;
+
+    println!("Merkle tree vector commitments over GF(13)");
+
+    println!();
+
+    let values: Vec<F> = (0..n).map(|_| F::rand(&mut rng)).collect();
+    println!("Committing to vector of {n} random field elements:");
+    let ints: Vec<u64> = values.iter().map(|v| small_fields::to_u64(*v)).collect();
+    println!("  {}", group_utils::list_vec(&ints, ", "));
+
+    println!("Building Merkle tree using toy hash with 1 byte digests:");
+    let tree = MerkleTree::<ToyHash>::new(&values);
+    for level in (0..=tree.depth()).rev() {
+        println!("  level {level}: {}", group_utils::list_vec(tree.layer(level), " "));
+    }
+    let root = tree.root();
+    println!("Commitment is the root digest: {root}");
+
+    println!();
+
+    // single openings
+
+    let index = 5;
+    println!("Opening entry {index} with authentication path of sibling digests, leaf to root:");
+    let path = tree.open(index);
+    println!("  v_{index} = {}, path = {path}", small_fields::to_u64(values[index]));
+    println!("Verifier recomputes root from v_{index} and path: {}", decision(path.verify(&root, &values[index])));
+
+    let forged = values[index] + F::one();
+    println!("Verifier recomputes root from forged v_{index} = {} and path: {}",
+        small_fields::to_u64(forged),
+        decision(path.verify(&root, &forged)));
+
+    println!();
+
+    // batched openings
+
+    let indices = vec![1, 2, 3, 6];
+    let opened: Vec<F> = indices.iter().map(|&i| values[i]).collect();
+    let opened_ints: Vec<u64> = opened.iter().map(|v| small_fields::to_u64(*v)).collect();
+    println!("Opening entries {} with a single batched proof", group_utils::list_vec(&indices, ", "));
+    let proof = tree.open_many(&indices);
+    println!("  values = {}, nodes = {proof}", group_utils::list_vec(&opened_ints, ", "));
+    println!("  {} digests instead of {} for separate paths", proof.nodes.len(), indices.len() * tree.depth());
+    println!("Verifier recomputes root from values and proof: {}", decision(proof.verify(&root, n, &opened)));
+
+    println!();
+
+    println!("The same commitment with SHA-256 in place of the toy hash:");
+    let tree = MerkleTree::<Sha256Hash>::new(&values);
+    println!("  root = {}", tree.root());
+    println!("  leaf digest of v_0 = {}", Sha256Hash::hash_value(&values[0]));
+}
+
+fn decision(b: bool) -> &'static str {
+    if b { "Accept" } else { "Reject" }
+}
@@ -26,7 +26,7 @@ fn main() {
     let commitment: G = group_utils::msm(&gens, &data);
     println!("\nCommitment:");
     let terms: Vec<String> = zip(gens.iter(), data.iter())
-            .map(|(g, d)| format!("{}*{}", d.to_string(), g.to_string()))
+            .map(|(g, d)| format!("{}*{}", d, g))
             .collect();
     let expr: String = terms.join(" + ");
     println!("{}", commitment);
@@ -135,29 +135,29 @@ impl IP<Data> for PedersenProver {
 
         // round 1
 
-        log.write(format!("P picks vector d of field elements uniformly at random"));
+        log.write("P picks vector d of field elements uniformly at random".to_string());
         let d: Vec<F> = (0..vec_len).map(|_| F::rand(&mut rng)).collect();
         log.write(format!("  d = {}", group_utils::list_vec(&d, " ")));
 
-        log.write(format!("P computes commitment to d"));
+        log.write("P computes commitment to d".to_string());
         let r1 = F::rand(&mut rng);
         let com_d = group_utils::msm(&self.gens.vector_gens, &d)
             + (self.gens.blinding_gen * r1);
         log.write(format!("  r1 = {r1}; C_d = Com(d, r1) = {com_d}"));
 
-        log.write(format!("P computes inner product <d,y> of d with public vector"));
+        log.write("P computes inner product <d,y> of d with public vector".to_string());
         let d_ip: F = iter::zip(d.iter(), self.public_vector.iter())
             .map(|(a, y)| a*y)
             .sum();
         log.write(format!("  <d,y> = {d_ip}"));
 
-        log.write(format!("P computes commitment to inner product <d,y>"));
+        log.write("P computes commitment to inner product <d,y>".to_string());
         let r2 = F::rand(&mut rng);
         let com_d_ip = (self.gens.scalar_gen * d_ip)
             + (self.gens.blinding_gen * r2);
         log.write(format!("  r2 = {r2}; C_<d,y> = Com(<d,y>, r2) = {com_d_ip}"));
 
-        log.write(format!("P -> (C_d, C_<d,y>)"));
+        log.write("P -> (C_d, C_<d,y>)".to_string());
         ch.send(Data::Commitment(com_d));
         ch.send(Data::Commitment(com_d_ip));
 
@@ -168,7 +168,7 @@ impl IP<Data> for PedersenProver {
 
         // round 2
 
-        log.write(format!("P computes random vector u' = e*u + d directly"));
+        log.write("P computes random vector u' = e*u + d directly".to_string());
         let rand_coeffs: Vec<F> = self.coeffs.iter()
             .map(|x| e*x)
             .zip(d)
@@ -176,12 +176,12 @@ impl IP<Data> for PedersenProver {
             .collect();
         log.write(format!("  u' = {}", group_utils::list_vec(&rand_coeffs, " ")));
 
-        log.write(format!("P computes derived blinding factors of derived commitments for u' and <u',y>"));
+        log.write("P computes derived blinding factors of derived commitments for u' and <u',y>".to_string());
         let rand_blinding_factor = *self.coeffs_blinding_factor * e + r1;
         let rand_ip_blinding_factor = *self.ip_blinding_factor * e + r2;
         log.write(format!("  r_u' = {rand_blinding_factor}; r_<u',y> = {rand_ip_blinding_factor}"));
 
-        log.write(format!("P -> (u', r_u', r_<u',y>)"));
+        log.write("P -> (u', r_u', r_<u',y>)".to_string());
         ch.send(Data::Vector(rand_coeffs));
         ch.send(Data::Scalar(rand_blinding_factor));
         ch.send(Data::Scalar(rand_ip_blinding_factor));
@@ -212,7 +212,7 @@ impl IP<Data> for PedersenVerifier {
 
         // round 1
 
-        log.write(format!("V picks e uniformly at random"));
+        log.write("V picks e uniformly at random".to_string());
         let e = F::rand(&mut rng);
 
         let data = Data::Scalar(e);
@@ -232,13 +232,13 @@ impl IP<Data> for PedersenVerifier {
 
         // compute decision
 
-        log.write(format!("V computes inner product of u' and public vector directly"));
+        log.write("V computes inner product of u' and public vector directly".to_string());
         let rand_ip: F = iter::zip(rand_coeffs.iter(), self.public_vector.iter())
             .map(|(a, y)| a*y)
             .sum();
         log.write(format!("  <u',y> = {rand_ip}"));
 
-        log.write(format!("V computes commitments to u' and <u', y> directly"));
+        log.write("V computes commitments to u' and <u', y> directly".to_string());
         let com_rc = group_utils::msm(&self.gens.vector_gens, &rand_coeffs)
             + (self.gens.blinding_gen * rand_blinding_factor);
         let com_rc_ip = (self.gens.scalar_gen * rand_ip)
@@ -246,13 +246,13 @@ impl IP<Data> for PedersenVerifier {
         log.write(format!("  C_u' = Com(u', r_u') = {com_rc}"));
         log.write(format!("  C_<u',y> = Com(<u',y>, r_<u',y>) = {com_rc_ip}"));
 
-        log.write(format!("V derives commitments to random vector and inner product using additive homomorphism"));
+        log.write("V derives commitments to random vector and inner product using additive homomorphism".to_string());
         let com_rc_computed = *self.coeffs_commitment * e + com_d;
         let com_rc_ip_computed = *self.ip_commitment * e + com_d_ip;
         log.write(format!("  C_u'* = e*C_u + C_d = {com_rc_computed}"));
         log.write(format!("  C_<u',y>* = e*C_<u,y> + C_<d,y> = {com_rc_ip_computed}"));
 
-        log.write(format!("V checks that directly computed commitments match derived commitments"));
+        log.write("V checks that directly computed commitments match derived commitments".to_string());
         let decision = com_rc == com_rc_computed && com_rc_ip == com_rc_ip_computed;
 
         let data = Data::Decision(decision);
@@ -273,20 +273,20 @@ enum Data {
 }
 
 impl Data {
-    fn to_scalar(self) -> Option<F> {
-        if let Data::Scalar(x) = self { Some(x) } else { None }
+    fn to_scalar(&self) -> Option<F> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
     }
 
-    fn to_vector(self) -> Option<Vec<F>> {
-        if let Data::Vector(x) = self { Some(x) } else { None }
+    fn to_vector(&self) -> Option<Vec<F>> {
+        if let Data::Vector(x) = self { Some(x.clone()) } else { None }
     }
 
-    fn to_commitment(self) -> Option<G> {
-        if let Data::Commitment(p) = self { Some(p) } else { None }
+    fn to_commitment(&self) -> Option<G> {
+        if let Data::Commitment(p) = self { Some(*p) } else { None }
     }
 
-    fn _to_decision(self) -> Option<bool> {
-        if let Data::Decision(d) = self { Some(d) } else { None }
+    fn _to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
     }
 }
 
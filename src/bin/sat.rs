@@ -0,0 +1,76 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+
+use ark_ff::PrimeField;
+use ark_test_curves::bls12_381::Fr;
+
+use pazk::small_fields::F251;
+use pazk::poly_parser::DEFAULT_VARIABLES;
+use pazk::sat::{self,Formula,SatProver,SatVerifier};
+use pazk::sum_check::Data;
+use pazk::ip;
+
+const USAGE: &str = "usage: sat [FORMULA | --dimacs FILE]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (formula, num_vars) = match args.as_slice() {
+        [] => parse_or_exit("(x | y | !z) & (!x | z) & (y | z)"),
+        [flag, path] if flag == "--dimacs" => {
+            let input = fs::read_to_string(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            });
+            sat::parse_dimacs(&input).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            })
+        }
+        [input] if !input.starts_with("--") => parse_or_exit(input),
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+    if num_vars == 0 {
+        eprintln!("formula must have at least one variable");
+        process::exit(1);
+    }
+    if num_vars >= 64 {
+        eprintln!("formula must have fewer than 64 variables to count its satisfying assignments");
+        process::exit(1);
+    }
+
+    println!("#SAT Protocol");
+    println!("=============");
+    println!("Formula phi = {} in {} variables", formula, num_vars);
+    println!("Degree of arithmetization in each variable at most {:?}", formula.occurrences(num_vars));
+
+    // the count is at most 2^v, which must be smaller than the field size to
+    // be determined by its residue
+    let formula = Arc::new(formula);
+    if num_vars <= 251u64.ilog2() as usize {
+        println!("Working over GF(251)");
+        run::<F251>(formula, num_vars);
+    } else {
+        println!("Working over the BLS12-381 scalar field, as 2^{} exceeds 251", num_vars);
+        run::<Fr>(formula, num_vars);
+    }
+}
+
+fn parse_or_exit(input: &str) -> (Formula, usize) {
+    let formula = sat::parse_formula(input, &DEFAULT_VARIABLES).unwrap_or_else(|error| {
+        eprintln!("{}", error.show(input));
+        process::exit(1);
+    });
+    let num_vars = formula.num_vars();
+    (formula, num_vars)
+}
+
+fn run<F: PrimeField>(formula: Arc<Formula>, num_vars: usize) {
+    let prover = SatProver { formula: formula.clone(), num_vars };
+    let verifier = SatVerifier { formula, num_vars };
+    ip::execute::<Data<F>>(prover, verifier);
+}
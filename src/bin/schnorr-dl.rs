@@ -68,7 +68,7 @@ impl IP<Data> for SchnorrDLProver {
         let a = *self.g * r;
         log.write(format!("P computes a = g^r = {a}"));
 
-        log.write(format!("P -> a"));
+        log.write("P -> a".to_string());
         ch.send( Data::GroupElement(a) );
 
         // wait for verifier response
@@ -80,7 +80,7 @@ impl IP<Data> for SchnorrDLProver {
         let z = *self.w * e + r;
         log.write(format!("P computes exponent z = w*e + r = {z}"));
 
-        log.write(format!("P -> z"));
+        log.write("P -> z".to_string());
         ch.send( Data::Scalar(z) );
 
         // execution complete
@@ -106,7 +106,7 @@ impl IP<Data> for SchnorrDLVerifier {
         let e: F = F::rand(&mut rng);
         log.write(format!("V picks random exponent e = {e} from F"));
 
-        log.write(format!("V -> e"));
+        log.write("V -> e".to_string());
         ch.send( Data::Scalar(e) );
 
         // wait for Prover response
@@ -115,7 +115,7 @@ impl IP<Data> for SchnorrDLVerifier {
 
         // compute decision
 
-        log.write(format!("V checks that a*h^e == g^z"));
+        log.write("V checks that a*h^e == g^z".to_string());
         let decision = a + *self.h * e == *self.g * z;
 
         let data = Data::Decision(decision);
@@ -135,16 +135,16 @@ enum Data {
 }
 
 impl Data {
-    fn to_scalar(self) -> Option<F> {
-        if let Data::Scalar(x) = self { Some(x) } else { None }
+    fn to_scalar(&self) -> Option<F> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
     }
 
-    fn to_group_element(self) -> Option<G> {
-        if let Data::GroupElement(p) = self { Some(p) } else { None }
+    fn to_group_element(&self) -> Option<G> {
+        if let Data::GroupElement(p) = self { Some(*p) } else { None }
     }
 
-    fn _to_decision(self) -> Option<bool> {
-        if let Data::Decision(d) = self { Some(d) } else { None }
+    fn _to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
     }
 }
 
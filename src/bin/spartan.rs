@@ -0,0 +1,129 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+
+use ark_ff::PrimeField;
+use ark_test_curves::bls12_381::Fr;
+use ndarray::Array2;
+
+use pazk::small_fields::F13;
+use pazk::circuit::Circuit;
+use pazk::circuit_parser;
+use pazk::r1cs::{self,R1CS};
+use pazk::spartan::{self,SpartanProver,SpartanVerifier};
+use pazk::polynomials as polys;
+use pazk::sum_check::Data;
+use pazk::ip::{self,IP,Channel,Log};
+
+const USAGE: &str = "usage: spartan [--circuit FILE INPUT...]";
+
+// x^3 + x + 5, the usual first example of an R1CS
+const DEFAULT_CIRCUIT: &str = "\
+input x
+five = const 5
+cube = mul x x x
+y = add cube x five
+output y
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (name, text, values): (String, String, Vec<i64>) = match args.as_slice() {
+        [] => (String::from("x^3 + x + 5"), String::from(DEFAULT_CIRCUIT), vec![3]),
+        [flag, path, inputs @ ..] if flag == "--circuit" => {
+            let text = fs::read_to_string(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            });
+            let inputs = inputs.iter()
+                .map(|arg| arg.parse::<i64>().unwrap_or_else(|_| usage()))
+                .collect();
+            (path.clone(), text, inputs)
+        }
+        _ => usage(),
+    };
+
+    println!("Spartan-style Argument for R1CS");
+    println!("===============================");
+    let circuit = load::<F13>(&name, &text);
+    let r1cs = R1CS::from_circuit(&circuit);
+    let inputs: Vec<F13> = values.iter().map(|x| field_element(*x)).collect();
+    if inputs.len() != circuit.num_inputs() {
+        eprintln!("circuit has {} inputs but {} were given", circuit.num_inputs(), inputs.len());
+        process::exit(1);
+    }
+    let (public, witness) = r1cs::circuit_assignment(&circuit, &inputs);
+
+    println!("R1CS for {} with {} constraints over z = (1, x, w), with {} public inputs and {} witness entries",
+        name, r1cs.num_constraints(), r1cs.num_public, r1cs.num_witness);
+    if r1cs.num_variables() <= 16 {
+        for (label, matrix) in [("A", &r1cs.a), ("B", &r1cs.b), ("C", &r1cs.c)] {
+            println!("\n{} =\n{}", label, format_matrix(&matrix.to_dense()));
+        }
+    }
+    println!("\nx = {}", polys::format_point(&public));
+    println!("w = {}", polys::format_point(&witness));
+    println!("Working over GF(13), with the witness extension W~ given to V as an oracle\n");
+
+    let oracle = Arc::new(spartan::witness_mle(&r1cs, &witness));
+    let r1cs = Arc::new(r1cs);
+    let public = Arc::new(public);
+    let prover = SpartanProver { r1cs: r1cs.clone(), public: public.clone(), witness: Arc::new(witness) };
+    let verifier = SpartanVerifier { r1cs, public, witness: oracle };
+    ip::execute::<Data<F13>>(prover, verifier);
+
+    // a witness violating one constraint, over a large field and without
+    // printing the transcript
+    println!("\nProver with a modified witness, over the BLS12-381 scalar field");
+    let circuit = load::<Fr>(&name, &text);
+    let r1cs = R1CS::from_circuit(&circuit);
+    let inputs: Vec<Fr> = values.iter().map(|x| field_element(*x)).collect();
+    let (public, mut witness) = r1cs::circuit_assignment(&circuit, &inputs);
+    if witness.is_empty() {
+        println!("The R1CS has no witness entries to change");
+        return;
+    }
+    witness[0] += Fr::from(1u64);
+    if let Err(failure) = r1cs.is_satisfied(&public, &witness) {
+        println!("R1CS check: {}", failure);
+    }
+    let oracle = spartan::witness_mle(&r1cs, &witness);
+    let r1cs = Arc::new(r1cs);
+    let (ch_p, ch_v) = Channel::gen();
+    let prover = SpartanProver { r1cs: r1cs.clone(), public: Arc::new(public.clone()), witness: Arc::new(witness) };
+    let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+    let result = spartan::verify(&r1cs, &public, |point| oracle.evaluate(point), &ch_v, &Log::new());
+    handle.join().unwrap();
+    match result {
+        Ok(()) => println!("Verifier accepts"),
+        Err(failure) => println!("Verifier rejects: {}", failure),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+fn load<F: PrimeField>(name: &str, text: &str) -> Circuit<F> {
+    circuit_parser::parse_circuit(text).unwrap_or_else(|error| {
+        let (line, column) = error.location(text);
+        eprintln!("{}:{}:{}: {}", name, line, column, error.message);
+        eprintln!("{}", error.show(text));
+        process::exit(1);
+    })
+}
+
+fn field_element<F: PrimeField>(x: i64) -> F {
+    if x < 0 { -F::from(x.unsigned_abs()) } else { F::from(x as u64) }
+}
+
+fn format_matrix<F: PrimeField>(matrix: &Array2<F>) -> String {
+    matrix.rows()
+        .into_iter()
+        .map(|row| row.iter().map(|e| format!("{:>3}", polys::format_field(*e, true))).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
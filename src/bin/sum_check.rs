@@ -1,196 +1,255 @@
-use std::fmt;
-use std::iter::Iterator;
+use std::env;
 use std::sync::Arc;
+use std::process;
+use std::thread;
 
-use rand;
-
-use ark_ff::{Zero,One,UniformRand};
-use ark_poly::Polynomial;
+use ark_ff::{Field,PrimeField,Zero};
+use ark_poly::{Polynomial,DenseUVPolynomial};
 use ark_poly::polynomial::multivariate::{SparsePolynomial as MultiPoly, SparseTerm};
 use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
 
-use pazk::small_fields as sf;
 use pazk::small_fields::{F13 as Fq};
-use pazk::polynomials as polys;
+use pazk::poly_parser;
+use pazk::polynomials::{self as polys,PolyFormat};
+use pazk::sum_check::{self,SumCheckPolynomial,SumCheckProver,SumCheckVerifier,SumCheckFailure,BruteForcePolynomial,MessageEncoding,Data};
 use pazk::ip;
 use pazk::ip::{IP,Channel,Log};
 
+const TRIALS: usize = 1000;
+const USAGE: &str = "usage: sum_check [--evaluations] [--brute-force] [--latex] [POLYNOMIAL [CLAIMED_SUM]]";
+const FLAGS: [&str; 3] = ["--evaluations", "--brute-force", "--latex"];
+
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (flags, positional): (Vec<&String>, Vec<&String>) = args.iter().partition(|arg| arg.starts_with("--"));
+    if positional.len() > 2 || flags.iter().any(|f| !FLAGS.contains(&f.as_str())) {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    }
+
     // f(x, y) = x^2 + 2xy + 3y^2
     // --> 2x^2 + 2x + 3
     // --> 10
-    let poly_spec =
-        vec![
-            (1, vec![(0, 2)]),
-            (2, vec![(0, 1), (1, 1)]),
-            (3, vec![(1, 2)]),
-        ];
-    let polynomial = Arc::new(polys::construct_poly(2, poly_spec));
-    let claimed_sum = Fq::from(10u64);
-    let degrees = polys::variable_degrees(&polynomial);
+    let input = positional.first().map_or("x^2 + 2x y + 3y^2", |s| s.as_str());
+    let polynomial: MultiPoly<Fq, SparseTerm> = parse_or_exit(input);
+    if polynomial.num_vars == 0 {
+        eprintln!("polynomial must have at least one variable");
+        process::exit(1);
+    }
+    let claimed_sum = match positional.get(1) {
+        Some(input) => {
+            let sum = parse_or_exit(input);
+            if sum.num_vars > 0 {
+                eprintln!("claimed sum must be a constant");
+                process::exit(1);
+            }
+            sum.evaluate(&vec![])
+        }
+        None => polynomial.hypercube_sum(),
+    };
+
+    // prover sends evaluations g_j(0), ..., g_j(d) instead of coefficients
+    let encoding = if flags.iter().any(|arg| *arg == "--evaluations") {
+        MessageEncoding::Evaluations
+    } else {
+        MessageEncoding::Coefficients
+    };
 
     println!("Sum Check Protocol");
     println!("==================");
+    println!("Polynomial g = {} over GF(13) in {} variables", input, polynomial.num_vars);
+    let names = poly_parser::variable_names(input, &poly_parser::DEFAULT_VARIABLES).unwrap();
+    let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    println!("  expanded: g = {}", polys::format_multi_poly(&polynomial, &names));
+    if flags.iter().any(|arg| *arg == "--latex") {
+        let latex = PolyFormat { latex: true, ..PolyFormat::new(&names) };
+        println!("  LaTeX: g = {}", latex.multivariate(&polynomial));
+    }
+    println!("Claimed sum over the hypercube: {}", claimed_sum.into_bigint());
+    // prover evaluates g at every point instead of using partial summation
+    if flags.iter().any(|arg| *arg == "--brute-force") {
+        println!("Prover computes messages by brute force evaluation of g");
+        run(BruteForcePolynomial::new(polynomial.clone()), encoding, claimed_sum);
+    } else {
+        run(polynomial.clone(), encoding, claimed_sum);
+    }
+
+    let polynomial = Arc::new(polynomial);
+    let false_sum = claimed_sum + Fq::from(1u64);
+
+    // h = g + 3x^2 - 3x agrees with g on the hypercube
+    let other = Arc::new(&*polynomial + &parse_or_exit("3x^2 - 3x"));
+
+    heading(&format!("Lying prover claiming sum {}", false_sum.into_bigint()));
+    let lying_prover = LyingProver { polynomial: polynomial.clone(), claimed_sum: false_sum };
+    let verifier = SumCheckVerifier { polynomial: polynomial.clone(), claimed_sum: false_sum };
+    ip::execute(lying_prover, verifier);
+
+    println!("\nProver sending polynomials of too high degree");
+    println!(  "=============================================");
+    let high_degree_prover = HighDegreeProver { polynomial: polynomial.clone() };
+    let verifier = SumCheckVerifier { polynomial: polynomial.clone(), claimed_sum };
+    ip::execute(high_degree_prover, verifier);
+
+    heading("Prover using h = g + 3x^2 - 3x in place of g");
+    let other_prover = SumCheckProver { polynomial: other.clone(), encoding };
+    let verifier = SumCheckVerifier { polynomial: polynomial.clone(), claimed_sum };
+    ip::execute::<Data<Fq>>(other_prover, verifier);
+
+    // soundness error is at most d * v / |F| for total degree d in v variables
+    let degrees = polynomial.variable_degrees();
+    let bound = degrees.iter().sum::<usize>();
+    heading(&format!("Cheating prover statistics over {} trials", TRIALS));
+    println!("Soundness error is at most sum_j deg_j(g) / |F| = {}/13 = {:.1}%", bound, 100.0 * bound as f64 / 13.0);
+    println!();
+    statistics("Lying prover", TRIALS, &polynomial, false_sum, || {
+        LyingProver { polynomial: polynomial.clone(), claimed_sum: false_sum }
+    });
+    statistics("Prover using h in place of g", TRIALS, &polynomial, claimed_sum, || {
+        SumCheckProver { polynomial: other.clone(), encoding }
+    });
+}
+
+fn parse_or_exit(input: &str) -> MultiPoly<Fq, SparseTerm> {
+    poly_parser::parse_multi_poly(input, &poly_parser::DEFAULT_VARIABLES).unwrap_or_else(|error| {
+        eprintln!("{}", error.show(input));
+        process::exit(1);
+    })
+}
+
+fn heading(title: &str) {
+    println!("\n{}", title);
+    println!("{}", "=".repeat(title.len()));
+}
+
+fn run<P: SumCheckPolynomial<Fq> + 'static>(polynomial: P, encoding: MessageEncoding, claimed_sum: Fq) {
+    let polynomial = Arc::new(polynomial);
     let prover = SumCheckProver {
         polynomial: polynomial.clone(),
+        encoding,
     };
     let verifier = SumCheckVerifier {
         polynomial: polynomial.clone(),
         claimed_sum,
-        degrees,
     };
-    ip::execute(prover, verifier);
-}
-
-struct SumCheckProver {
-    polynomial: Arc< MultiPoly<Fq, SparseTerm> >,
+    ip::execute::<Data<Fq>>(prover, verifier);
 }
 
-impl IP<Data> for SumCheckProver {
-    fn execute(&self, ch: Channel<Data>, log: Log) {
-        let num_vars = self.polynomial.num_vars;
-        let mut poly = self.polynomial.clone();
-
-        for j in (0..num_vars).rev() {
-            // compute univariate restriction
-            log.write(format!("P computes univariate polynomial g_{}", j));
-            let vals = (0..num_vars)
-                .map(|n| {
-                    if n < j {
-                        Some(vec![Fq::zero(), Fq::one()])
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            let partial = polys::partial_summation(&poly, &vals);
-            let uni = polys::into_univariate(&partial, j);
-
-            // send univariate restriction to verifier
-            let data = Data::Polynomial(uni);
-            log.write(format!("P --> (g_{} = {})", j, data));
-            ch.send(data);
-
-            // wait for random challenge, except for last challenge
-            if j > 0 {
-                let data = ch.receive();
-                if let Data::Decision(false) = data { return; }
-                let challenge = data.to_scalar().unwrap();
-
-                // restrict according to random challenge
-                log.write(format!("P computes partial evaluation at x_{} = r_{}", j, j));
-                poly = Arc::new(polys::partial_eval(&poly, challenge, j));
-            }
+// run the protocol repeatedly without printing transcripts, and tally which
+// check caught the prover, or how often the prover got lucky
+fn statistics<P, G>(name: &str, trials: usize, polynomial: &Arc<G>, claimed_sum: Fq, prover: impl Fn() -> P)
+where
+    P: IP<Data<Fq>> + Send + 'static,
+    G: SumCheckPolynomial<Fq>,
+{
+    let mut tally: Vec<(String, usize)> = Vec::new();
+    for _ in 0..trials {
+        let (ch_p, ch_v) = Channel::gen();
+        let prover = prover();
+        let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+        let outcome = match sum_check::verify(&**polynomial, claimed_sum, &ch_v, &Log::new()) {
+            Ok(()) => String::from("accepted"),
+            Err(SumCheckFailure::Degree { round, .. }) => format!("rejected by degree check on g_{}", round),
+            Err(SumCheckFailure::Encoding { round, .. }) => format!("rejected by encoding check on g_{}", round),
+            Err(SumCheckFailure::Sum { round, .. }) => format!("rejected by sum check on g_{}", round),
+            Err(SumCheckFailure::Oracle { .. }) => String::from("rejected by oracle check"),
+            Err(SumCheckFailure::Malformed { round }) => format!("rejected malformed g_{}", round),
+        };
+        handle.join().unwrap();
+        match tally.iter_mut().find(|(o, _)| *o == outcome) {
+            Some((_, count)) => *count += 1,
+            None => tally.push((outcome, 1)),
         }
     }
+    tally.sort();
+    println!("{}:", name);
+    for (outcome, count) in tally {
+        println!("  {:<32} {:>4} ({:.1}%)", outcome, count, 100.0 * count as f64 / trials as f64);
+    }
 }
 
-struct SumCheckVerifier {
-    polynomial: Arc< MultiPoly<Fq, SparseTerm> >,
-    degrees: Vec<usize>,
+// Prover for a false claimed sum, which lies consistently: in each round where
+// its check value is wrong it sends g_j + D_j, where D_j sums to the error over
+// {0,1} and has as many roots as the degree bound allows.  If the verifier's
+// challenge hits a root of D_j, the check value becomes correct again and the
+// prover is honest from then on.
+struct LyingProver<P> {
+    polynomial: Arc<P>,
     claimed_sum: Fq,
 }
 
-impl IP<Data> for SumCheckVerifier {
-    fn execute(&self, ch: Channel<Data>, log: Log) {
-        let mut rng = rand::thread_rng();
-
-        let zero = Fq::zero();
-        let one  = Fq::one();
-
-        // let current check value equal to claimed sum
+impl<P: SumCheckPolynomial<Fq>> IP<Data<Fq>> for LyingProver<P> {
+    fn execute(&self, ch: Channel<Data<Fq>>, log: Log) {
+        let mut poly = (*self.polynomial).clone();
         let mut check_value = self.claimed_sum;
-        let mut challenges: Vec<Fq> = Vec::with_capacity(self.polynomial.num_vars);
-
-        for j in (0..self.polynomial.num_vars).rev() {
-            // wait for univariate restriction
-            let uni = ch.receive().to_polynomial().unwrap();
-
-            log.write(format!("V checks g_{} has small enough degree", j));
-            if uni.degree() > self.degrees[j] {
-                let data = Data::Decision(false);
-                log.write(format!("V --> ({})", data));
-                ch.send(data);
-                return;
-            }
-
-            log.write(format!("V checks g_{} sums to check value", j));
-            if uni.evaluate(&zero) + uni.evaluate(&one) != check_value {
-                let data = Data::Decision(false);
-                log.write(format!("V --> ({})", data));
-                ch.send(data);
-                return;
-            }
 
-            log.write(format!("V picks r_{} uniformly at random", j));
-            let challenge = Fq::rand(&mut rng);
-
-            log.write(format!("V updates check value to g_{}(r_{})", j, j));
-            check_value = uni.evaluate(&challenge);
-
-            // record challenge for later reference
-            challenges.push(challenge);
+        for j in (0..poly.num_vars()).rev() {
+            let g = poly.round_polynomial();
+            let error = check_value - g.evaluate(&Fq::from(0u64)) - g.evaluate(&Fq::from(1u64));
+            let message = if error.is_zero() {
+                log.write(format!("P check value is correct, so P sends true g_{}", j));
+                g
+            } else {
+                let d = poly.variable_degrees()[j];
+                log.write(format!("P adds to g_{} a polynomial of degree {} summing to the error {}", j, d, error.into_bigint()));
+                &g + &lie_polynomial(d, error)
+            };
+            let data = Data::Polynomial(message.clone());
+            log.write(format!("P --> (g_{} = {})", j, data));
+            ch.send(data);
 
-            // send random challenge to prover, except for last challenge
-            let data = Data::Scalar(challenge);
             if j > 0 {
-                log.write(format!("V --> (r_{} = {})", j, data));
-                ch.send(data);
-            } else {
-                log.write(format!("V has (r_0 = {}) but does not send to P", data));
+                let data = ch.receive();
+                if let Data::Decision(false) = data { return; }
+                let challenge = data.to_scalar().unwrap();
+                check_value = message.evaluate(&challenge);
+                poly.fix_last_variable(challenge);
             }
         }
-
-        // evaluate polynomial at vector of challenge points
-        log.write(String::from("V evaluates g(r) with a single oracle query"));
-        challenges.reverse();
-        let oracle_evaluation = self.polynomial.evaluate(&challenges);
-
-        // accept if the oracle evaluation equals the final check value
-        // otherwise reject
-        log.write(String::from("V checks that oracle evaluation equals final check value"));
-        let decision = Data::Decision(oracle_evaluation == check_value);
-
-        log.write(format!("V --> ({})", decision));
-        ch.send(Data::Decision(oracle_evaluation == check_value));
     }
 }
 
-// TODO implement brute force prover which works for small fields
-
-
-#[derive(Clone)]
-enum Data {
-    Scalar(Fq),
-    Polynomial(UniPoly<Fq>),
-    Decision(bool),
-}
-
-impl Data {
-    fn to_scalar(self) -> Option<Fq> {
-        if let Data::Scalar(x) = self { Some(x) } else { None }
+// polynomial D of degree d with D(0) + D(1) = error, vanishing at 2, ..., d+1
+fn lie_polynomial(d: usize, error: Fq) -> UniPoly<Fq> {
+    let roots: Vec<Fq> = (2..d as u64 + 2).map(Fq::from).collect();
+    let mut p = UniPoly::from_coefficients_vec(vec![Fq::from(1u64)]);
+    for root in roots {
+        p = p.naive_mul(&UniPoly::from_coefficients_vec(vec![-root, Fq::from(1u64)]));
     }
-
-    fn to_polynomial(self) -> Option<UniPoly<Fq>> {
-        if let Data::Polynomial(p) = self { Some(p) } else { None }
+    let mut sum = p.evaluate(&Fq::from(0u64)) + p.evaluate(&Fq::from(1u64));
+    if sum.is_zero() {
+        // fall back to D(X) = error * X
+        p = UniPoly::from_coefficients_vec(vec![Fq::from(0u64), Fq::from(1u64)]);
+        sum = Fq::from(1u64);
     }
+    &p * (error * sum.inverse().unwrap())
+}
 
-    fn _to_decision(self) -> Option<bool> {
-        if let Data::Decision(d) = self { Some(d) } else { None }
-    }
+// Prover which adds X^(d+1) (X - 1) to each message, which vanishes on {0,1}
+// and so doesn't change the sum, but exceeds the degree bound d.
+struct HighDegreeProver<P> {
+    polynomial: Arc<P>,
 }
 
-impl fmt::Display for Data {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Data::Scalar(x) => {
-                write!(f, "{}", sf::to_u64(*x))
-            }
-            Data::Polynomial(p) => {
-                write!(f, "{}", polys::format_univ_poly(&p, "x"))
-            }
-            Data::Decision(b) => {
-                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+impl<P: SumCheckPolynomial<Fq>> IP<Data<Fq>> for HighDegreeProver<P> {
+    fn execute(&self, ch: Channel<Data<Fq>>, log: Log) {
+        let mut poly = (*self.polynomial).clone();
+
+        for j in (0..poly.num_vars()).rev() {
+            let d = poly.variable_degrees()[j];
+            log.write(format!("P adds x^{} (x - 1) to g_{}", d + 1, j));
+            let mut coeffs = vec![Fq::from(0u64); d + 3];
+            coeffs[d + 1] = -Fq::from(1u64);
+            coeffs[d + 2] = Fq::from(1u64);
+            let message = &poly.round_polynomial() + &UniPoly::from_coefficients_vec(coeffs);
+            let data = Data::Polynomial(message);
+            log.write(format!("P --> (g_{} = {})", j, data));
+            ch.send(data);
+
+            if j > 0 {
+                let data = ch.receive();
+                if let Data::Decision(false) = data { return; }
+                poly.fix_last_variable(data.to_scalar().unwrap());
             }
         }
     }
@@ -0,0 +1,108 @@
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use ark_ff::UniformRand;
+use ark_test_curves::bls12_381::{Bls12_381,Fr};
+
+use pazk::small_curves::SS67Pairing as E;
+use pazk::small_fields::F17 as F;
+use pazk::multilinear_kzg;
+use pazk::multilinear::ProductMLE;
+use pazk::sum_check::SumCheckPolynomial;
+use pazk::sum_check_argument::{self,Message,SumCheckArgumentProver,SumCheckArgumentVerifier};
+use pazk::polynomials as polys;
+use pazk::ip::{self,IP,Channel,Log};
+
+const USAGE: &str = "usage: sum_check_argument [--bls12-381 NUM_VARS]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let large = match args.as_slice() {
+        [] => None,
+        [flag, n] if flag == "--bls12-381" => match n.parse() {
+            Ok(n) => Some(n),
+            Err(_) => usage(),
+        },
+        _ => usage(),
+    };
+
+    let mut rng = rand::thread_rng();
+    let num_vars = 3;
+    let num_factors = 2;
+
+    println!("Succinct argument from sum-check with multilinear KZG polynomial commitments");
+    println!();
+    println!("Pairing-friendly curve: y^2 = x^3 + x over GF(67), embedding degree 2");
+    println!("Groups: G1 = G2 = subgroup of order 17, GT = subgroup of order 17 in GF(67^2)*");
+    println!("Scalar field: GF(17)");
+    println!("Polynomial: g = f_1 f_2 for random multilinear f_1, f_2 in {num_vars} variables");
+    println!();
+
+    let polynomial = ProductMLE::<F>::rand(num_vars, num_factors, &mut rng);
+    for (i, factor) in polynomial.factors.iter().enumerate() {
+        let table: Vec<String> = factor.evals.iter().map(|x| polys::format_field(*x, false)).collect();
+        println!("f_{} evaluation table: {}", i + 1, table.join(" "));
+    }
+    let claimed_sum = polynomial.hypercube_sum();
+    println!("Claimed sum: {}", polys::format_field(claimed_sum, false));
+
+    println!();
+    println!("Trusted setup for {num_vars} variables, discarding the toxic waste tau");
+    let tau: Vec<F> = (0..num_vars).map(|_| F::rand(&mut rng)).collect();
+    let params = Arc::new(multilinear_kzg::setup::<E>(&tau));
+    let prover = SumCheckArgumentProver { params: params.clone(), polynomial: Arc::new(polynomial) };
+    let verifier = SumCheckArgumentVerifier { params, claimed_sum, num_vars, num_factors };
+    println!();
+    println!("Begin interactive protocol execution...");
+    ip::execute::<Message<E>>(prover, verifier);
+
+    println!();
+    println!("Communication for g = f_1 f_2 in v variables, in group elements G and scalars F:");
+    println!("{:>4}  {:>14}  {:>20}", "v", "oracle (F)", "argument (G + F)");
+    for v in [10usize, 16, 20, 24, 30] {
+        let (group_elements, field_elements) = sum_check_argument::proof_size(v, num_factors);
+        println!("{:>4}  {:>14}  {:>20}", v, (num_factors as u64) << v, format!("{} + {}", group_elements, field_elements));
+    }
+    println!("A commitment is one group element and the batched opening v more, so the proof");
+    println!("size is O(v) = O(log n) for n = 2^v: the price is the trusted setup.");
+
+    if let Some(num_vars) = large {
+        run_large(num_vars, num_factors);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1);
+}
+
+// run the argument over the BLS12-381 pairing, without printing the
+// transcript
+fn run_large(num_vars: usize, num_factors: usize) {
+    let mut rng = rand::thread_rng();
+    let title = format!("Random product of {} polynomials in {} variables over BLS12-381", num_factors, num_vars);
+    println!("\n{}", title);
+    println!("{}", "=".repeat(title.len()));
+
+    let tau: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+    let start = Instant::now();
+    let params = Arc::new(multilinear_kzg::setup::<Bls12_381>(&tau));
+    println!("Trusted setup: {:>12.3?}", start.elapsed());
+    let polynomial = ProductMLE::<Fr>::rand(num_vars, num_factors, &mut rng);
+    let claimed_sum = polynomial.hypercube_sum();
+
+    let start = Instant::now();
+    let (ch_p, ch_v) = Channel::gen();
+    let prover = SumCheckArgumentProver { params: params.clone(), polynomial: Arc::new(polynomial) };
+    let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+    let result = sum_check_argument::verify(&params, claimed_sum, num_vars, num_factors, &ch_v, &Log::new());
+    handle.join().unwrap();
+    println!("Commitment, sum-check and opening: {:>12.3?}", start.elapsed());
+    match result {
+        Ok(()) => println!("Verifier accepts"),
+        Err(failure) => println!("Verifier rejects: {}", failure),
+    }
+}
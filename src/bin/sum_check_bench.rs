@@ -0,0 +1,63 @@
+use std::env;
+use std::time::{Duration,Instant};
+
+use ark_std::UniformRand;
+use ark_test_curves::bls12_381::Fr as F;
+
+use pazk::multilinear::ProductMLE;
+use pazk::sum_check::SumCheckPolynomial;
+
+const USAGE: &str = "usage: sum_check_bench [--factors K] [--max-vars N] [--max-symbolic-vars M]";
+
+fn main() {
+    let mut factors = 2;
+    let mut max_vars = 18;
+    let mut max_symbolic_vars = 8;
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    for pair in args.chunks(2) {
+        let value = pair.get(1).and_then(|v| v.parse::<usize>().ok());
+        match (pair[0].as_str(), value) {
+            ("--factors", Some(k)) if k > 0 => factors = k,
+            ("--max-vars", Some(n)) => max_vars = n,
+            ("--max-symbolic-vars", Some(m)) => max_symbolic_vars = m,
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    println!("Sum-check prover time for a product of {factors} multilinear polynomials over the BLS12-381 scalar field");
+    println!();
+    println!("Symbolic: partial summation over the expanded sparse polynomial in each round");
+    println!("Linear-time: halving the evaluation table of each factor in each round");
+    println!();
+    println!("{:>4}  {:>14}  {:>14}", "vars", "symbolic", "linear-time");
+
+    let mut rng = rand::thread_rng();
+    for num_vars in 1..=max_vars {
+        let product = ProductMLE::<F>::rand(num_vars, factors, &mut rng);
+        let challenges: Vec<F> = (0..num_vars).map(|_| F::rand(&mut rng)).collect();
+
+        let symbolic = if num_vars <= max_symbolic_vars {
+            let poly = product.to_multi_poly();
+            format!("{:.3?}", time_prover(poly, &challenges))
+        } else {
+            String::from("-")
+        };
+        let linear = time_prover(product, &challenges);
+
+        println!("{:>4}  {:>14}  {:>14}", num_vars, symbolic, format!("{:.3?}", linear));
+    }
+}
+
+// time the prover's computation of all round messages, with fixed challenges
+fn time_prover<P: SumCheckPolynomial<F>>(mut poly: P, challenges: &[F]) -> Duration {
+    let start = Instant::now();
+    for r in challenges.iter().rev() {
+        poly.round_evaluations();
+        poly.fix_last_variable(*r);
+    }
+    start.elapsed()
+}
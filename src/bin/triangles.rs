@@ -0,0 +1,95 @@
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration,Instant};
+
+use ark_test_curves::bls12_381::Fr as F;
+use rand::Rng;
+
+use pazk::sum_check::Data;
+use pazk::bench;
+use pazk::triangles::{self,Graph,TriangleProver,TriangleVerifier};
+use pazk::ip;
+
+const USAGE: &str = "usage: triangles [EDGE_LIST_FILE | --random N]";
+
+// largest graph for which the transcript is printed
+const MAX_TRANSCRIPT_VERTICES: usize = 64;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let graph = match args.as_slice() {
+        // K4 with a pendant vertex, and a separate triangle
+        [] => Graph::new(8, &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3), (3, 4), (5, 6), (6, 7), (7, 5)]),
+        [flag, n] if flag == "--random" => match n.parse() {
+            Ok(n) if n > 0 => random_graph(n),
+            _ => {
+                eprintln!("{USAGE}");
+                process::exit(1);
+            }
+        },
+        [path] if !path.starts_with("--") => {
+            let input = fs::read_to_string(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            });
+            triangles::parse_edge_list(&input).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path, error);
+                process::exit(1);
+            })
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    let m = graph.log_vertices();
+    println!("Counting Triangles Protocol");
+    println!("===========================");
+    println!("Graph with {} vertices and {} edges, padded to 2^{} vertices", graph.num_vertices, graph.num_edges(), m);
+    println!("Sum-check over g(X,Y,Z) = A~(X,Y) A~(Y,Z) A~(X,Z) in {} variables over the BLS12-381 scalar field", 3 * m);
+
+    let graph = Arc::new(graph);
+    if graph.num_vertices <= MAX_TRANSCRIPT_VERTICES {
+        println!();
+        let prover = TriangleProver { graph: graph.clone() };
+        let verifier = TriangleVerifier { graph: graph.clone() };
+        ip::execute::<Data<F>>(prover, verifier);
+    }
+
+    let (prover_time, verifier_time) = time_parties(&graph);
+    println!();
+    println!("Prover time:   {:>12.3?}  (O(n^3) to count and to compute the messages)", prover_time);
+    println!("Verifier time: {:>12.3?}  (O(n^2) to evaluate A~ at three points)", verifier_time);
+}
+
+// random graph with each edge present independently with probability 1/2
+fn random_graph(n: usize) -> Graph {
+    let mut rng = rand::thread_rng();
+    let edges: Vec<(usize, usize)> = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .filter(|_| rng.gen())
+        .collect();
+    Graph::new(n, &edges)
+}
+
+// run both parties of the protocol in a single thread, timing the work of
+// each separately
+fn time_parties(graph: &Graph) -> (Duration, Duration) {
+    let start = Instant::now();
+    let count = F::from(graph.count_triangles());
+    let poly = graph.triangle_polynomial::<F>();
+    let mut prover_time = start.elapsed();
+
+    let rounds = bench::time_rounds(poly, count * F::from(6u64)).expect("honest prover fails a round check");
+    prover_time += rounds.prover_time;
+    let mut verifier_time = rounds.verifier_time;
+
+    let start = Instant::now();
+    assert_eq!(triangles::evaluate_triangle_polynomial(&graph.adjacency_mle(), &rounds.point), rounds.check_value);
+    verifier_time += start.elapsed();
+
+    (prover_time, verifier_time)
+}
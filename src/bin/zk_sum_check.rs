@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use pazk::small_curves::SS67Pairing as E;
+use pazk::small_fields::F17 as F;
+use pazk::multilinear::ProductMLE;
+use pazk::sum_check::SumCheckPolynomial;
+use pazk::sum_check_argument::Message;
+use pazk::zk_sum_check::{self,ZkSumCheckProver,ZkSumCheckVerifier};
+use pazk::polynomials as polys;
+use pazk::ip;
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let num_vars = 3;
+    let num_factors = 2;
+
+    println!("Zero-knowledge sum-check with a masking polynomial");
+    println!();
+    println!("Pairing-friendly curve: y^2 = x^3 + x over GF(67), embedding degree 2");
+    println!("Groups: G1 = G2 = subgroup of order 17, GT = subgroup of order 17 in GF(67^2)*");
+    println!("Scalar field: GF(17)");
+    println!("Polynomial: g = f_1 f_2 for random multilinear f_1, f_2 in {num_vars} variables");
+    println!();
+
+    let polynomial = ProductMLE::<F>::rand(num_vars, num_factors, &mut rng);
+    for (i, factor) in polynomial.factors.iter().enumerate() {
+        let table: Vec<String> = factor.evals.iter().map(|x| polys::format_field(*x, false)).collect();
+        println!("f_{} evaluation table: {}", i + 1, table.join(" "));
+    }
+    let claimed_sum = polynomial.hypercube_sum();
+    println!("Claimed sum: {}", polys::format_field(claimed_sum, false));
+
+    let params = Arc::new(zk_sum_check::setup::<E>(num_vars, num_factors, &mut rng));
+    let prover = ZkSumCheckProver { params: params.clone(), polynomial: Arc::new(polynomial) };
+    let verifier = ZkSumCheckVerifier { params, claimed_sum, num_factors };
+    println!();
+    println!("Begin interactive protocol execution...");
+    ip::execute::<Message<E>>(prover, verifier);
+}
@@ -0,0 +1,315 @@
+use std::fmt;
+use std::iter;
+use std::sync::Arc;
+
+use ark_ec::Group;
+use ark_ff::{Field,PrimeField,Zero};
+use ark_std::UniformRand;
+
+use crate::group_utils;
+use crate::ip::{IP,Channel,Log,Embeds};
+
+// PAZK, Protocol 13
+// Protocol convinces verifier in logarithmic communication that the inner
+// product of a given public vector with a vector committed as a generalized
+// Pedersen commitment (or as any additively homomorphic commitment type) is a
+// specified value.
+//
+// In each round the prover sends the cross terms of the commitment and of the
+// inner product for the two halves of the vectors, and both parties fold the
+// generators, the public vector and the claims with a random alpha, halving
+// the length.  At the end the prover sends the single remaining coefficient
+// in the clear.  The vectors must have length a power of 2.
+
+/// Prover side of the inner product argument for <u, y> with u = `coeffs`
+/// and y = `public_vector`, committed with the generators `gens`.
+pub fn prove<G: Group + fmt::Display, M: Embeds<Data<G>>>(
+        gens: &[G],
+        public_vector: &[G::ScalarField],
+        coeffs: &[G::ScalarField],
+        ch: &Channel<M>,
+        log: &Log) {
+    // variables updated each recursive round
+    let mut vec_len = public_vector.len();
+    let mut u = coeffs.to_vec();
+    let mut g = gens.to_vec();
+    let mut y = public_vector.to_vec();
+
+    // compute number of rounds
+    let n_rounds = vec_len.ilog2();
+    if vec_len != 2usize.pow(n_rounds) {
+        return;
+    }
+
+    for round in 0..n_rounds {
+        log.write(String::new());
+        log.write(format!("Starting round {}...", round+1));
+
+        let half = vec_len / 2;
+
+        log.write("P computes cross terms for folded Pedersen commitment".to_string());
+        let comm_cross_term_l = group_utils::msm(&g[half..], &u[..half]);
+        let comm_cross_term_r = group_utils::msm(&g[..half], &u[half..]);
+        log.write(format!("  v_L = <u_L,g_R> = {}", comm_cross_term_l));
+        log.write(format!("  v_R = <u_R,g_L> = {}", comm_cross_term_r));
+
+        log.write("P computes cross terms for folded inner product".to_string());
+        let poly_cross_term_l = group_utils::msm(&y[half..], &u[..half]);
+        let poly_cross_term_r = group_utils::msm(&y[..half], &u[half..]);
+        log.write(format!("  v'_L = <u_L,y_R> = {}", Data::<G>::Scalar(poly_cross_term_l)));
+        log.write(format!("  v'_R = <u_R,y_L> = {}", Data::<G>::Scalar(poly_cross_term_r)));
+
+        log.write("P -> (v_L, v_R, v'_L, v'_R)".to_string());
+        ch.send_as(Data::Commitment(comm_cross_term_l));
+        ch.send_as(Data::Commitment(comm_cross_term_r));
+        ch.send_as(Data::Scalar(poly_cross_term_l));
+        ch.send_as(Data::Scalar(poly_cross_term_r));
+
+        let Some(alpha) = ch.receive_as::<Data<G>>().and_then(|data| data.to_scalar()) else { return };
+        if alpha == G::ScalarField::zero() {
+            log.write("Error: received coefficient alpha is zero".to_string());
+            log.write("Aborting...".to_string());
+            return;
+        }
+        let alpha_inv = alpha.inverse().unwrap();
+
+        log.write("P computes folded generators".to_string());
+        g = fold(&g, alpha_inv, alpha);
+
+        log.write("P computes folded public vector".to_string());
+        y = fold(&y, alpha_inv, alpha);
+
+        log.write("P computes folded coefficients vector".to_string());
+        u = fold(&u, alpha, alpha_inv);
+        log.write(format!("  {}", list_scalars::<G>(&u)));
+
+        vec_len = half;
+    }
+
+    // final round: send compressed discrete logarithm u
+
+    log.write(String::new());
+    log.write(format!("Starting round {}...", n_rounds+1));
+
+    log.write("P sends final folded coefficient in the clear".to_string());
+    let data = Data::Scalar(u[0]);
+    log.write(format!("P -> (u = {data})"));
+    ch.send_as(data);
+}
+
+/// Verifier side of the inner product argument that the vector committed in
+/// `commitment` has inner product `inner_product` with `public_vector`.
+/// Sends and returns the decision, rejecting messages of the wrong form.
+pub fn verify<G: Group + fmt::Display, M: Embeds<Data<G>>>(
+        gens: &[G],
+        public_vector: &[G::ScalarField],
+        commitment: G,
+        inner_product: G::ScalarField,
+        ch: &Channel<M>,
+        log: &Log) -> bool {
+    let mut rng = rand::thread_rng();
+    let receive = || ch.receive_as::<Data<G>>();
+    let reject = || {
+        log.write("V rejects malformed inner product argument message".to_string());
+        let data = Data::<G>::Decision(false);
+        log.write(format!("V --> ({})", data));
+        ch.send_as(data);
+        false
+    };
+
+    // variables updated each recursive round
+    let mut vec_len = public_vector.len();
+    let mut c = commitment;
+    let mut g = gens.to_vec();
+    let mut y = public_vector.to_vec();
+    let mut v = inner_product;
+
+    // compute number of rounds
+    let n_rounds = vec_len.ilog2();
+    if vec_len != 2usize.pow(n_rounds) {
+        return false;
+    }
+
+    for _round in 0..n_rounds {
+        let half = vec_len / 2;
+
+        let (Some(comm_cross_term_l), Some(comm_cross_term_r), Some(poly_cross_term_l), Some(poly_cross_term_r)) = (
+            receive().and_then(|data| data.to_commitment()),
+            receive().and_then(|data| data.to_commitment()),
+            receive().and_then(|data| data.to_scalar()),
+            receive().and_then(|data| data.to_scalar()),
+        ) else { return reject() };
+
+        log.write("V picks nonzero scalar alpha uniformly at random".to_string());
+        let mut alpha = G::ScalarField::zero();
+        while alpha == G::ScalarField::zero() {
+            alpha = G::ScalarField::rand(&mut rng);
+        }
+
+        let alpha_inv = alpha.inverse().unwrap();
+
+        log.write("V computes folded generators".to_string());
+        g = fold(&g, alpha_inv, alpha);
+        log.write(format!("  {}", group_utils::list_vec(&g, ", ")));
+
+        log.write("V computes folded public vector".to_string());
+        y = fold(&y, alpha_inv, alpha);
+        log.write(format!("  {}", list_scalars::<G>(&y)));
+
+        log.write("V computes folded Pedersen commitment".to_string());
+        c += comm_cross_term_l*(alpha*alpha) + comm_cross_term_r*(alpha_inv*alpha_inv);
+        log.write(format!("  {}", c));
+
+        log.write("V computes folded inner product".to_string());
+        v += poly_cross_term_l*(alpha*alpha) + poly_cross_term_r*(alpha_inv*alpha_inv);
+        log.write(format!("  {}", Data::<G>::Scalar(v)));
+
+        vec_len = half;
+
+        let data = Data::Scalar(alpha);
+        log.write(format!("V -> (alpha = {data})"));
+        ch.send_as(data);
+    }
+
+    let Some(u0) = receive().and_then(|data| data.to_scalar()) else { return reject() };
+    let g0 = g[0];
+    let y0 = y[0];
+
+    log.write("V checks that discrete log relations hold:".to_string());
+    log.write(format!("  u*g == {}*{} ?= {} == C_u",
+        Data::<G>::Scalar(u0), Data::Commitment(g0), Data::Commitment(c)));
+    log.write(format!("  u*y == {}*{} ?= {} == v",
+        Data::<G>::Scalar(u0), Data::<G>::Scalar(y0), Data::<G>::Scalar(v)));
+
+    let decision = g0*u0 == c && y0*u0 == v;
+
+    let data = Data::Decision(decision);
+    log.write(format!("V --> ({})", data));
+    ch.send_as(data);
+    decision
+}
+
+// fold a vector in half as a * left + b * right
+fn fold<T, S>(vector: &[T], a: S, b: S) -> Vec<T> where
+    T: Copy + std::ops::Mul<S, Output=T> + std::ops::Add<T, Output=T>,
+    S: Copy,
+{
+    let half = vector.len() / 2;
+    iter::zip(&vector[..half], &vector[half..])
+        .map(|(&x, &y)| x*a + y*b)
+        .collect()
+}
+
+fn list_scalars<G: Group>(scalars: &[G::ScalarField]) -> String {
+    let data: Vec<Data<G>> = scalars.iter().map(|x| Data::Scalar(*x)).collect();
+    group_utils::list_vec(&data, ", ")
+}
+
+pub struct BulletproofProver<G: Group> {
+    pub gens: Arc< Vec<G> >,
+    pub public_vector: Arc< Vec<G::ScalarField> >,
+    pub coeffs: Arc< Vec<G::ScalarField> >,
+}
+
+impl<G: Group + fmt::Display> IP<Data<G>> for BulletproofProver<G> {
+    fn execute(&self, ch: Channel<Data<G>>, log: Log) {
+        prove(&self.gens, &self.public_vector, &self.coeffs, &ch, &log);
+    }
+}
+
+pub struct BulletproofVerifier<G: Group> {
+    pub gens: Arc< Vec<G> >,
+    pub public_vector: Arc< Vec<G::ScalarField> >,
+    pub public_ip: Arc< G::ScalarField >,
+    pub coeffs_commitment: Arc< G >,
+}
+
+impl<G: Group + fmt::Display> IP<Data<G>> for BulletproofVerifier<G> {
+    fn execute(&self, ch: Channel<Data<G>>, log: Log) {
+        verify(&self.gens, &self.public_vector, *self.coeffs_commitment, *self.public_ip, &ch, &log);
+    }
+}
+
+
+#[derive(Clone)]
+pub enum Data<G: Group> {
+    Scalar(G::ScalarField),
+    Commitment(G),
+    Decision(bool),
+}
+
+impl<G: Group> Data<G> {
+    pub fn to_scalar(&self) -> Option<G::ScalarField> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
+    }
+
+    pub fn to_commitment(&self) -> Option<G> {
+        if let Data::Commitment(p) = self { Some(*p) } else { None }
+    }
+
+    pub fn to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
+    }
+}
+
+impl<G: Group + fmt::Display> fmt::Display for Data<G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Data::Scalar(x) => {
+                write!(f, "{}", x.into_bigint())
+            }
+            Data::Commitment(g) => {
+                write!(f, "{}", g)
+            }
+            Data::Decision(b) => {
+                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_test_curves::bls12_381::{Fr,G1Projective};
+    use crate::small_curves::C17Projective;
+    use crate::small_fields::F17;
+    use crate::test_utils;
+
+    fn run<G: Group + fmt::Display>(gens: Vec<G>, y: Vec<G::ScalarField>, u: Vec<G::ScalarField>, v: G::ScalarField) -> bool {
+        let commitment = group_utils::msm(&gens, &u);
+        let prover = BulletproofProver { gens: Arc::new(gens.clone()), public_vector: Arc::new(y.clone()), coeffs: Arc::new(u) };
+        test_utils::run_against(prover, |ch, log| verify(&gens, &y, commitment, v, ch, log))
+    }
+
+    #[test]
+    fn test_bulletproof() {
+        let mut rng = rand::thread_rng();
+        let gens: Vec<C17Projective> = group_utils::rand_gens(8, &mut rng);
+        let y: Vec<F17> = (0..8).map(|_| F17::rand(&mut rng)).collect();
+        let u: Vec<F17> = (0..8).map(|_| F17::rand(&mut rng)).collect();
+        let v = group_utils::msm(&y, &u);
+        assert!(run(gens, y, u, v));
+
+        // a false inner product is rejected, except with negligible probability
+        // over a large field
+        let gens: Vec<G1Projective> = group_utils::rand_gens(4, &mut rng);
+        let y: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let u: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let v = group_utils::msm(&y, &u);
+        assert!(run(gens.clone(), y.clone(), u.clone(), v));
+        assert!(!run(gens, y, u, v + Fr::from(1u64)));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut rng = rand::thread_rng();
+        let gens: Vec<C17Projective> = group_utils::rand_gens(2, &mut rng);
+        let y: Vec<F17> = (0..2).map(|_| F17::rand(&mut rng)).collect();
+        // a scalar in place of the first cross term commitment
+        let messages = vec![Data::Scalar(F17::from(1u64)); 4];
+        let prover = test_utils::ScriptedProver(messages);
+        assert!(!test_utils::run_against(prover, |ch, log| verify(&gens, &y, gens[0], F17::from(0u64), ch, log)));
+    }
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+
+use ark_ff::{Field,PrimeField};
+
+use crate::polynomials;
+
+use crate::multilinear::{self,DenseMLE};
+
+// PAZK, Section 4.6
+// Layered arithmetic circuits of fan-in 2, with layer 0 the output layer and
+// layer d the inputs.  Each gate in layer i < d adds or multiplies two values
+// from layer i+1.  Layer i has S_i gates, indexed by k_i = log S_i bits after
+// padding with gates of value zero, and the values of layer i define the
+// function W_i: {0,1}^{k_i} -> F.  The wiring predicates
+//   add_i(a, b, c) = 1 if gate a of layer i adds gates b and c of layer i+1
+//   mul_i(a, b, c) = 1 if gate a of layer i multiplies gates b and c
+// determine how each layer depends on the next, as
+//   W_i(a) = sum_{b,c} add_i(a,b,c) (W_{i+1}(b) + W_{i+1}(c))
+//                    + mul_i(a,b,c) W_{i+1}(b) W_{i+1}(c).
+//
+// General arithmetic circuits are directed acyclic graphs of named gates,
+// each an input, a constant, or a sum or product of any number of earlier
+// gates, and can be converted to layered circuits when they already have the
+// layered structure.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GateType {
+    Add,
+    Mul,
+}
+
+/// Gate computing the sum or product of the gates `left` and `right` of the
+/// next layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gate {
+    pub gate_type: GateType,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Gate {
+    pub fn add(left: usize, right: usize) -> Gate {
+        Gate { gate_type: GateType::Add, left, right }
+    }
+
+    pub fn mul(left: usize, right: usize) -> Gate {
+        Gate { gate_type: GateType::Mul, left, right }
+    }
+}
+
+/// Layered arithmetic circuit, with `layers[i]` the gates of layer i, from the
+/// outputs at layer 0 down to the layer above the inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayeredCircuit {
+    pub layers: Vec<Vec<Gate>>,
+    pub num_inputs: usize,
+}
+
+impl LayeredCircuit {
+    pub fn new(layers: Vec<Vec<Gate>>, num_inputs: usize) -> LayeredCircuit {
+        assert!(!layers.is_empty(), "circuit must have at least one layer of gates");
+        let circuit = LayeredCircuit { layers, num_inputs };
+        for (i, layer) in circuit.layers.iter().enumerate() {
+            assert!(!layer.is_empty(), "layer {} has no gates", i);
+            let next_size = circuit.layer_size(i + 1);
+            for gate in layer {
+                assert!(gate.left < next_size && gate.right < next_size, "gate in layer {} has input out of range", i);
+            }
+        }
+        circuit
+    }
+
+    /// Number d of layers of gates, so that the inputs are layer d.
+    pub fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Number of gates in layer i, or of inputs if i is the depth.
+    pub fn layer_size(&self, i: usize) -> usize {
+        if i == self.depth() { self.num_inputs } else { self.layers[i].len() }
+    }
+
+    /// Number k_i of bits indexing the gates of layer i, which is at least 1.
+    pub fn layer_bits(&self, i: usize) -> usize {
+        multilinear::index_bits(self.layer_size(i))
+    }
+
+    /// Evaluate the circuit, returning the values of every layer from the
+    /// outputs at index 0 to the inputs at index d.
+    pub fn evaluate<F: Field>(&self, inputs: &[F]) -> Vec<Vec<F>> {
+        assert_eq!(inputs.len(), self.num_inputs, "wrong number of inputs");
+        let mut values = vec![inputs.to_vec()];
+        for layer in self.layers.iter().rev() {
+            let next = values.last().unwrap();
+            let current = layer.iter()
+                .map(|gate| match gate.gate_type {
+                    GateType::Add => next[gate.left] + next[gate.right],
+                    GateType::Mul => next[gate.left] * next[gate.right],
+                })
+                .collect();
+            values.push(current);
+        }
+        values.reverse();
+        values
+    }
+
+    /// Multilinear extension W_i~ of the values of layer i, padded with zeros
+    /// to 2^{k_i} gates.
+    pub fn layer_mle<F: Field>(&self, i: usize, values: &[F]) -> DenseMLE<F> {
+        let mut evals = values.to_vec();
+        evals.resize(1 << self.layer_bits(i), F::zero());
+        DenseMLE::new(self.layer_bits(i), evals)
+    }
+
+    /// Evaluate the multilinear extension of the wiring predicate add_i or
+    /// mul_i of layer i at (r_a, r_b, r_c), in time linear in the sizes of
+    /// layers i and i+1.
+    pub fn wiring_predicate<F: Field>(&self, i: usize, gate_type: GateType, r_a: &[F], r_b: &[F], r_c: &[F]) -> F {
+        let eq_a = multilinear::eq_table(r_a);
+        let eq_b = multilinear::eq_table(r_b);
+        let eq_c = multilinear::eq_table(r_c);
+        self.layers[i].iter()
+            .enumerate()
+            .filter(|(_, gate)| gate.gate_type == gate_type)
+            .map(|(a, gate)| eq_a.evals[a] * eq_b.evals[gate.left] * eq_c.evals[gate.right])
+            .sum()
+    }
+}
+
+impl fmt::Display for LayeredCircuit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, layer) in self.layers.iter().enumerate() {
+            let gates: Vec<String> = layer.iter()
+                .map(|gate| {
+                    let op = match gate.gate_type { GateType::Add => "+", GateType::Mul => "*" };
+                    format!("{} {} {}", gate.left, op, gate.right)
+                })
+                .collect();
+            writeln!(f, "layer {}: {}", i, gates.join(", "))?;
+        }
+        write!(f, "layer {}: {} inputs", self.depth(), self.num_inputs)
+    }
+}
+
+
+/// Operation computed by a gate of a general arithmetic circuit, with operands
+/// given by the indices of earlier gates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation<F> {
+    Input,
+    Const(F),
+    Add(Vec<usize>),
+    Mul(Vec<usize>),
+}
+
+/// General arithmetic circuit, with gates in topological order so that every
+/// operand of a gate comes before it.  The inputs are the input gates in
+/// order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Circuit<F> {
+    pub names: Vec<String>,
+    pub gates: Vec<Operation<F>>,
+    pub outputs: Vec<usize>,
+}
+
+/// Reason that a circuit is invalid or lacks a required structure, naming
+/// the gate at fault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitError {
+    pub gate: String,
+    pub message: String,
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gate '{}': {}", self.gate, self.message)
+    }
+}
+
+impl<F: Field> Circuit<F> {
+    fn error(&self, gate: usize, message: String) -> CircuitError {
+        CircuitError { gate: self.names[gate].clone(), message }
+    }
+
+    /// Indices of the input gates, in order.
+    pub fn inputs(&self) -> Vec<usize> {
+        (0..self.gates.len()).filter(|g| self.gates[*g] == Operation::Input).collect()
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.inputs().len()
+    }
+
+    /// Check that names are unique, that every gate has operands which come
+    /// before it, and that the outputs exist.
+    pub fn validate(&self) -> Result<(), CircuitError> {
+        assert_eq!(self.names.len(), self.gates.len(), "every gate must have a name");
+        for (g, op) in self.gates.iter().enumerate() {
+            if self.names[..g].contains(&self.names[g]) {
+                return Err(self.error(g, String::from("defined more than once")));
+            }
+            if let Operation::Add(operands) | Operation::Mul(operands) = op {
+                if operands.is_empty() {
+                    return Err(self.error(g, String::from("has no operands")));
+                }
+                if operands.iter().any(|operand| *operand >= g) {
+                    return Err(self.error(g, String::from("has an operand which is not defined before it")));
+                }
+            }
+        }
+        if self.outputs.is_empty() {
+            return Err(CircuitError { gate: String::new(), message: String::from("circuit has no outputs") });
+        }
+        if let Some(output) = self.outputs.iter().find(|output| **output >= self.gates.len()) {
+            return Err(CircuitError { gate: output.to_string(), message: String::from("output does not exist") });
+        }
+        Ok(())
+    }
+
+    /// Check that every sum and product gate has at most `max` operands.
+    pub fn check_fan_in(&self, max: usize) -> Result<(), CircuitError> {
+        for (g, op) in self.gates.iter().enumerate() {
+            if let Operation::Add(operands) | Operation::Mul(operands) = op {
+                if operands.len() > max {
+                    return Err(self.error(g, format!("has fan-in {} but at most {} is allowed", operands.len(), max)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate every gate of the circuit on the given inputs.
+    pub fn evaluate(&self, inputs: &[F]) -> Vec<F> {
+        assert_eq!(inputs.len(), self.num_inputs(), "wrong number of inputs");
+        let mut inputs = inputs.iter();
+        let mut values: Vec<F> = Vec::with_capacity(self.gates.len());
+        for op in &self.gates {
+            let value = match op {
+                Operation::Input => *inputs.next().unwrap(),
+                Operation::Const(c) => *c,
+                Operation::Add(operands) => operands.iter().map(|o| values[*o]).sum(),
+                Operation::Mul(operands) => operands.iter().map(|o| values[*o]).product(),
+            };
+            values.push(value);
+        }
+        values
+    }
+
+    /// Values of the outputs, given the values of every gate.
+    pub fn output_values(&self, values: &[F]) -> Vec<F> {
+        self.outputs.iter().map(|o| values[*o]).collect()
+    }
+
+    /// Distance of each gate from the inputs, with inputs and constants at
+    /// depth 0.
+    pub fn depths(&self) -> Vec<usize> {
+        let mut depths: Vec<usize> = Vec::with_capacity(self.gates.len());
+        for op in &self.gates {
+            let depth = match op {
+                Operation::Input | Operation::Const(_) => 0,
+                Operation::Add(operands) | Operation::Mul(operands) => {
+                    1 + operands.iter().map(|o| depths[*o]).max().unwrap()
+                }
+            };
+            depths.push(depth);
+        }
+        depths
+    }
+
+    /// Convert to a layered circuit, which requires every sum and product to
+    /// have fan-in 2 with both operands one layer below it, no constants, and
+    /// the outputs to be exactly the gates of greatest depth.
+    pub fn to_layered(&self) -> Result<LayeredCircuit, CircuitError> {
+        self.validate()?;
+        self.check_fan_in(2)?;
+        let depths = self.depths();
+        let depth = self.outputs.iter().map(|o| depths[*o]).max().unwrap();
+        if depth == 0 {
+            return Err(self.error(self.outputs[0], String::from("output must not be an input")));
+        }
+
+        // position of each gate within its layer, with the outputs first
+        let mut positions = vec![0; self.gates.len()];
+        let mut layer_sizes = vec![0; depth + 1];
+        for (g, op) in self.gates.iter().enumerate() {
+            match op {
+                Operation::Const(_) => return Err(self.error(g, String::from("constants are not allowed in layered circuits"))),
+                Operation::Add(operands) | Operation::Mul(operands) => {
+                    if operands.len() != 2 {
+                        return Err(self.error(g, format!("has fan-in {} but layered circuits need fan-in 2", operands.len())));
+                    }
+                    if let Some(o) = operands.iter().find(|o| depths[**o] + 1 != depths[g]) {
+                        return Err(self.error(g, format!("operand '{}' is not in the layer directly below", self.names[*o])));
+                    }
+                }
+                Operation::Input => {}
+            }
+            if depths[g] == depth {
+                if !self.outputs.contains(&g) {
+                    return Err(self.error(g, String::from("is in the output layer but is not an output")));
+                }
+            } else {
+                positions[g] = layer_sizes[depths[g]];
+                layer_sizes[depths[g]] += 1;
+            }
+        }
+        for (position, output) in self.outputs.iter().enumerate() {
+            if depths[*output] != depth {
+                return Err(self.error(*output, String::from("output is not in the output layer")));
+            }
+            if self.outputs[..position].contains(output) {
+                return Err(self.error(*output, String::from("is listed as an output more than once")));
+            }
+            positions[*output] = position;
+        }
+
+        let mut layers: Vec<Vec<Gate>> = vec![Vec::new(); depth];
+        layers[0] = self.outputs.iter().map(|o| self.layered_gate(*o, &positions)).collect();
+        for g in (0..self.gates.len()).filter(|g| depths[*g] > 0 && depths[*g] < depth) {
+            layers[depth - depths[g]].push(self.layered_gate(g, &positions));
+        }
+        Ok(LayeredCircuit::new(layers, layer_sizes[0]))
+    }
+
+    fn layered_gate(&self, g: usize, positions: &[usize]) -> Gate {
+        match &self.gates[g] {
+            Operation::Add(operands) => Gate::add(positions[operands[0]], positions[operands[1]]),
+            Operation::Mul(operands) => Gate::mul(positions[operands[0]], positions[operands[1]]),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<F: PrimeField> Circuit<F> {
+    /// Graphviz DOT description of the circuit, with edges from each operand
+    /// to the gates using it.
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec![String::from("digraph circuit {"), String::from("    rankdir=BT;")];
+        for (g, op) in self.gates.iter().enumerate() {
+            let (label, shape) = match op {
+                Operation::Input => (self.names[g].clone(), "box"),
+                Operation::Const(c) => (polynomials::format_field(*c, true), "plaintext"),
+                Operation::Add(_) => (format!("{}\\n+", self.names[g]), "circle"),
+                Operation::Mul(_) => (format!("{}\\n*", self.names[g]), "circle"),
+            };
+            let shape = if self.outputs.contains(&g) { "doublecircle" } else { shape };
+            lines.push(format!("    g{} [label=\"{}\", shape={}];", g, label, shape));
+        }
+        for (g, op) in self.gates.iter().enumerate() {
+            if let Operation::Add(operands) | Operation::Mul(operands) = op {
+                for o in operands {
+                    lines.push(format!("    g{} -> g{};", o, g));
+                }
+            }
+        }
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+}
+
+impl<F: PrimeField> fmt::Display for Circuit<F> {
+    /// Write the circuit in the text format read by `circuit_parser`, with
+    /// the gates in order so that parsing gives back the same circuit.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut inputs: Vec<&str> = Vec::new();
+        for (g, op) in self.gates.iter().enumerate() {
+            let operands = |operands: &[usize]| {
+                operands.iter().map(|o| self.names[*o].as_str()).collect::<Vec<_>>().join(" ")
+            };
+            // consecutive inputs share a line
+            if *op != Operation::Input && !inputs.is_empty() {
+                writeln!(f, "input {}", inputs.join(" "))?;
+                inputs.clear();
+            }
+            match op {
+                Operation::Input => inputs.push(self.names[g].as_str()),
+                Operation::Const(c) => writeln!(f, "{} = const {}", self.names[g], polynomials::format_field(*c, true))?,
+                Operation::Add(o) => writeln!(f, "{} = add {}", self.names[g], operands(o))?,
+                Operation::Mul(o) => writeln!(f, "{} = mul {}", self.names[g], operands(o))?,
+            }
+        }
+        if !inputs.is_empty() {
+            writeln!(f, "input {}", inputs.join(" "))?;
+        }
+        let outputs: Vec<&str> = self.outputs.iter().map(|g| self.names[*g].as_str()).collect();
+        write!(f, "output {}", outputs.join(" "))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::small_fields::F13 as F;
+
+    // outputs (x_0 x_1)^2 and (x_1 + x_2) x_3^2
+    fn example_circuit() -> LayeredCircuit {
+        LayeredCircuit::new(vec![
+            vec![Gate::mul(0, 0), Gate::mul(1, 2)],
+            vec![Gate::mul(0, 1), Gate::add(1, 2), Gate::mul(3, 3)],
+        ], 4)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let circuit = example_circuit();
+        let inputs: Vec<F> = [2u64, 3, 4, 5].into_iter().map(F::from).collect();
+        let values = circuit.evaluate(&inputs);
+        assert_eq!(values[0], vec![F::from(36u64), F::from(7u64 * 25)]);
+        assert_eq!(values[2], inputs);
+        assert_eq!(circuit.layer_bits(1), 2);
+    }
+
+    #[test]
+    fn test_wiring_predicate() {
+        let circuit = example_circuit();
+        let (k_a, k_b) = (circuit.layer_bits(1), circuit.layer_bits(2));
+
+        // agrees with the definition on the hypercube
+        let mul = |a: usize, b: usize, c: usize| circuit.wiring_predicate(1, GateType::Mul,
+            &multilinear::hypercube_point::<F>(k_a, a),
+            &multilinear::hypercube_point(k_b, b),
+            &multilinear::hypercube_point(k_b, c));
+        assert_eq!(mul(0, 0, 1), F::from(1u64));
+        assert_eq!(mul(2, 3, 3), F::from(1u64));
+        assert_eq!(mul(1, 1, 2), F::from(0u64));
+
+        // and is the multilinear extension, checked by summing over the gates
+        let mut rng = rand::thread_rng();
+        let r: Vec<F> = (0..k_a + 2 * k_b).map(|_| F::rand(&mut rng)).collect();
+        let (r_a, r_bc) = r.split_at(k_a);
+        let (r_b, r_c) = r_bc.split_at(k_b);
+        let expected: F = (0..1 << k_a).flat_map(|a| (0..1 << k_b).flat_map(move |b| (0..1 << k_b).map(move |c| (a, b, c))))
+            .filter(|&(a, b, c)| a < 3 && circuit.layers[1][a] == Gate::add(b, c))
+            .map(|(a, b, c)| {
+                multilinear::eq_eval(&multilinear::hypercube_point(k_a, a), r_a)
+                    * multilinear::eq_eval(&multilinear::hypercube_point(k_b, b), r_b)
+                    * multilinear::eq_eval(&multilinear::hypercube_point(k_b, c), r_c)
+            })
+            .sum();
+        assert_eq!(circuit.wiring_predicate(1, GateType::Add, r_a, r_b, r_c), expected);
+    }
+}
@@ -0,0 +1,201 @@
+use ark_ff::PrimeField;
+
+use crate::circuit::{Circuit,Operation};
+use crate::poly_parser::{self,ParseError};
+
+// Text format for arithmetic circuits over a prime field, with one statement
+// per line and comments starting with '#':
+//
+//   input x y z          declare input gates, in order
+//   t = add x y          sum of any number of earlier gates
+//   u = mul t z t        product of any number of earlier gates
+//   c = const -3         constant, reduced modulo the field size
+//   output u t           declare output gates, in order
+//
+// Gates are defined before they are used, so every circuit in this format is
+// acyclic.  Layered circuits for GKR are those in which every sum and product
+// has two operands in the layer directly below, which is checked when
+// converting with `Circuit::to_layered`.
+
+/// Parse a circuit in the text format above.
+pub fn parse_circuit<F: PrimeField>(input: &str) -> Result<Circuit<F>, ParseError> {
+    let mut circuit = Circuit { names: Vec::new(), gates: Vec::new(), outputs: Vec::new() };
+
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let content = line.split('#').next().unwrap();
+        let words: Vec<(usize, &str)> = poly_parser::words(content)
+            .map(|(word_offset, word)| (line_start + word_offset, word))
+            .collect();
+        let Some(&(_, keyword)) = words.first() else { continue };
+
+        match keyword {
+            "input" => {
+                for &(position, name) in &words[1..] {
+                    define(&mut circuit, position, name, Operation::Input)?;
+                }
+            }
+            "output" => {
+                for &(position, name) in &words[1..] {
+                    circuit.outputs.push(lookup(&circuit, position, name)?);
+                }
+            }
+            name => {
+                let Some(&(position, "=")) = words.get(1) else {
+                    let position = words.get(1).map_or(line_start + content.trim_end().len(), |w| w.0);
+                    return Err(ParseError { position, message: format!("expected '=' after gate name '{}'", name) });
+                };
+                let Some(&(op_position, op)) = words.get(2) else {
+                    return Err(ParseError { position: position + 1, message: String::from("expected operation after '='") });
+                };
+                let args = &words[3..];
+                let operation = match op {
+                    "add" | "mul" => {
+                        if args.is_empty() {
+                            return Err(ParseError { position: op_position, message: format!("'{}' needs at least one operand", op) });
+                        }
+                        let operands = args.iter()
+                            .map(|&(position, name)| lookup(&circuit, position, name))
+                            .collect::<Result<Vec<usize>, ParseError>>()?;
+                        if op == "add" { Operation::Add(operands) } else { Operation::Mul(operands) }
+                    }
+                    "const" => match args {
+                        [(position, value)] => Operation::Const(parse_constant(*position, value)?),
+                        _ => return Err(ParseError { position: op_position, message: String::from("'const' needs exactly one value") }),
+                    },
+                    _ => return Err(ParseError { position: op_position, message: format!("unknown operation '{}'", op) }),
+                };
+                define(&mut circuit, words[0].0, name, operation)?;
+            }
+        }
+    }
+
+    if circuit.outputs.is_empty() {
+        return Err(ParseError { position: input.len(), message: String::from("circuit has no outputs") });
+    }
+    Ok(circuit)
+}
+
+fn define<F>(circuit: &mut Circuit<F>, position: usize, name: &str, operation: Operation<F>) -> Result<(), ParseError> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !valid || ["input", "output"].contains(&name) {
+        return Err(ParseError { position, message: format!("invalid gate name '{}'", name) });
+    }
+    if circuit.names.iter().any(|n| n == name) {
+        return Err(ParseError { position, message: format!("gate '{}' is already defined", name) });
+    }
+    circuit.names.push(name.to_string());
+    circuit.gates.push(operation);
+    Ok(())
+}
+
+fn lookup<F>(circuit: &Circuit<F>, position: usize, name: &str) -> Result<usize, ParseError> {
+    circuit.names.iter()
+        .position(|n| n == name)
+        .ok_or(ParseError { position, message: format!("unknown gate '{}'", name) })
+}
+
+fn parse_constant<F: PrimeField>(position: usize, value: &str) -> Result<F, ParseError> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ParseError { position, message: format!("invalid constant '{}'", value) });
+    }
+    // reduce modulo the field size one digit at a time
+    let ten = F::from(10u64);
+    let x = digits.chars().fold(F::zero(), |acc, d| acc * ten + F::from(d.to_digit(10).unwrap() as u64));
+    Ok(if negative { -x } else { x })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Gate,LayeredCircuit};
+    use crate::small_fields::F13 as F;
+
+    const EXAMPLE: &str = "\
+# outputs (x_0 x_1)^2 and (x_1 + x_2) x_3^2
+input x0 x1 x2 x3
+a = mul x0 x1
+b = add x1 x2
+c = mul x3 x3
+out0 = mul a a   # square
+out1 = mul b c
+output out0 out1
+";
+
+    #[test]
+    fn test_parse_circuit() {
+        let circuit = parse_circuit::<F>(EXAMPLE).unwrap();
+        assert_eq!(circuit.num_inputs(), 4);
+        assert_eq!(circuit.validate(), Ok(()));
+
+        let inputs: Vec<F> = [2u64, 3, 4, 5].into_iter().map(F::from).collect();
+        let values = circuit.evaluate(&inputs);
+        assert_eq!(circuit.output_values(&values), vec![F::from(36u64), F::from(175u64)]);
+
+        // display writes the same format back
+        let text = circuit.to_string();
+        assert_eq!(parse_circuit::<F>(&text), Ok(circuit.clone()));
+
+        let layered = circuit.to_layered().unwrap();
+        assert_eq!(layered, LayeredCircuit::new(vec![
+            vec![Gate::mul(0, 0), Gate::mul(1, 2)],
+            vec![Gate::mul(0, 1), Gate::add(1, 2), Gate::mul(3, 3)],
+        ], 4));
+        assert_eq!(layered.evaluate(&inputs)[0], circuit.output_values(&values));
+        assert!(circuit.to_dot().contains("g4 -> g7;"));
+    }
+
+    #[test]
+    fn test_constants_and_fan_in() {
+        // an input declared after other gates keeps its place
+        let circuit = parse_circuit::<F>("input x\nk = const -3\ninput y z\nw = add x k y z\noutput w").unwrap();
+        assert_eq!(circuit.to_string(), "input x\nk = const -3\ninput y z\nw = add x k y z\noutput w");
+        assert_eq!(parse_circuit::<F>(&circuit.to_string()), Ok(circuit));
+
+        let error = parse_circuit::<F>("input x\nk = const -3\ny = add x k x\nz = mul y y 20\noutput z").unwrap_err();
+        assert_eq!(error, ParseError { position: 47, message: String::from("unknown gate '20'") });
+
+        let circuit = parse_circuit::<F>("input x\nk = const -30\ny = add x k x\noutput y").unwrap();
+        let values = circuit.evaluate(&[F::from(4u64)]);
+        assert_eq!(circuit.output_values(&values), vec![F::from(8u64) - F::from(30u64)]);
+        assert_eq!(circuit.check_fan_in(2).unwrap_err().gate, "y");
+        assert!(circuit.to_layered().is_err());
+    }
+
+    #[test]
+    fn test_layering_errors() {
+        let circuit = parse_circuit::<F>("input x y\na = mul x y\nb = add a x\noutput b").unwrap();
+        assert_eq!(circuit.to_layered().unwrap_err().to_string(), "gate 'b': operand 'x' is not in the layer directly below");
+
+        let circuit = parse_circuit::<F>("input x y\na = mul x y\nb = add x y\noutput a").unwrap();
+        assert_eq!(circuit.to_layered().unwrap_err().to_string(), "gate 'b': is in the output layer but is not an output");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("input x\ny = sub x x\noutput y", 12, "unknown operation 'sub'"),
+            ("input x\ny = add x z\noutput y", 18, "unknown gate 'z'"),
+            ("input x\nx = add x x\noutput x", 8, "gate 'x' is already defined"),
+            ("input x\ny add x x\noutput y", 10, "expected '=' after gate name 'y'"),
+            ("input x\ny = const 1 2\noutput y", 12, "'const' needs exactly one value"),
+            ("input x\ny = const 1x\noutput y", 18, "invalid constant '1x'"),
+            ("input x\ny = mul x x\n", 20, "circuit has no outputs"),
+        ];
+        for (input, position, message) in cases {
+            let error = parse_circuit::<F>(input).unwrap_err();
+            assert_eq!(error, ParseError { position, message: String::from(message) }, "{}", input);
+        }
+        let error = parse_circuit::<F>("input x\ny = add x z\noutput y").unwrap_err();
+        assert_eq!(error.location("input x\ny = add x z\noutput y"), (2, 11));
+    }
+}
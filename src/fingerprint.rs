@@ -0,0 +1,158 @@
+use std::iter;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+
+use crate::small_fields;
+use crate::sum_check::Data;
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 2.1
+// Reed-Solomon fingerprinting: Alice and Bob hold files a and b of the same
+// length n, as vectors of field elements, and want to check whether a = b
+// while communicating much less than n symbols.  Alice picks a random r in F
+// and sends r together with the fingerprint
+//   h_a(r) = a_0 + a_1 r + ... + a_{n-1} r^{n-1},
+// a single symbol of the Reed-Solomon encoding of a, and Bob outputs EQUAL
+// if h_b(r) = h_a(r).  If a != b then h_a - h_b is a nonzero polynomial of
+// degree at most n - 1, with at most n - 1 roots, so Bob is fooled with
+// probability at most (n - 1)/|F| < n/|F|.  Only two field elements are sent,
+// O(log n) bits when |F| is polynomial in n.
+//
+// Bytes are mapped to field elements directly when |F| > 256.  Over a smaller
+// prime field p each byte is split into its base p digits, so that different
+// files still give different symbol vectors, and the bound applies with n the
+// number of symbols.
+
+/// Number of symbols encoding one byte: 1 if |F| > 256, and otherwise the
+/// number of base p digits of a byte.
+pub fn symbols_per_byte<F: PrimeField>() -> usize {
+    if F::MODULUS_BIT_SIZE > 8 {
+        return 1;
+    }
+    let p = small_fields::field_size::<F>();
+    iter::successors(Some(1u64), |power| Some(power * p))
+        .take_while(|power| *power < 256)
+        .count()
+}
+
+/// Symbols of a file, with each byte as a field element, or as its base p
+/// digits from the least significant if |F| = p <= 256.
+pub fn to_symbols<F: PrimeField>(data: &[u8]) -> Vec<F> {
+    let digits = symbols_per_byte::<F>();
+    if digits == 1 {
+        return data.iter().map(|b| F::from(*b as u64)).collect();
+    }
+    let p = small_fields::field_size::<F>();
+    data.iter()
+        .flat_map(|b| iter::successors(Some(*b as u64), |rest| Some(rest / p)).take(digits))
+        .map(|rest| F::from(rest % p))
+        .collect()
+}
+
+/// Fingerprint h_a(r) of the file `symbols` at `r`.
+pub fn fingerprint<F: Field>(symbols: &[F], r: F) -> F {
+    symbols.iter().rev().fold(F::zero(), |acc, a| acc * r + a)
+}
+
+/// Alice's side: send a random r and the fingerprint of her file at r.
+pub fn send_fingerprint<F: PrimeField>(symbols: &[F], ch: &Channel<Data<F>>, log: &Log) {
+    let mut rng = rand::thread_rng();
+    log.write(String::from("A picks r uniformly at random and computes h_a(r)"));
+    let r = F::rand(&mut rng);
+    let (r, value) = (Data::Scalar(r), Data::Scalar(fingerprint(symbols, r)));
+    log.write(format!("A --> (r = {}, h_a(r) = {})", r, value));
+    ch.send(r);
+    ch.send(value);
+}
+
+/// Bob's side: compare the received fingerprint with that of his file, and
+/// return whether he outputs EQUAL.
+pub fn check_fingerprint<F: PrimeField>(symbols: &[F], ch: &Channel<Data<F>>, log: &Log) -> bool {
+    let r = ch.receive().to_scalar().expect("expected random point");
+    let value = ch.receive().to_scalar().expect("expected fingerprint");
+    let own = fingerprint(symbols, r);
+    log.write(format!("B computes h_b(r) = {}", Data::Scalar(own)));
+    let equal = own == value;
+    log.write(format!("B outputs {}", if equal { "EQUAL" } else { "NOT EQUAL" }));
+    equal
+}
+
+/// Alice, holding the file a.
+pub struct Alice<F: Field> {
+    pub symbols: Arc<Vec<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for Alice<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        send_fingerprint(&self.symbols, &ch, &log);
+    }
+}
+
+/// Bob, holding the file b of the same length.
+pub struct Bob<F: Field> {
+    pub symbols: Arc<Vec<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for Bob<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        check_fingerprint(&self.symbols, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::{Polynomial,DenseUVPolynomial};
+    use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+    use ark_test_curves::bls12_381::Fr;
+    use crate::small_fields::{F13,F251,F257};
+    use crate::test_utils;
+
+    fn run<F: PrimeField>(a: &[u8], b: &[u8]) -> bool {
+        let alice = Alice { symbols: Arc::new(to_symbols::<F>(a)) };
+        test_utils::run_against(alice, |ch, log| check_fingerprint(&to_symbols::<F>(b), ch, log))
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let symbols = [3u64, 1, 4, 1, 5].map(F13::from).to_vec();
+        let r = F13::from(7u64);
+        let poly = UniPoly::from_coefficients_slice(&symbols);
+        assert_eq!(fingerprint(&symbols, r), poly.evaluate(&r));
+
+        // nearly equal files collide exactly at the roots of h_a - h_b, of
+        // which there are at most n - 1
+        let other = [3u64, 1, 4, 2, 4].map(F13::from).to_vec();
+        let collisions: Vec<u64> = (0..13)
+            .filter(|r| fingerprint(&symbols, F13::from(*r)) == fingerprint(&other, F13::from(*r)))
+            .collect();
+        // h_a - h_b = r^4 - r^3 = r^3 (r - 1)
+        assert_eq!(collisions, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_to_symbols() {
+        // bytes differing by a multiple of 13 or 251 are still distinguished
+        assert_eq!(symbols_per_byte::<F13>(), 3);
+        assert_eq!(to_symbols::<F13>(b"\x00\xff"), [0u64, 0, 0, 8, 6, 1].map(F13::from).to_vec());
+        assert_ne!(to_symbols::<F13>(b"a\x00"), to_symbols::<F13>(b"a\x0d"));
+        assert_eq!(symbols_per_byte::<F251>(), 2);
+        assert_ne!(to_symbols::<F251>(b"a\x00"), to_symbols::<F251>(b"a\xfb"));
+        assert_eq!(symbols_per_byte::<F257>(), 1);
+        assert_eq!(to_symbols::<F257>(b"a\xfb"), [97u64, 251].map(F257::from).to_vec());
+    }
+
+    #[test]
+    fn test_protocol() {
+        let file = b"The quick brown fox jumps over the lazy dog";
+        assert!(run::<F13>(file, file));
+
+        // over a large field a difference is detected except with negligible
+        // probability
+        let other = b"The quick brown fox jumps over the lazy cog";
+        assert!(run::<Fr>(file, file));
+        assert!(!run::<Fr>(file, other));
+    }
+}
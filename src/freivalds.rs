@@ -0,0 +1,208 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ndarray::{Array1,Array2};
+use rand::Rng;
+
+use crate::polynomials;
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 2.2
+// Freivalds' algorithm: given n x n matrices A and B, and a claimed product C
+// sent by the prover, the verifier picks a vector x and accepts if
+// C x = A (B x), in O(n^2) time rather than the time to multiply A and B.  If
+// C != A B then some row d of D = C - A B is nonzero, and the verifier is
+// fooled only if <d, x> = 0.
+//
+// With x = (1, r, ..., r^{n-1}) for a random r, as in the book, <d, x> is a
+// nonzero polynomial of degree at most n - 1 in r, so the verifier is fooled
+// with probability at most (n - 1)/|F| and only one random field element is
+// needed.  With x uniformly random in F^n, <d, x> is uniformly distributed,
+// and the probability is at most 1/|F| at the cost of n random elements.
+
+/// Choice of the vector x of the check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TestVector {
+    /// x = (1, r, ..., r^{n-1}) for a random r.
+    #[default]
+    Powers,
+    /// x uniformly random in F^n.
+    Random,
+}
+
+impl TestVector {
+    /// Random test vector of length `n`.
+    pub fn sample<F: Field>(&self, n: usize, rng: &mut impl Rng) -> Array1<F> {
+        match self {
+            TestVector::Powers => powers_vector(F::rand(rng), n),
+            TestVector::Random => Array1::from_shape_simple_fn(n, || F::rand(rng)),
+        }
+    }
+
+    /// Bound on the probability of accepting a wrong product with `n`
+    /// columns over a field of the given size.
+    pub fn error_bound(&self, n: usize, field_size: u64) -> f64 {
+        let bound = match self {
+            TestVector::Powers => n.saturating_sub(1) as f64 / field_size as f64,
+            TestVector::Random => 1.0 / field_size as f64,
+        };
+        bound.min(1.0)
+    }
+}
+
+impl fmt::Display for TestVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestVector::Powers => write!(f, "x = (1, r, ..., r^(n-1))"),
+            TestVector::Random => write!(f, "x uniformly random"),
+        }
+    }
+}
+
+/// The vector (1, r, ..., r^{n-1}).
+pub fn powers_vector<F: Field>(r: F, n: usize) -> Array1<F> {
+    let mut x = Array1::<F>::default(n);
+    let mut r_power = F::one();
+    for entry in x.iter_mut() {
+        *entry = r_power;
+        r_power *= r;
+    }
+    x
+}
+
+/// Check C x = A (B x).
+pub fn check<F: Field>(a: &Array2<F>, b: &Array2<F>, c: &Array2<F>, x: &Array1<F>) -> bool {
+    c.dot(x) == a.dot(&b.dot(x))
+}
+
+/// Prover side: send the claimed product.
+pub fn prove<F: PrimeField>(c: &Array2<F>, ch: &Channel<Data<F>>, log: &Log) {
+    let data = Data::Matrix(c.clone());
+    log.write(format!("P --> (C =\n{})", data));
+    ch.send(data);
+}
+
+/// Verifier side: receive the claimed product of `a` and `b`, check it with
+/// a test vector of the given kind, and send and return the decision.
+pub fn verify<F: PrimeField>(a: &Array2<F>, b: &Array2<F>, test_vector: TestVector, ch: &Channel<Data<F>>, log: &Log) -> bool {
+    assert_eq!(a.ncols(), b.nrows(), "inner dimensions must agree");
+    let mut rng = rand::thread_rng();
+    let c = ch.receive().to_matrix().expect("expected claimed product");
+    if c.dim() != (a.nrows(), b.ncols()) {
+        log.write(String::from("V rejects a product with the wrong dimensions"));
+        let data = Data::Decision(false);
+        log.write(format!("V --> ({})", data));
+        ch.send(data);
+        return false;
+    }
+
+    log.write(format!("V picks {}", test_vector));
+    let x = test_vector.sample(c.ncols(), &mut rng);
+    log.write(format!("  x = {}", polynomials::format_point(x.as_slice().unwrap())));
+    let (cx, abx) = (c.dot(&x), a.dot(&b.dot(&x)));
+    log.write(String::from("V checks C x == A (B x)"));
+    log.write(format!("  {} ?= {}", polynomials::format_point(cx.as_slice().unwrap()), polynomials::format_point(abx.as_slice().unwrap())));
+
+    let data = Data::Decision(cx == abx);
+    log.write(format!("V --> ({})", data));
+    ch.send(data);
+    cx == abx
+}
+
+/// Prover claiming that `c` is the product.
+pub struct FreivaldsProver<F: Field> {
+    pub c: Arc<Array2<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for FreivaldsProver<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        prove(&self.c, &ch, &log);
+    }
+}
+
+pub struct FreivaldsVerifier<F: Field> {
+    pub a: Arc<Array2<F>>,
+    pub b: Arc<Array2<F>>,
+    pub test_vector: TestVector,
+}
+
+impl<F: PrimeField> IP<Data<F>> for FreivaldsVerifier<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        verify(&self.a, &self.b, self.test_vector, &ch, &log);
+    }
+}
+
+
+#[derive(Clone)]
+pub enum Data<F: Field> {
+    Matrix(Array2<F>),
+    Decision(bool),
+}
+
+impl<F: Field> Data<F> {
+    pub fn to_matrix(&self) -> Option<Array2<F>> {
+        if let Data::Matrix(m) = self { Some(m.clone()) } else { None }
+    }
+
+    pub fn to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
+    }
+}
+
+impl<F: PrimeField> fmt::Display for Data<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Data::Matrix(m) => {
+                let rows: Vec<String> = m.rows()
+                    .into_iter()
+                    .map(|row| row.iter().map(|x| format!("{:>4}", polynomials::format_field(*x, false))).collect())
+                    .collect();
+                write!(f, "{}", rows.join("\n"))
+            }
+            Data::Decision(b) => {
+                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::small_fields::F13;
+    use crate::test_utils;
+
+    fn random_matrix(rows: usize, cols: usize) -> Array2<F13> {
+        let mut rng = rand::thread_rng();
+        Array2::from_shape_simple_fn((rows, cols), || F13::rand(&mut rng))
+    }
+
+    fn run(a: Array2<F13>, b: Array2<F13>, c: Array2<F13>, test_vector: TestVector) -> bool {
+        let prover = FreivaldsProver { c: Arc::new(c) };
+        test_utils::run_against(prover, |ch, log| verify(&a, &b, test_vector, ch, log))
+    }
+
+    #[test]
+    fn test_freivalds() {
+        let (a, b) = (random_matrix(3, 4), random_matrix(4, 3));
+        let c = a.dot(&b);
+        assert!(run(a.clone(), b.clone(), c.clone(), TestVector::Powers));
+        assert!(run(a.clone(), b.clone(), c.clone(), TestVector::Random));
+        assert!(!run(a.clone(), b.clone(), random_matrix(2, 2), TestVector::Powers));
+
+        // D = C - A B with first row 6 - 5r + r^2 = (r - 2)(r - 3) fools the
+        // powers test for exactly 2 = n - 1 values of r
+        let mut wrong = c;
+        for (j, d) in [6u64, 8, 1].into_iter().enumerate() {
+            wrong[(0, j)] += F13::from(d);
+        }
+        let fooled: Vec<u64> = (0..13)
+            .filter(|r| check(&a, &b, &wrong, &powers_vector(F13::from(*r), 3)))
+            .collect();
+        assert_eq!(fooled, vec![2, 3]);
+        assert_eq!(TestVector::Powers.error_bound(3, 13), 2.0 / 13.0);
+    }
+}
@@ -0,0 +1,496 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::Polynomial;
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::ip::{IP,Channel,Log};
+use crate::merkle::{MerkleHash,MerkleTree,MerklePath};
+use crate::transcript::Transcript;
+use crate::group_utils;
+
+// PAZK, Section 10.4.4
+// FRI tests that a committed codeword, the evaluations of a function over a
+// multiplicative subgroup L_0, is close to a polynomial of low degree.  Each
+// round the prover splits f(X) = f_E(X^2) + X*f_O(X^2) and folds the codeword
+// into the evaluations of f_E + alpha*f_O over the squares L_1 = {x^2 : x in
+// L_0}, halving both degree and domain size, until the degree bound reaches
+// zero and the final codeword is sent in the clear.  The verifier then checks
+// the folding at random query positions through Merkle openings.
+
+/// Parameters of an execution of FRI over a domain of size 2^log_domain_size
+/// testing proximity to polynomials of degree less than the domain size
+/// divided by the blowup factor 2^log_blowup.
+#[derive(Clone, Copy, Debug)]
+pub struct FriParams {
+    pub log_domain_size: usize,
+    pub log_blowup: usize,
+    pub num_queries: usize,
+}
+
+impl FriParams {
+    pub fn domain_size(&self) -> usize {
+        1 << self.log_domain_size
+    }
+
+    pub fn degree_bound(&self) -> usize {
+        1 << (self.log_domain_size - self.log_blowup)
+    }
+
+    /// Number of folding rounds, after which the codeword should be constant.
+    pub fn num_rounds(&self) -> usize {
+        self.log_domain_size - self.log_blowup
+    }
+
+    /// Generator of the evaluation domain of the codeword in the given round.
+    pub fn domain_generator<F: PrimeField>(&self, round: usize) -> F {
+        F::get_root_of_unity((self.domain_size() >> round) as u64)
+            .expect("field has no evaluation domain of the required size")
+    }
+}
+
+/// Evaluate `poly` over the domain of size `n` generated by `omega`.
+pub fn encode<F: Field>(poly: &UniPoly<F>, omega: F, n: usize) -> Vec<F> {
+    (0..n as u64)
+        .map(|j| poly.evaluate(&omega.pow([j])))
+        .collect()
+}
+
+/// Fold a codeword over the domain generated by `omega` into a codeword of
+/// half the length over the domain generated by `omega^2`, using
+///   f'(x^2) = (f(x) + f(-x))/2 + alpha*(f(x) - f(-x))/(2x).
+pub fn fold<F: Field>(codeword: &[F], alpha: F, omega: F) -> Vec<F> {
+    let half = codeword.len() / 2;
+    (0..half)
+        .map(|j| fold_pair(codeword[j], codeword[j + half], alpha, omega.pow([j as u64])))
+        .collect()
+}
+
+/// Fold the values f(x) and f(-x) into the value at x^2 of the folded codeword.
+fn fold_pair<F: Field>(f_x: F, f_neg_x: F, alpha: F, x: F) -> F {
+    let two_inv = F::from(2u64).inverse().unwrap();
+    let even = (f_x + f_neg_x) * two_inv;
+    let odd = (f_x - f_neg_x) * two_inv * x.inverse().unwrap();
+    even + alpha * odd
+}
+
+/// Codewords of each round of FRI, with Merkle trees for the committed ones.
+struct FriLayers<F: PrimeField, H: MerkleHash> {
+    codewords: Vec<Vec<F>>,
+    trees: Vec<MerkleTree<H>>,
+}
+
+impl<F: PrimeField, H: MerkleHash> FriLayers<F, H> {
+    fn new(codeword: Vec<F>) -> Self {
+        FriLayers { codewords: vec![codeword], trees: Vec::new() }
+    }
+
+    fn last(&self) -> &[F] {
+        self.codewords.last().unwrap()
+    }
+
+    /// Commit to the current codeword, returning the Merkle root.
+    fn commit(&mut self) -> H::Digest {
+        let tree = MerkleTree::<H>::new(self.last());
+        let root = tree.root();
+        self.trees.push(tree);
+        root
+    }
+
+    fn fold(&mut self, alpha: F, omega: F) {
+        let folded = fold(self.last(), alpha, omega);
+        self.codewords.push(folded);
+    }
+
+    /// Open the pair of entries f(x), f(-x) of each committed codeword which
+    /// are used to check the folding at query position `q` of the first domain.
+    fn open(&self, q: usize) -> Vec<(F, MerklePath<H>)> {
+        let mut openings = Vec::with_capacity(2 * self.trees.len());
+        for (codeword, tree) in self.codewords.iter().zip(&self.trees) {
+            let half = codeword.len() / 2;
+            let j = q % half;
+            openings.push((codeword[j], tree.open(j)));
+            openings.push((codeword[j + half], tree.open(j + half)));
+        }
+        openings
+    }
+}
+
+/// Check that the final codeword sent in the clear is constant, i.e. the
+/// evaluations of a polynomial of degree zero.
+pub fn check_final_codeword<F: PrimeField>(params: &FriParams, codeword: &[F]) -> Result<(), String> {
+    if codeword.len() != params.domain_size() >> params.num_rounds() {
+        return Err(format!("final codeword has length {} instead of {}",
+            codeword.len(), params.domain_size() >> params.num_rounds()));
+    }
+    if codeword.iter().any(|v| *v != codeword[0]) {
+        return Err(String::from("final codeword is not constant"));
+    }
+    Ok(())
+}
+
+/// Check the openings for query position `q` against the Merkle roots of the
+/// committed codewords, the folding challenges, and the final codeword.  On
+/// failure, returns a description of the failed check.
+pub fn check_query<F: PrimeField, H: MerkleHash>(
+        params: &FriParams,
+        roots: &[H::Digest],
+        alphas: &[F],
+        final_codeword: &[F],
+        q: usize,
+        openings: &[(F, MerklePath<H>)]) -> Result<(), String> {
+    let n_rounds = params.num_rounds();
+    if roots.len() != n_rounds || alphas.len() != n_rounds || openings.len() != 2 * n_rounds {
+        return Err(String::from("wrong number of commitments or openings"));
+    }
+
+    // position and value of the entry of the current codeword determined by
+    // folding the previous codeword
+    let mut expected = (q % params.domain_size(), None);
+    for round in 0..n_rounds {
+        let half = (params.domain_size() >> round) / 2;
+        let j = q % half;
+        let (f_x, path_x) = &openings[2 * round];
+        let (f_neg_x, path_neg_x) = &openings[2 * round + 1];
+
+        if path_x.index != j || path_neg_x.index != j + half {
+            return Err(format!("round {round}: openings at wrong positions"));
+        }
+        if !path_x.verify(&roots[round], f_x) || !path_neg_x.verify(&roots[round], f_neg_x) {
+            return Err(format!("round {round}: Merkle path does not match committed root"));
+        }
+        if let (pos, Some(value)) = expected {
+            let opened = if pos == j { f_x } else { f_neg_x };
+            if *opened != value {
+                return Err(format!("round {round}: opened value {} at position {pos} does not match folded value {}",
+                    opened.into_bigint(), value.into_bigint()));
+            }
+        }
+
+        let x = params.domain_generator::<F>(round).pow([j as u64]);
+        expected = (j, Some(fold_pair(*f_x, *f_neg_x, alphas[round], x)));
+    }
+
+    if let (pos, Some(value)) = expected {
+        if final_codeword[pos] != value {
+            return Err(format!("final codeword value {} at position {pos} does not match folded value {}",
+                final_codeword[pos].into_bigint(), value.into_bigint()));
+        }
+    }
+    Ok(())
+}
+
+pub struct FriProver<F: PrimeField> {
+    pub params: FriParams,
+    pub poly: Arc< UniPoly<F> >,
+}
+
+impl<F: PrimeField, H: MerkleHash> IP<Data<F, H>> for FriProver<F> {
+    fn execute(&self, ch: Channel<Data<F, H>>, log: Log) {
+        let n = self.params.domain_size();
+        let omega: F = self.params.domain_generator(0);
+
+        log.write(format!("P evaluates p over domain L_0 generated by w = {} of size {n}", omega.into_bigint()));
+        let mut layers = FriLayers::<F, H>::new(encode(&self.poly, omega, n));
+
+        // commit phase
+
+        for round in 0..self.params.num_rounds() {
+            log.write(format!("  f_{round} = {}", list_scalars(layers.last())));
+            let data = Data::Root(layers.commit());
+            log.write(format!("P --> (root_{round} = {data})"));
+            ch.send(data);
+
+            let alpha = ch.receive().to_scalar().unwrap();
+
+            log.write(format!("P folds f_{round} with alpha_{round} into f_{} over the squares of L_{round}", round+1));
+            layers.fold(alpha, self.params.domain_generator(round));
+        }
+
+        let data = Data::Codeword(layers.last().to_vec());
+        log.write(format!("P --> (f_{} = {data})", self.params.num_rounds()));
+        ch.send(data);
+
+        // query phase
+
+        for _ in 0..self.params.num_queries {
+            let data = ch.receive();
+            if let Data::Decision(_) = data { return; }
+            let q = data.to_index().unwrap();
+
+            log.write(format!("P opens f_i(x), f_i(-x) in each committed codeword for query {q}"));
+            for (value, path) in layers.open(q) {
+                let data = Data::Opening(value, path);
+                log.write(format!("P --> ({data})"));
+                ch.send(data);
+            }
+        }
+    }
+}
+
+pub struct FriVerifier {
+    pub params: FriParams,
+}
+
+impl<F: PrimeField, H: MerkleHash> IP<Data<F, H>> for FriVerifier {
+    fn execute(&self, ch: Channel<Data<F, H>>, log: Log) {
+        let mut rng = rand::thread_rng();
+        let n_rounds = self.params.num_rounds();
+
+        // commit phase
+
+        let mut roots = Vec::with_capacity(n_rounds);
+        let mut alphas = Vec::with_capacity(n_rounds);
+        for round in 0..n_rounds {
+            roots.push(ch.receive().to_root().unwrap());
+
+            log.write(format!("V picks alpha_{round} uniformly at random"));
+            let alpha = F::rand(&mut rng);
+            alphas.push(alpha);
+
+            let data = Data::Scalar(alpha);
+            log.write(format!("V --> (alpha_{round} = {data})"));
+            ch.send(data);
+        }
+
+        let final_codeword = ch.receive().to_codeword().unwrap();
+
+        log.write(format!("V checks f_{n_rounds} is constant"));
+        if let Err(message) = check_final_codeword(&self.params, &final_codeword) {
+            reject(&ch, &log, message);
+            return;
+        }
+
+        // query phase
+
+        for _ in 0..self.params.num_queries {
+            log.write(String::from("V picks query position q in L_0 uniformly at random"));
+            let q = rand::Rng::gen_range(&mut rng, 0..self.params.domain_size());
+            let data = Data::Index(q);
+            log.write(format!("V --> (q = {data})"));
+            ch.send(data);
+
+            let openings: Vec<(F, MerklePath<H>)> = (0..2*n_rounds)
+                .map(|_| ch.receive().to_opening().unwrap())
+                .collect();
+
+            log.write(format!("V checks Merkle paths and folding consistency for query {q}"));
+            let result = check_query(&self.params, &roots, &alphas, &final_codeword, q, &openings);
+            if let Err(message) = result {
+                reject(&ch, &log, message);
+                return;
+            }
+        }
+
+        let data = Data::Decision(true);
+        log.write(format!("V --> ({data})"));
+        ch.send(data);
+    }
+}
+
+fn reject<F: PrimeField, H: MerkleHash>(ch: &Channel<Data<F, H>>, log: &Log, message: String) {
+    log.write(format!("V rejects: {message}"));
+    let data = Data::Decision(false);
+    log.write(format!("V --> ({data})"));
+    ch.send(data);
+}
+
+/// Non-interactive FRI proof obtained by the Fiat-Shamir transformation.
+pub struct FriProof<F: PrimeField, H: MerkleHash> {
+    pub roots: Vec<H::Digest>,
+    pub final_codeword: Vec<F>,
+    pub queries: Vec<Vec<(F, MerklePath<H>)>>,
+}
+
+impl<F: PrimeField, H: MerkleHash> FriProof<F, H> {
+    /// Size of the proof as a number of field elements and digests.
+    pub fn size(&self) -> (usize, usize) {
+        let n_scalars = self.final_codeword.len()
+            + self.queries.iter().map(|q| q.len()).sum::<usize>();
+        let n_digests = self.roots.len()
+            + self.queries.iter().flatten().map(|(_, path)| path.siblings.len()).sum::<usize>();
+        (n_scalars, n_digests)
+    }
+}
+
+/// Run the FRI prover with challenges derived from a hash of the transcript.
+pub fn prove<F: PrimeField, H: MerkleHash>(params: &FriParams, poly: &UniPoly<F>) -> FriProof<F, H> {
+    let mut transcript = Transcript::new(b"FRI");
+    let mut layers = FriLayers::<F, H>::new(
+        encode(poly, params.domain_generator(0), params.domain_size()));
+
+    let mut roots = Vec::with_capacity(params.num_rounds());
+    for round in 0..params.num_rounds() {
+        let root = layers.commit();
+        transcript.append_bytes(root.as_ref());
+        roots.push(root);
+        let alpha = transcript.challenge_scalar();
+        layers.fold(alpha, params.domain_generator(round));
+    }
+
+    let final_codeword = layers.last().to_vec();
+    transcript.append(&final_codeword);
+
+    let queries = (0..params.num_queries)
+        .map(|_| layers.open(transcript.challenge_index(params.domain_size())))
+        .collect();
+
+    FriProof { roots, final_codeword, queries }
+}
+
+/// Verify a non-interactive FRI proof, recomputing the challenges from the
+/// transcript.  On failure, returns a description of the failed check.
+pub fn verify<F: PrimeField, H: MerkleHash>(params: &FriParams, proof: &FriProof<F, H>) -> Result<(), String> {
+    if proof.roots.len() != params.num_rounds() || proof.queries.len() != params.num_queries {
+        return Err(String::from("wrong number of commitments or queries"));
+    }
+    let mut transcript = Transcript::new(b"FRI");
+    let alphas: Vec<F> = proof.roots
+        .iter()
+        .map(|root| {
+            transcript.append_bytes(root.as_ref());
+            transcript.challenge_scalar()
+        })
+        .collect();
+
+    check_final_codeword(params, &proof.final_codeword)?;
+    transcript.append(&proof.final_codeword);
+
+    for openings in &proof.queries {
+        let q = transcript.challenge_index(params.domain_size());
+        check_query(params, &proof.roots, &alphas, &proof.final_codeword, q, openings)?;
+    }
+    Ok(())
+}
+
+/// List field elements as integers, separated by spaces.
+fn list_scalars<F: PrimeField>(v: &[F]) -> String {
+    let ints: Vec<F::BigInt> = v.iter().map(|x| x.into_bigint()).collect();
+    group_utils::list_vec(&ints, " ")
+}
+
+
+pub enum Data<F: PrimeField, H: MerkleHash> {
+    Scalar(F),
+    Index(usize),
+    Root(H::Digest),
+    Codeword(Vec<F>),
+    Opening(F, MerklePath<H>),
+    Decision(bool),
+}
+
+impl<F: PrimeField, H: MerkleHash> Clone for Data<F, H> {
+    fn clone(&self) -> Self {
+        match self {
+            Data::Scalar(x) => Data::Scalar(*x),
+            Data::Index(q) => Data::Index(*q),
+            Data::Root(d) => Data::Root(d.clone()),
+            Data::Codeword(v) => Data::Codeword(v.clone()),
+            Data::Opening(x, path) => Data::Opening(*x, path.clone()),
+            Data::Decision(b) => Data::Decision(*b),
+        }
+    }
+}
+
+impl<F: PrimeField, H: MerkleHash> Data<F, H> {
+    pub fn to_scalar(&self) -> Option<F> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
+    }
+
+    pub fn to_index(&self) -> Option<usize> {
+        if let Data::Index(q) = self { Some(*q) } else { None }
+    }
+
+    pub fn to_root(&self) -> Option<H::Digest> {
+        if let Data::Root(d) = self { Some(d.clone()) } else { None }
+    }
+
+    pub fn to_codeword(&self) -> Option<Vec<F>> {
+        if let Data::Codeword(v) = self { Some(v.clone()) } else { None }
+    }
+
+    pub fn to_opening(&self) -> Option<(F, MerklePath<H>)> {
+        if let Data::Opening(x, path) = self { Some((*x, path.clone())) } else { None }
+    }
+
+    pub fn to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
+    }
+}
+
+impl<F: PrimeField, H: MerkleHash> fmt::Display for Data<F, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Data::Scalar(x) => {
+                write!(f, "{}", x.into_bigint())
+            }
+            Data::Index(q) => {
+                write!(f, "{}", q)
+            }
+            Data::Root(d) => {
+                write!(f, "{}", d)
+            }
+            Data::Codeword(v) => {
+                write!(f, "{}", list_scalars(v))
+            }
+            Data::Opening(x, path) => {
+                write!(f, "f[{}] = {}, path = {}", path.index, x.into_bigint(), path)
+            }
+            Data::Decision(b) => {
+                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_poly::DenseUVPolynomial;
+    use crate::small_fields::F257 as F;
+    use crate::merkle::{ToyHash,Sha256Hash};
+
+    const PARAMS: FriParams = FriParams { log_domain_size: 5, log_blowup: 2, num_queries: 4 };
+
+    #[test]
+    fn test_fold() {
+        // folding the evaluations of p(X) = p_E(X^2) + X*p_O(X^2) gives the
+        // evaluations of p_E + alpha*p_O over the squares
+        let mut rng = rand::thread_rng();
+        let poly = UniPoly::<F>::rand(7, &mut rng);
+        let alpha = F::rand(&mut rng);
+        let omega: F = PARAMS.domain_generator(0);
+
+        let folded_poly = UniPoly::from_coefficients_vec(
+            poly.coeffs.chunks(2).map(|c| c[0] + alpha * c[1]).collect());
+
+        let folded = fold(&encode(&poly, omega, 32), alpha, omega);
+        assert_eq!(folded, encode(&folded_poly, omega * omega, 16));
+    }
+
+    #[test]
+    fn test_prove_verify() {
+        let mut rng = rand::thread_rng();
+        let poly = UniPoly::<F>::rand(PARAMS.degree_bound() - 1, &mut rng);
+        let proof = prove::<F, Sha256Hash>(&PARAMS, &poly);
+        assert_eq!(verify(&PARAMS, &proof), Ok(()));
+
+        let proof = prove::<F, ToyHash>(&PARAMS, &poly);
+        assert_eq!(verify(&PARAMS, &proof), Ok(()));
+    }
+
+    #[test]
+    fn test_high_degree_rejected() {
+        // p(X) = X^(2d - 1) + 1 folds to a final codeword alpha_0*alpha_1*alpha_2*X + 1,
+        // which is not constant since the derived challenges are nonzero
+        let mut coeffs = vec![F::from(0u64); 2 * PARAMS.degree_bound()];
+        coeffs[0] = F::from(1u64);
+        coeffs[2 * PARAMS.degree_bound() - 1] = F::from(1u64);
+        let poly = UniPoly::from_coefficients_vec(coeffs);
+        let proof = prove::<F, Sha256Hash>(&PARAMS, &poly);
+        assert!(verify(&PARAMS, &proof).is_err());
+    }
+}
@@ -0,0 +1,425 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::Polynomial;
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials;
+use crate::circuit::{GateType,LayeredCircuit};
+use crate::multilinear::{self,DenseMLE};
+use crate::sum_check::{self,SumCheckPolynomial,SumCheckFailure,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 4.6
+// The GKR protocol for a layered arithmetic circuit of depth d.  The prover
+// claims the outputs, and the verifier reduces a claim about W_0~(z_0) for a
+// random z_0 to a claim about W_1~(z_1), and so on down to a claim about the
+// multilinear extension of the inputs, which it checks itself.  For layer i,
+// the claim m_i = W_i~(z_i) is reduced by sum-check applied to
+//   f(b, c) = add_i~(z_i, b, c) (W_{i+1}~(b) + W_{i+1}~(c))
+//           + mul_i~(z_i, b, c) W_{i+1}~(b) W_{i+1}~(c)
+// in 2 k_{i+1} variables, which ends with the need to know W_{i+1}~ at two
+// points b* and c*.  To reduce these two claims to one, the prover sends the
+// restriction q(t) = W_{i+1}~(l(t)) to the line l with l(0) = b*, l(1) = c*,
+// from which the verifier takes W_{i+1}~(b*) = q(0) and W_{i+1}~(c*) = q(1),
+// and then picks a random point z_{i+1} = l(r*) on the line with the new claim
+// m_{i+1} = q(r*).  The verifier evaluates the wiring predicates itself.
+//
+// The variables of f are b = (x_0, ..., x_{k-1}) and c = (x_k, ..., x_{2k-1}),
+// and unlike a standalone sum-check the verifier sends the last challenge, as
+// the prover needs all of (b*, c*) to compute q.
+
+/// The polynomial f(b, c) of the sum-check for a layer, represented by the
+/// gates of layer i rather than by tables of the wiring predicates over all
+/// 2^{2 k_{i+1}} pairs (b, c), so that the prover does O(S_i + S_{i+1}) work
+/// for a layer with S_i gates (Section 4.6.5, following Libra).
+///
+/// Since c = (x_k, ..., x_{2k-1}) is bound first, in the rounds for c the sum
+/// of f over the remaining variables is that of h_1(c) + h_2(c) W~(c) with
+///   h_1(c) = sum_b add~(z, b, c) W~(b),
+///   h_2(c) = sum_b add~(z, b, c) + mul~(z, b, c) W~(b),
+/// and once c is bound to c* the sum in the rounds for b is that of
+/// h_1(b) + h_2(b) W~(b) with
+///   h_1(b) = add~(z, b, c*) W~(c*),
+///   h_2(b) = add~(z, b, c*) + mul~(z, b, c*) W~(c*).
+/// Each h is multilinear with a table of size 2^k filled in from the gates,
+/// as only the entries at the inputs of some gate are nonzero.  f has degree
+/// 2 in each variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayerPolynomial<F: Field> {
+    /// Gates of layer i as (type, left, right, eq(z_i, a)) for gate a.
+    gates: Vec<(GateType, usize, usize, F)>,
+    /// W_{i+1}~ on the hypercube.
+    w: DenseMLE<F>,
+    // h_1, h_2 and W~ restricted to the bound variables of the current phase
+    h_1: DenseMLE<F>,
+    h_2: DenseMLE<F>,
+    w_bound: DenseMLE<F>,
+    // challenges bound to the variables from x_{2k-1} down, in order
+    bound: Vec<F>,
+}
+
+impl<F: Field> LayerPolynomial<F> {
+    /// Construct f for layer i and the point z_i, from the values of layer
+    /// i+1.
+    pub fn new(circuit: &LayeredCircuit, i: usize, z: &[F], next_values: &[F]) -> LayerPolynomial<F> {
+        let eq_z = multilinear::eq_table(z);
+        let gates: Vec<(GateType, usize, usize, F)> = circuit.layers[i]
+            .iter()
+            .zip(eq_z.evals)
+            .map(|(gate, eq)| (gate.gate_type, gate.left, gate.right, eq))
+            .collect();
+        let w = circuit.layer_mle(i + 1, next_values);
+
+        let k = w.num_vars;
+        let mut h_1 = vec![F::zero(); 1 << k];
+        let mut h_2 = vec![F::zero(); 1 << k];
+        for (gate_type, left, right, eq) in &gates {
+            match gate_type {
+                GateType::Add => {
+                    h_1[*right] += *eq * w.evals[*left];
+                    h_2[*right] += eq;
+                }
+                GateType::Mul => h_2[*right] += *eq * w.evals[*left],
+            }
+        }
+        LayerPolynomial {
+            gates,
+            h_1: DenseMLE::new(k, h_1),
+            h_2: DenseMLE::new(k, h_2),
+            w_bound: w.clone(),
+            w,
+            bound: Vec::new(),
+        }
+    }
+
+    // with c bound to c*, replace the tables by those for the rounds for b
+    fn start_second_phase(&mut self) {
+        let k = self.w.num_vars;
+        let eq_c = multilinear::eq_table(&self.bound);
+        let w_c = self.w_bound.evals[0];
+        let mut h_1 = vec![F::zero(); 1 << k];
+        let mut h_2 = vec![F::zero(); 1 << k];
+        for (gate_type, left, right, eq) in &self.gates {
+            let weight = *eq * eq_c.evals[*right];
+            match gate_type {
+                GateType::Add => {
+                    h_1[*left] += weight * w_c;
+                    h_2[*left] += weight;
+                }
+                GateType::Mul => h_2[*left] += weight * w_c,
+            }
+        }
+        self.h_1 = DenseMLE::new(k, h_1);
+        self.h_2 = DenseMLE::new(k, h_2);
+        self.w_bound = self.w.clone();
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for LayerPolynomial<F> {
+    fn num_vars(&self) -> usize {
+        2 * self.w.num_vars - self.bound.len()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![2; self.num_vars()]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polynomials::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    // sum of h_1 + h_2 W~ over the pairs of entries differing in the last
+    // variable of the current phase, evaluated at 0, 1 and 2 along each pair
+    fn round_evaluations(&self) -> Vec<F> {
+        let half = self.h_1.evals.len() / 2;
+        let mut evals = vec![F::zero(); 3];
+        for idx in 0..half {
+            let mut h_1 = self.h_1.evals[idx];
+            let mut h_2 = self.h_2.evals[idx];
+            let mut w = self.w_bound.evals[idx];
+            let d_1 = self.h_1.evals[idx + half] - h_1;
+            let d_2 = self.h_2.evals[idx + half] - h_2;
+            let d_w = self.w_bound.evals[idx + half] - w;
+            for eval in evals.iter_mut() {
+                *eval += h_1 + h_2 * w;
+                h_1 += d_1;
+                h_2 += d_2;
+                w += d_w;
+            }
+        }
+        evals
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.h_1.fix_last_variable(r);
+        self.h_2.fix_last_variable(r);
+        self.w_bound.fix_last_variable(r);
+        self.bound.insert(0, r);
+        if self.bound.len() == self.w.num_vars {
+            self.start_second_phase();
+        }
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        let full_point = [point, &self.bound].concat();
+        let (b, c) = full_point.split_at(self.w.num_vars);
+        let (eq_b, eq_c) = (multilinear::eq_table(b), multilinear::eq_table(c));
+        let (w_b, w_c) = (self.w.evaluate(b), self.w.evaluate(c));
+        self.gates.iter()
+            .map(|(gate_type, left, right, eq)| {
+                let wiring = *eq * eq_b.evals[*left] * eq_c.evals[*right];
+                match gate_type {
+                    GateType::Add => wiring * (w_b + w_c),
+                    GateType::Mul => wiring * w_b * w_c,
+                }
+            })
+            .sum()
+    }
+}
+
+/// Point l(t) = b + t (c - b) on the line through b and c.
+pub fn line_point<F: Field>(b: &[F], c: &[F], t: F) -> Vec<F> {
+    b.iter().zip(c).map(|(b_i, c_i)| *b_i + t * (*c_i - b_i)).collect()
+}
+
+/// Restriction q(t) = w(l(t)) of a multilinear polynomial to the line through
+/// b and c, of degree at most the number of variables.
+pub fn restrict_to_line<F: Field>(w: &DenseMLE<F>, b: &[F], c: &[F]) -> UniPoly<F> {
+    let values: Vec<F> = (0..=w.num_vars as u64)
+        .map(|t| w.evaluate(&line_point(b, c, F::from(t))))
+        .collect();
+    polynomials::interpolate_evaluations(&values)
+}
+
+/// Check of the GKR verifier which failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GkrFailure<F> {
+    /// A round of the sum-check for layer i failed.
+    SumCheck { layer: usize, failure: SumCheckFailure<F> },
+    /// The restriction q to the line has degree larger than k_{i+1}.
+    LineDegree { layer: usize, degree: usize, bound: usize },
+    /// The final check value of the sum-check for layer i differs from f(b*, c*)
+    /// computed from q(0) and q(1).
+    Layer { layer: usize, evaluation: F, check_value: F },
+    /// The claim about the multilinear extension of the inputs is false.
+    Input { evaluation: F, claim: F },
+}
+
+impl<F: PrimeField> fmt::Display for GkrFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GkrFailure::SumCheck { layer, failure } => write!(f, "sum-check for layer {}: {}", layer, failure),
+            GkrFailure::LineDegree { layer, degree, bound } => {
+                write!(f, "q for layer {} has degree {} but W_{}~ has {} variables", layer + 1, degree, layer + 1, bound)
+            }
+            GkrFailure::Layer { layer, evaluation, check_value } => {
+                write!(f, "f(b*, c*) = {} for layer {} but final check value is {}",
+                    evaluation.into_bigint(), layer, check_value.into_bigint())
+            }
+            GkrFailure::Input { evaluation, claim } => {
+                write!(f, "input extension evaluates to {} but claim is {}", evaluation.into_bigint(), claim.into_bigint())
+            }
+        }
+    }
+}
+
+fn reject<F: PrimeField>(ch: &Channel<Data<F>>, log: &Log, failure: GkrFailure<F>) -> GkrFailure<F> {
+    log.write(format!("V check fails: {}", failure));
+    let decision = Data::Decision(false);
+    log.write(format!("V --> ({})", decision));
+    ch.send(decision);
+    failure
+}
+
+/// Prover side of the GKR protocol for the circuit on the given inputs.
+pub fn prove<F: PrimeField>(circuit: &LayeredCircuit, inputs: &[F], ch: &Channel<Data<F>>, log: &Log) {
+    log.write(String::from("P evaluates the circuit"));
+    let values = circuit.evaluate(inputs);
+    let data = Data::Evaluations(values[0].clone());
+    log.write(format!("P --> (outputs = {})", data));
+    ch.send(data);
+
+    let Data::Evaluations(mut z) = ch.receive() else { return };
+    for i in 0..circuit.depth() {
+        log.write(format!("P runs sum-check for layer {}", i));
+        let mut poly = LayerPolynomial::new(circuit, i, &z, &values[i + 1]);
+        let Some(challenges) = sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, ch, log) else { return };
+        let Some(r_0) = ch.receive().to_scalar() else { return };
+
+        // challenges are received for x_{2k-1} down to x_1, then x_0
+        let point: Vec<F> = std::iter::once(r_0).chain(challenges.into_iter().rev()).collect();
+        let (b, c) = point.split_at(circuit.layer_bits(i + 1));
+        log.write(format!("P restricts W_{}~ to the line through b* and c*", i + 1));
+        let q = restrict_to_line(&circuit.layer_mle(i + 1, &values[i + 1]), b, c);
+        let data = Data::Polynomial(q);
+        log.write(format!("P --> (q = {})", data));
+        ch.send(data);
+
+        let Some(t) = ch.receive().to_scalar() else { return };
+        z = line_point(b, c, t);
+    }
+    ch.receive();
+}
+
+/// Verifier side of the GKR protocol for the circuit on the given inputs,
+/// returning the outputs claimed by the prover if it accepts.
+pub fn verify<F: PrimeField>(circuit: &LayeredCircuit, inputs: &[F], ch: &Channel<Data<F>>, log: &Log) -> Result<Vec<F>, GkrFailure<F>> {
+    let mut rng = rand::thread_rng();
+    let outputs = ch.receive().to_evaluations().unwrap();
+
+    log.write(String::from("V picks z_0 uniformly at random"));
+    let mut z: Vec<F> = (0..circuit.layer_bits(0)).map(|_| F::rand(&mut rng)).collect();
+    log.write(format!("V --> (z_0 = {})", polynomials::format_point(&z)));
+    ch.send(Data::Evaluations(z.clone()));
+    let mut claim = circuit.layer_mle(0, &outputs).evaluate(&z);
+    log.write(format!("V computes claim m_0 = D~(z_0) = {} from the outputs", polynomials::format_field(claim, false)));
+
+    for i in 0..circuit.depth() {
+        let k = circuit.layer_bits(i + 1);
+        log.write(format!("V runs sum-check for layer {} with claimed sum m_{}", i, i));
+        let degrees = vec![2; 2 * k];
+        let (point, check_value) = sum_check::verify_rounds(claim, &degrees, ch, log)
+            .map_err(|failure| GkrFailure::SumCheck { layer: i, failure })?;
+        let data = Data::Scalar(point[0]);
+        log.write(format!("V --> (r_0 = {}) so that P can restrict to the line", data));
+        ch.send(data);
+
+        let q = ch.receive().to_polynomial().unwrap();
+        log.write(format!("V checks q has degree at most {}", k));
+        if q.degree() > k {
+            return Err(reject(ch, log, GkrFailure::LineDegree { layer: i, degree: q.degree(), bound: k }));
+        }
+
+        log.write(format!("V evaluates add_{}~ and mul_{}~ at (z_{}, b*, c*)", i, i, i));
+        let (b, c) = point.split_at(k);
+        let add = circuit.wiring_predicate(i, GateType::Add, &z, b, c);
+        let mul = circuit.wiring_predicate(i, GateType::Mul, &z, b, c);
+        let (w_b, w_c) = (q.evaluate(&F::zero()), q.evaluate(&F::one()));
+        let evaluation = add * (w_b + w_c) + mul * w_b * w_c;
+        log.write(String::from("V checks final check value equals f(b*, c*) with W~(b*) = q(0), W~(c*) = q(1)"));
+        if evaluation != check_value {
+            return Err(reject(ch, log, GkrFailure::Layer { layer: i, evaluation, check_value }));
+        }
+
+        log.write(String::from("V picks r* uniformly at random"));
+        let t = F::rand(&mut rng);
+        let data = Data::Scalar(t);
+        log.write(format!("V --> (r* = {})", data));
+        ch.send(data);
+        z = line_point(b, c, t);
+        claim = q.evaluate(&t);
+        log.write(format!("V sets z_{} = l(r*) and m_{} = q(r*) = {}", i + 1, i + 1, polynomials::format_field(claim, false)));
+    }
+
+    log.write(String::from("V checks m_d against the multilinear extension of the inputs"));
+    let evaluation = circuit.layer_mle(circuit.depth(), inputs).evaluate(&z);
+    if evaluation != claim {
+        return Err(reject(ch, log, GkrFailure::Input { evaluation, claim }));
+    }
+    let decision = Data::Decision(true);
+    log.write(format!("V --> ({})", decision));
+    ch.send(decision);
+    Ok(outputs)
+}
+
+pub struct GkrProver<F: Field> {
+    pub circuit: Arc<LayeredCircuit>,
+    pub inputs: Arc<Vec<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for GkrProver<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        prove(&self.circuit, &self.inputs, &ch, &log);
+    }
+}
+
+pub struct GkrVerifier<F: Field> {
+    pub circuit: Arc<LayeredCircuit>,
+    pub inputs: Arc<Vec<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for GkrVerifier<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        // failures are recorded in the log
+        let _ = verify(&self.circuit, &self.inputs, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::Fr;
+    use crate::circuit::Gate;
+    use crate::small_fields::F13;
+    use crate::test_utils;
+
+    fn example_circuit() -> LayeredCircuit {
+        LayeredCircuit::new(vec![
+            vec![Gate::mul(0, 0), Gate::mul(1, 2)],
+            vec![Gate::mul(0, 1), Gate::add(1, 2), Gate::mul(3, 3)],
+        ], 4)
+    }
+
+    fn run<F: PrimeField>(circuit: LayeredCircuit, prover_inputs: Vec<F>, inputs: Vec<F>) -> Result<Vec<F>, GkrFailure<F>> {
+        let circuit = Arc::new(circuit);
+        let prover = GkrProver { circuit: circuit.clone(), inputs: Arc::new(prover_inputs) };
+        test_utils::run_against(prover, |ch, log| verify(&circuit, &inputs, ch, log))
+    }
+
+    #[test]
+    fn test_layer_polynomial() {
+        let circuit = example_circuit();
+        let inputs: Vec<F13> = [2u64, 3, 4, 5].into_iter().map(F13::from).collect();
+        let values = circuit.evaluate(&inputs);
+        let z: Vec<F13> = (0..2).map(|_| F13::rand(&mut rand::thread_rng())).collect();
+        let mut poly = LayerPolynomial::new(&circuit, 1, &z, &values[2]);
+        assert_eq!(poly.hypercube_sum(), circuit.layer_mle(1, &values[1]).evaluate(&z));
+
+        // messages agree with brute force evaluation of f from the wiring
+        // predicates, through both phases
+        let w = circuit.layer_mle(2, &values[2]);
+        let f = |point: &[F13]| {
+            let (b, c) = point.split_at(2);
+            let add = circuit.wiring_predicate(1, GateType::Add, &z, b, c);
+            let mul = circuit.wiring_predicate(1, GateType::Mul, &z, b, c);
+            add * (w.evaluate(b) + w.evaluate(c)) + mul * w.evaluate(b) * w.evaluate(c)
+        };
+        let mut bound = Vec::new();
+        for j in (0..4).rev() {
+            assert_eq!(poly.round_evaluations(), sum_check::brute_force_evaluations(2, j, &bound, f));
+            let r = F13::rand(&mut rand::thread_rng());
+            poly.fix_last_variable(r);
+            bound.insert(0, r);
+        }
+        assert_eq!(poly.oracle_evaluation(&[]), f(&bound));
+    }
+
+    #[test]
+    fn test_restrict_to_line() {
+        let mut rng = rand::thread_rng();
+        let w = DenseMLE::<F13>::rand(3, &mut rng);
+        let b: Vec<F13> = (0..3).map(|_| F13::rand(&mut rng)).collect();
+        let c: Vec<F13> = (0..3).map(|_| F13::rand(&mut rng)).collect();
+        let q = restrict_to_line(&w, &b, &c);
+        assert_eq!(q.evaluate(&F13::from(0u64)), w.evaluate(&b));
+        assert_eq!(q.evaluate(&F13::from(1u64)), w.evaluate(&c));
+        let t = F13::rand(&mut rng);
+        assert_eq!(q.evaluate(&t), w.evaluate(&line_point(&b, &c, t)));
+    }
+
+    #[test]
+    fn test_gkr() {
+        let inputs: Vec<F13> = [2u64, 3, 4, 5].into_iter().map(F13::from).collect();
+        let outputs = run(example_circuit(), inputs.clone(), inputs).unwrap();
+        assert_eq!(outputs, vec![F13::from(36u64), F13::from(175u64)]);
+
+        // prover evaluating the circuit on other inputs is caught at the end
+        let inputs: Vec<Fr> = [2u64, 3, 4, 5].into_iter().map(Fr::from).collect();
+        let other: Vec<Fr> = [2u64, 3, 4, 6].into_iter().map(Fr::from).collect();
+        assert!(matches!(run(example_circuit(), other, inputs), Err(GkrFailure::Input { .. })));
+    }
+}
@@ -54,7 +54,7 @@ mod tests {
         let gens: Vec<G> = (0..n).map(|_| G::rand(&mut rng)).collect(); 
         let data: Vec<ScalarField> = (0..n).map(|_| ScalarField::rand(&mut rng)).collect();
 
-        let mexp: G = multi_exponent(&gens, &data);
+        let mexp: G = msm(&gens, &data);
 
         let mexp_by_hand: G =
             (0..n)
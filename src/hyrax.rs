@@ -0,0 +1,203 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ec::Group;
+use ark_ff::Zero;
+use rand::Rng;
+
+use crate::group_utils;
+use crate::bulletproof::{self,Data};
+use crate::multilinear::{self,DenseMLE};
+use crate::ip::{IP,Channel,Log,Embeds};
+
+// PAZK, Section 14.3
+// Hyrax commitments to multilinear polynomials.  The table of 2^v evaluations
+// of f~ is arranged as a 2^{v-a} x 2^a matrix M, with the entry at index
+// j + 2^a i in row i and column j, so that the first a variables select the
+// column and the remaining v - a variables the row.  Splitting a point
+// r = (r_lo, r_hi) in the same way,
+//   f~(r) = sum_{i,j} M(i,j) eq(i, r_hi) eq(j, r_lo) = L^T M R
+// with L and R the tables of eq(., r_hi) and eq(., r_lo).  The commitment is
+// the list of Pedersen vector commitments C_i = <M_i, g> to the rows, with
+// a = ceil(v/2) so that there are about sqrt(2^v) commitments of sqrt(2^v)
+// entries each.  To open f~ at r, both parties compute the commitment
+//   C' = sum_i L_i C_i = <L^T M, g>
+// to the vector u = L^T M by homomorphism, and the prover shows <u, R> = f~(r)
+// with the Bulletproofs inner product argument, in O(log 2^a) rounds.
+//
+// The commitments here have no blinding factors, so they are binding but not
+// hiding, as in the Bulletproofs demo.
+
+/// Public generators for committing to multilinear polynomials in `num_vars`
+/// variables, one for each column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyraxParams<G: Group> {
+    pub num_vars: usize,
+    pub gens: Vec<G>,
+}
+
+impl<G: Group> HyraxParams<G> {
+    /// Number a of variables selecting the column.
+    pub fn col_bits(&self) -> usize {
+        self.gens.len().ilog2() as usize
+    }
+
+    /// Number v - a of variables selecting the row.
+    pub fn row_bits(&self) -> usize {
+        self.num_vars - self.col_bits()
+    }
+
+    /// Split a point into (r_lo, r_hi), for the column and row variables.
+    pub fn split_point<'a>(&self, point: &'a [G::ScalarField]) -> (&'a [G::ScalarField], &'a [G::ScalarField]) {
+        assert_eq!(point.len(), self.num_vars, "point has wrong number of coordinates");
+        point.split_at(self.col_bits())
+    }
+}
+
+/// Pick random generators for polynomials in `num_vars` variables.
+pub fn setup<G: Group>(num_vars: usize, rng: &mut impl Rng) -> HyraxParams<G> {
+    let col_bits = num_vars.div_ceil(2);
+    HyraxParams { num_vars, gens: group_utils::rand_gens(1 << col_bits, rng) }
+}
+
+/// Commitments to the rows of the evaluation table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyraxCommitment<G: Group> {
+    pub rows: Vec<G>,
+}
+
+pub fn commit<G: Group>(params: &HyraxParams<G>, mle: &DenseMLE<G::ScalarField>) -> HyraxCommitment<G> {
+    assert_eq!(mle.num_vars, params.num_vars, "polynomial has wrong number of variables");
+    let rows = mle.evals
+        .chunks(params.gens.len())
+        .map(|row| group_utils::msm(&params.gens, row))
+        .collect();
+    HyraxCommitment { rows }
+}
+
+/// Vector u = L^T M of the evaluation proof at `point`.
+pub fn combine_rows<G: Group>(params: &HyraxParams<G>, mle: &DenseMLE<G::ScalarField>, point: &[G::ScalarField]) -> Vec<G::ScalarField> {
+    let (_, r_hi) = params.split_point(point);
+    let l = multilinear::eq_table(r_hi);
+    let mut u = vec![G::ScalarField::zero(); params.gens.len()];
+    for (row, l_i) in mle.evals.chunks(params.gens.len()).zip(&l.evals) {
+        for (u_j, m_ij) in u.iter_mut().zip(row) {
+            *u_j += *l_i * m_ij;
+        }
+    }
+    u
+}
+
+/// Commitment C' = sum_i L_i C_i to the vector u = L^T M.
+pub fn combine_commitments<G: Group>(params: &HyraxParams<G>, commitment: &HyraxCommitment<G>, point: &[G::ScalarField]) -> G {
+    let (_, r_hi) = params.split_point(point);
+    group_utils::msm(&commitment.rows, &multilinear::eq_table(r_hi).evals)
+}
+
+/// Prover side of the proof that the committed polynomial has the value
+/// f~(point) at `point`.
+pub fn prove_evaluation<G: Group + fmt::Display, M: Embeds<Data<G>>>(
+        params: &HyraxParams<G>,
+        mle: &DenseMLE<G::ScalarField>,
+        point: &[G::ScalarField],
+        ch: &Channel<M>,
+        log: &Log) {
+    log.write(String::from("P computes u = L^T M from the rows of the table"));
+    let u = combine_rows(params, mle, point);
+    let (r_lo, _) = params.split_point(point);
+    log.write(String::from("P proves <u, R> = f~(r) with the inner product argument"));
+    bulletproof::prove(&params.gens, &multilinear::eq_table(r_lo).evals, &u, ch, log);
+}
+
+/// Verifier side of the proof that the polynomial committed in `commitment`
+/// has the value `value` at `point`.  Sends and returns the decision.
+pub fn verify_evaluation<G: Group + fmt::Display, M: Embeds<Data<G>>>(
+        params: &HyraxParams<G>,
+        commitment: &HyraxCommitment<G>,
+        point: &[G::ScalarField],
+        value: G::ScalarField,
+        ch: &Channel<M>,
+        log: &Log) -> bool {
+    log.write(format!("V computes C' = sum_i L_i C_i from the {} row commitments", commitment.rows.len()));
+    let combined = combine_commitments(params, commitment, point);
+    let (r_lo, _) = params.split_point(point);
+    bulletproof::verify(&params.gens, &multilinear::eq_table(r_lo).evals, combined, value, ch, log)
+}
+
+pub struct HyraxProver<G: Group> {
+    pub params: Arc<HyraxParams<G>>,
+    pub mle: Arc<DenseMLE<G::ScalarField>>,
+    pub point: Arc<Vec<G::ScalarField>>,
+}
+
+impl<G: Group + fmt::Display> IP<Data<G>> for HyraxProver<G> {
+    fn execute(&self, ch: Channel<Data<G>>, log: Log) {
+        prove_evaluation(&self.params, &self.mle, &self.point, &ch, &log);
+    }
+}
+
+pub struct HyraxVerifier<G: Group> {
+    pub params: Arc<HyraxParams<G>>,
+    pub commitment: Arc<HyraxCommitment<G>>,
+    pub point: Arc<Vec<G::ScalarField>>,
+    pub value: G::ScalarField,
+}
+
+impl<G: Group + fmt::Display> IP<Data<G>> for HyraxVerifier<G> {
+    fn execute(&self, ch: Channel<Data<G>>, log: Log) {
+        verify_evaluation(&self.params, &self.commitment, &self.point, self.value, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::{Fr,G1Projective};
+    use crate::small_curves::C17Projective;
+    use crate::small_fields::F17;
+    use crate::test_utils;
+
+    fn run<G: Group + fmt::Display>(params: HyraxParams<G>, mle: DenseMLE<G::ScalarField>, point: Vec<G::ScalarField>, value: G::ScalarField) -> bool {
+        let commitment = commit(&params, &mle);
+        let params = Arc::new(params);
+        let prover = HyraxProver { params: params.clone(), mle: Arc::new(mle), point: Arc::new(point.clone()) };
+        test_utils::run_against(prover, |ch, log| verify_evaluation(&params, &commitment, &point, value, ch, log))
+    }
+
+    #[test]
+    fn test_combine() {
+        let mut rng = rand::thread_rng();
+        let params: HyraxParams<C17Projective> = setup(5, &mut rng);
+        assert_eq!((params.col_bits(), params.row_bits()), (3, 2));
+
+        let mle = DenseMLE::<F17>::rand(5, &mut rng);
+        let point: Vec<F17> = (0..5).map(|_| F17::rand(&mut rng)).collect();
+        let commitment = commit(&params, &mle);
+        assert_eq!(commitment.rows.len(), 4);
+
+        // C' commits to u, and <u, R> is the evaluation
+        let u = combine_rows(&params, &mle, &point);
+        assert_eq!(combine_commitments(&params, &commitment, &point), group_utils::msm(&params.gens, &u));
+        let (r_lo, _) = params.split_point(&point);
+        assert_eq!(group_utils::msm(&u, &multilinear::eq_table(r_lo).evals), mle.evaluate(&point));
+    }
+
+    #[test]
+    fn test_hyrax() {
+        let mut rng = rand::thread_rng();
+        let mle = DenseMLE::<F17>::rand(4, &mut rng);
+        let point: Vec<F17> = (0..4).map(|_| F17::rand(&mut rng)).collect();
+        let value = mle.evaluate(&point);
+        assert!(run(setup::<C17Projective>(4, &mut rng), mle, point, value));
+
+        // a false evaluation is rejected
+        let mle = DenseMLE::<Fr>::rand(3, &mut rng);
+        let point: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        let value = mle.evaluate(&point);
+        let params = setup::<G1Projective>(3, &mut rng);
+        assert!(run(params.clone(), mle.clone(), point.clone(), value));
+        assert!(!run(params, mle, point, value + Fr::from(1u64)));
+    }
+}
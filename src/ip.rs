@@ -33,6 +33,12 @@ pub struct Log {
     log: Arc<Mutex< Vec<String> >>,
 }
 
+impl Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Log {
     pub fn new() -> Log {
         let vec: Vec<String> = Vec::new();
@@ -51,6 +57,13 @@ impl Log {
     }
 }
 
+// message type of a protocol which carries the messages of a subprotocol with
+// message type S, so that the subprotocol can run over the same channel; every
+// message type embeds itself
+pub trait Embeds<S>: Clone + From<S> + TryInto<S> {}
+
+impl<S, T: Clone + From<S> + TryInto<S>> Embeds<S> for T {}
+
 // bidirectional channel
 pub struct Channel<T: Clone> {
     tx: mpsc::Sender<T>,
@@ -81,4 +94,15 @@ impl <T: Clone> Channel<T> {
     pub fn receive(&self) -> T {
         self.rx.recv().unwrap()
     }
+
+    // send a message of a subprotocol
+    pub fn send_as<S>(&self, data: S) where T: Embeds<S> {
+        self.send(T::from(data));
+    }
+
+    // receive a message of a subprotocol, or None if the message received
+    // belongs to the enclosing protocol
+    pub fn receive_as<S>(&self) -> Option<S> where T: Embeds<S> {
+        self.receive().try_into().ok()
+    }
 }
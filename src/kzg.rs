@@ -0,0 +1,156 @@
+use std::iter;
+
+use ark_ec::Group;
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field,One};
+use ark_poly::{Polynomial,DenseUVPolynomial};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::group_utils;
+use crate::polynomials;
+
+// PAZK, Section 15.2
+// KZG polynomial commitments: a commitment to p is g^{p(tau)} for a secret
+// tau fixed during a trusted setup, and an evaluation proof for p(z) = v is a
+// commitment to the quotient polynomial (p(X) - v)/(X - z), checked with a
+// single pairing equation.
+
+/// Structured reference string produced by the trusted setup, consisting of
+/// encodings of the powers of the toxic waste tau in both source groups.
+pub struct KzgParams<E: Pairing> {
+    pub powers_of_g: Vec<E::G1>,
+    pub powers_of_h: Vec<E::G2>,
+}
+
+impl<E: Pairing> KzgParams<E> {
+    /// Maximum degree of a polynomial which can be committed with these params.
+    pub fn max_degree(&self) -> usize {
+        self.powers_of_g.len() - 1
+    }
+}
+
+/// Run the trusted setup for polynomials of degree at most `max_degree` using
+/// the standard generators of G1 and G2.  The toxic waste `tau` must be
+/// discarded afterwards, since anyone who knows it can forge opening proofs.
+pub fn setup<E: Pairing>(max_degree: usize, tau: E::ScalarField) -> KzgParams<E> {
+    setup_with_generators::<E>(E::G1::generator(), E::G2::generator(), max_degree, tau)
+}
+
+/// Run the trusted setup using the given generators `g` of G1 and `h` of G2.
+/// Verification needs h^tau, so `max_degree` must be at least 1.
+pub fn setup_with_generators<E: Pairing>(g: E::G1, h: E::G2, max_degree: usize, tau: E::ScalarField) -> KzgParams<E> {
+    assert!(max_degree >= 1, "KZG setup needs max_degree >= 1 for h^tau");
+    let powers_of_tau: Vec<E::ScalarField> =
+        iter::successors(Some(E::ScalarField::one()), |t| Some(*t * tau))
+        .take(max_degree + 1)
+        .collect();
+    KzgParams {
+        powers_of_g: powers_of_tau.iter().map(|t| g * t).collect(),
+        powers_of_h: powers_of_tau.iter().map(|t| h * t).collect(),
+    }
+}
+
+/// Commit to `poly`, i.e. compute g^{poly(tau)} from the powers of tau in G1.
+pub fn commit<E: Pairing>(params: &KzgParams<E>, poly: &UniPoly<E::ScalarField>) -> E::G1 {
+    assert!(poly.degree() <= params.max_degree(), "polynomial degree exceeds setup bound");
+    group_utils::msm(&params.powers_of_g[..poly.coeffs.len()], &poly.coeffs)
+}
+
+/// Evaluate `poly` at `point`, returning the evaluation together with a proof
+/// consisting of a commitment to the quotient (poly(X) - v)/(X - point).
+pub fn open<E: Pairing>(params: &KzgParams<E>, poly: &UniPoly<E::ScalarField>, point: E::ScalarField) -> (E::ScalarField, E::G1) {
+    let value = poly.evaluate(&point);
+    let numerator = poly - &UniPoly::from_coefficients_vec(vec![value]);
+    let divisor = UniPoly::from_coefficients_vec(vec![-point, E::ScalarField::one()]);
+    let quotient = &numerator / &divisor;
+    (value, commit(params, &quotient))
+}
+
+/// Check an opening proof for the claim that the polynomial committed in
+/// `commitment` evaluates to `value` at `point`, using the pairing equation
+///   e(C - g^v, h) == e(pi, h^{tau - z}).
+pub fn verify<E: Pairing>(params: &KzgParams<E>, commitment: E::G1, point: E::ScalarField, value: E::ScalarField, proof: E::G1) -> bool {
+    let g = params.powers_of_g[0];
+    let h = params.powers_of_h[0];
+    let lhs = E::pairing(commitment - g * value, h);
+    let rhs = E::pairing(proof, params.powers_of_h[1] - h * point);
+    lhs == rhs
+}
+
+/// Evaluate `poly` at each of `points`, returning the evaluations together
+/// with a single proof consisting of a commitment to the quotient
+/// (poly(X) - I(X))/Z(X), where I interpolates the evaluations and Z is the
+/// vanishing polynomial of the points.
+pub fn batch_open<E: Pairing>(params: &KzgParams<E>, poly: &UniPoly<E::ScalarField>, points: &[E::ScalarField]) -> (Vec<E::ScalarField>, E::G1) {
+    let values: Vec<E::ScalarField> = points.iter().map(|z| poly.evaluate(z)).collect();
+    let numerator = poly - &polynomials::lagrange_interpolate(points, &values);
+    let quotient = &numerator / &vanishing_poly(points);
+    (values, commit(params, &quotient))
+}
+
+/// Check a batched opening proof for the claim that the polynomial committed
+/// in `commitment` evaluates to `values` at `points`, using the pairing
+/// equation
+///   e(C - g^{I(tau)}, h) == e(pi, h^{Z(tau)}).
+pub fn batch_verify<E: Pairing>(params: &KzgParams<E>, commitment: E::G1, points: &[E::ScalarField], values: &[E::ScalarField], proof: E::G1) -> bool {
+    if points.len() != values.len() || points.len() >= params.powers_of_h.len() {
+        return false;
+    }
+    let interpolant = polynomials::lagrange_interpolate(points, values);
+    let vanishing = vanishing_poly(points);
+    let h = params.powers_of_h[0];
+    let lhs = E::pairing(commitment - commit(params, &interpolant), h);
+    let rhs = E::pairing(proof, group_utils::msm(&params.powers_of_h[..vanishing.coeffs.len()], &vanishing.coeffs));
+    lhs == rhs
+}
+
+/// Compute the vanishing polynomial Z(X) = prod_i (X - z_i) of `points`.
+pub fn vanishing_poly<F: Field>(points: &[F]) -> UniPoly<F> {
+    points.iter()
+        .fold(UniPoly::from_coefficients_vec(vec![F::one()]), |acc, z| {
+            acc.naive_mul(&UniPoly::from_coefficients_vec(vec![-*z, F::one()]))
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::{Bls12_381 as E, Fr};
+
+    fn random_setup(deg: usize) -> (KzgParams<E>, UniPoly<Fr>) {
+        let mut rng = rand::thread_rng();
+        let params = setup::<E>(deg, Fr::rand(&mut rng));
+        let poly = UniPoly::rand(deg, &mut rng);
+        (params, poly)
+    }
+
+    #[test]
+    fn test_open_verify() {
+        let mut rng = rand::thread_rng();
+        let (params, poly) = random_setup(4);
+        let commitment = commit(&params, &poly);
+        let point = Fr::rand(&mut rng);
+
+        let (value, proof) = open(&params, &poly, point);
+        assert_eq!(value, poly.evaluate(&point));
+        assert!(verify(&params, commitment, point, value, proof));
+        assert!(!verify(&params, commitment, point, value + Fr::one(), proof));
+    }
+
+    #[test]
+    fn test_batch_open_verify() {
+        let mut rng = rand::thread_rng();
+        let (params, poly) = random_setup(4);
+        let commitment = commit(&params, &poly);
+        let points: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+
+        let (values, proof) = batch_open(&params, &poly, &points);
+        assert!(batch_verify(&params, commitment, &points, &values, proof));
+
+        let mut bad_values = values.clone();
+        bad_values[1] += Fr::one();
+        assert!(!batch_verify(&params, commitment, &points, &bad_values, proof));
+    }
+}
@@ -11,4 +11,53 @@ pub mod polynomials;
 pub use self::polynomials::*;
 
 pub mod group_utils;
-pub use self::group_utils::*;
\ No newline at end of file
+pub use self::group_utils::*;
+
+pub mod kzg;
+
+pub mod merkle;
+
+pub mod transcript;
+
+pub mod fri;
+
+pub mod multilinear;
+
+pub mod multilinear_kzg;
+
+pub mod sum_check;
+
+pub mod poly_parser;
+
+pub mod sat;
+
+pub mod triangles;
+
+pub mod mat_mult;
+
+pub mod circuit;
+
+pub mod gkr;
+
+pub mod circuit_parser;
+
+pub mod r1cs;
+
+pub mod spartan;
+
+pub mod bulletproof;
+
+pub mod hyrax;
+
+pub mod sum_check_argument;
+
+pub mod zk_sum_check;
+
+pub mod fingerprint;
+
+pub mod freivalds;
+
+pub mod bench;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ndarray::Array2;
+
+use crate::polynomials;
+use crate::freivalds;
+use crate::multilinear::{DenseMLE,ProductMLE,index_bits};
+use crate::sum_check::{self,SumCheckFailure,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 4.4
+// MatMult: to check a claimed product C = A * B of an n x k matrix A and a
+// k x p matrix B, the verifier picks random points r_1 and r_2 for the row
+// and column indices, and evaluates C~(r_1, r_2) itself.  If C = A * B then
+//   C~(r_1, r_2) = sum_{y in {0,1}^{log k}} A~(r_1, y) * B~(y, r_2),
+// since both sides are multilinear in (r_1, r_2) and agree on the hypercube,
+// and the prover shows this with sum-check applied to the product of the
+// multilinear polynomials A~(r_1, y) and B~(y, r_2) in log k variables.  At
+// the end the verifier evaluates A~ and B~ at one point each.  The verifier's
+// work is linear in the size of the matrices, and apart from computing C the
+// prover's work is as well.
+//
+// The matrix entry M(i,j) is at index i + 2^a j of the table of M~, where a is
+// the number of bits needed to index a row, so the row variables come first.
+
+/// Multilinear extension of a matrix, padded with zeros to 2^a x 2^b, in the
+/// a row variables followed by the b column variables.
+pub fn matrix_mle<F: Field>(matrix: &Array2<F>) -> DenseMLE<F> {
+    let (rows, cols) = matrix.dim();
+    let (a, b) = (index_bits(rows), index_bits(cols));
+    let mut evals = vec![F::zero(); 1 << (a + b)];
+    for ((i, j), entry) in matrix.indexed_iter() {
+        evals[i + (j << a)] = *entry;
+    }
+    DenseMLE::new(a + b, evals)
+}
+
+/// The product A~(r_1, y) * B~(y, r_2) in the variables y of the inner
+/// dimension, computed in time linear in the sizes of A and B.
+pub fn mat_mult_polynomial<F: Field>(a: &Array2<F>, b: &Array2<F>, r_1: &[F], r_2: &[F]) -> ProductMLE<F> {
+    let mut a_r_1 = matrix_mle(a);
+    for r in r_1 {
+        a_r_1.fix_first_variable(*r);
+    }
+    let mut b_r_2 = matrix_mle(b);
+    for r in r_2.iter().rev() {
+        b_r_2.fix_last_variable(*r);
+    }
+    ProductMLE::new(vec![a_r_1, b_r_2])
+}
+
+/// Freivalds' check of C = A * B, comparing C x with A (B x) for the vector
+/// x = (1, r, r^2, ...), which errs with probability at most (p - 1)/|F| for
+/// random r when C has p columns.
+pub fn freivalds_check<F: Field>(a: &Array2<F>, b: &Array2<F>, c: &Array2<F>, r: F) -> bool {
+    freivalds::check(a, b, c, &freivalds::powers_vector(r, c.ncols()))
+}
+
+/// Run the verifier's side of the MatMult protocol for the claim C = A * B,
+/// recording the exchange in the log.
+pub fn verify<F: PrimeField>(a: &Array2<F>, b: &Array2<F>, c: &Array2<F>, ch: &Channel<Data<F>>, log: &Log) -> Result<(), SumCheckFailure<F>> {
+    assert_eq!(a.ncols(), b.nrows(), "inner dimensions must agree");
+    assert_eq!(c.dim(), (a.nrows(), b.ncols()), "product has wrong dimensions");
+
+    let mut rng = rand::thread_rng();
+    log.write(String::from("V picks r_1 and r_2 uniformly at random"));
+    let r_1: Vec<F> = (0..index_bits(c.nrows())).map(|_| F::rand(&mut rng)).collect();
+    let r_2: Vec<F> = (0..index_bits(c.ncols())).map(|_| F::rand(&mut rng)).collect();
+    log.write(format!("V --> (r_1 = {}, r_2 = {})", polynomials::format_point(&r_1), polynomials::format_point(&r_2)));
+    for r in r_1.iter().chain(&r_2) {
+        ch.send(Data::Scalar(*r));
+    }
+
+    log.write(String::from("V evaluates C~(r_1, r_2) as the claimed sum"));
+    let claimed_sum = matrix_mle(c).evaluate(&[r_1.as_slice(), &r_2].concat());
+
+    let degrees = vec![2; index_bits(a.ncols())];
+    let oracle = |point: &[F]| {
+        log.write(String::from("V evaluates A~(r_1, r) and B~(r, r_2)"));
+        matrix_mle(a).evaluate(&[r_1.as_slice(), point].concat())
+            * matrix_mle(b).evaluate(&[point, r_2.as_slice()].concat())
+    };
+    sum_check::verify_with_oracle(claimed_sum, &degrees, oracle, ch, log)
+}
+
+pub struct MatMultProver<F: Field> {
+    pub a: Arc<Array2<F>>,
+    pub b: Arc<Array2<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for MatMultProver<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        let num_challenges = index_bits(self.a.nrows()) + index_bits(self.b.ncols());
+        let challenges: Vec<F> = (0..num_challenges).map(|_| ch.receive().to_scalar().unwrap()).collect();
+        let (r_1, r_2) = challenges.split_at(index_bits(self.a.nrows()));
+
+        log.write(String::from("P computes tables of A~(r_1, y) and B~(y, r_2)"));
+        let mut poly = mat_mult_polynomial(&self.a, &self.b, r_1, r_2);
+        sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, &ch, &log);
+    }
+}
+
+pub struct MatMultVerifier<F: Field> {
+    pub a: Arc<Array2<F>>,
+    pub b: Arc<Array2<F>>,
+    pub c: Arc<Array2<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for MatMultVerifier<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        // failures are recorded in the log
+        let _ = verify(&self.a, &self.b, &self.c, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::Fr as F;
+    use crate::test_utils;
+
+    fn random_matrix(rows: usize, cols: usize) -> Array2<F> {
+        let mut rng = rand::thread_rng();
+        Array2::from_shape_simple_fn((rows, cols), || F::rand(&mut rng))
+    }
+
+    fn run(a: Array2<F>, b: Array2<F>, c: Array2<F>) -> Result<(), SumCheckFailure<F>> {
+        let prover = MatMultProver { a: Arc::new(a.clone()), b: Arc::new(b.clone()) };
+        test_utils::run_against(prover, |ch, log| verify(&a, &b, &c, ch, log))
+    }
+
+    #[test]
+    fn test_matrix_mle() {
+        let a = random_matrix(3, 5);
+        let mle = matrix_mle(&a);
+        assert_eq!(mle.num_vars, 5);
+        assert_eq!(mle.evals[2 + (4 << 2)], a[(2, 4)]);
+        assert_eq!(mle.sum(), a.sum());
+    }
+
+    #[test]
+    fn test_mat_mult() {
+        let (a, b) = (random_matrix(4, 3), random_matrix(3, 2));
+        let c = a.dot(&b);
+        assert!(freivalds_check(&a, &b, &c, F::rand(&mut rand::thread_rng())));
+        assert_eq!(run(a.clone(), b.clone(), c.clone()), Ok(()));
+
+        let mut wrong = c.clone();
+        wrong[(3, 1)] += F::from(1u64);
+        assert!(!freivalds_check(&a, &b, &wrong, F::rand(&mut rand::thread_rng())));
+        assert!(matches!(run(a, b, wrong), Err(SumCheckFailure::Sum { round: 1, .. })));
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use ark_serialize::CanonicalSerialize;
+use sha2::{Sha256,Digest};
+
+// PAZK, Section 7.3.2
+// Merkle trees commit to a vector by hashing its entries into the leaves of a
+// binary tree and hashing pairs of sibling nodes up to a single root.  An
+// entry is opened by revealing the sibling nodes along the path from its leaf
+// to the root, from which the verifier recomputes the root.
+
+/// Hash function used to build Merkle trees.  Leaves are hashed from the
+/// serialization of the committed values, and internal nodes by compressing
+/// the digests of their two children.
+pub trait MerkleHash: Send + Sync + 'static {
+    type Digest: Clone + Eq + fmt::Debug + fmt::Display + AsRef<[u8]> + Send + Sync + 'static;
+
+    fn hash_bytes(bytes: &[u8]) -> Self::Digest;
+
+    fn compress(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Hash a committed value from its canonical serialization.
+    fn hash_value<T: CanonicalSerialize>(value: &T) -> Self::Digest {
+        let mut bytes = Vec::new();
+        value.serialize_compressed(&mut bytes).unwrap();
+        Self::hash_bytes(&bytes)
+    }
+}
+
+/// SHA-256, with 32 byte digests.
+#[derive(Clone)]
+pub struct Sha256Hash;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sha256Digest(pub [u8; 32]);
+
+impl fmt::Display for Sha256Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl AsRef<[u8]> for Sha256Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl MerkleHash for Sha256Hash {
+    type Digest = Sha256Digest;
+
+    fn hash_bytes(bytes: &[u8]) -> Sha256Digest {
+        Sha256Digest(Sha256::digest(bytes).into())
+    }
+
+    // domain separate leaves (prefix 0x00) from internal nodes (prefix 0x01),
+    // so that an internal node cannot be opened as a leaf
+    fn hash_value<T: CanonicalSerialize>(value: &T) -> Sha256Digest {
+        let mut bytes = vec![0x00];
+        value.serialize_compressed(&mut bytes).unwrap();
+        Sha256Hash::hash_bytes(&bytes)
+    }
+
+    fn compress(left: &Sha256Digest, right: &Sha256Digest) -> Sha256Digest {
+        let mut hasher = Sha256::new();
+        hasher.update([0x01]);
+        hasher.update(left.0);
+        hasher.update(right.0);
+        Sha256Digest(hasher.finalize().into())
+    }
+}
+
+/// Toy hash with single byte digests, obtained by folding a 32-bit FNV-1a hash.
+/// Collisions are easy to find by hand, so this is NOT binding, but it keeps
+/// trees over small fields short enough to print and follow.
+#[derive(Clone)]
+pub struct ToyHash;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ToyDigest(pub u8);
+
+impl fmt::Display for ToyDigest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02x}", self.0)
+    }
+}
+
+impl AsRef<[u8]> for ToyDigest {
+    fn as_ref(&self) -> &[u8] {
+        std::slice::from_ref(&self.0)
+    }
+}
+
+impl ToyHash {
+    fn fnv1a(bytes: &[u8]) -> u32 {
+        bytes.iter()
+            .fold(0x811c9dc5u32, |h, b| (h ^ *b as u32).wrapping_mul(0x01000193))
+    }
+}
+
+impl MerkleHash for ToyHash {
+    type Digest = ToyDigest;
+
+    fn hash_bytes(bytes: &[u8]) -> ToyDigest {
+        let h = ToyHash::fnv1a(bytes);
+        ToyDigest((h ^ (h >> 8) ^ (h >> 16) ^ (h >> 24)) as u8)
+    }
+
+    fn compress(left: &ToyDigest, right: &ToyDigest) -> ToyDigest {
+        // domain separate internal nodes from leaves
+        ToyHash::hash_bytes(&[0xff, left.0, right.0])
+    }
+}
+
+/// Merkle tree over the leaf digests of a committed vector.  The vector is
+/// padded with digests of the empty string up to a power of two length.
+pub struct MerkleTree<H: MerkleHash> {
+    /// Node digests by level, from the leaves at level 0 up to the root.
+    layers: Vec<Vec<H::Digest>>,
+    len: usize,
+}
+
+impl<H: MerkleHash> MerkleTree<H> {
+    /// Build the Merkle tree committing to `values`.
+    pub fn new<T: CanonicalSerialize>(values: &[T]) -> MerkleTree<H> {
+        assert!(!values.is_empty(), "cannot commit to an empty vector");
+        let mut leaves: Vec<H::Digest> = values.iter().map(H::hash_value).collect();
+        leaves.resize(values.len().next_power_of_two(), H::hash_bytes(&[]));
+
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers.last().unwrap()
+                .chunks(2)
+                .map(|pair| H::compress(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next);
+        }
+        MerkleTree { layers, len: values.len() }
+    }
+
+    pub fn root(&self) -> H::Digest {
+        self.layers.last().unwrap()[0].clone()
+    }
+
+    /// Length of the committed vector, before padding.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of levels of internal nodes, i.e. the length of an authentication path.
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    /// Digests of the nodes at the given level, with the leaves at level 0.
+    pub fn layer(&self, level: usize) -> &[H::Digest] {
+        &self.layers[level]
+    }
+
+    /// Produce the authentication path for the entry at `index`, consisting of
+    /// the sibling of each node on the path from the leaf up to the root.
+    pub fn open(&self, index: usize) -> MerklePath<H> {
+        assert!(index < self.len, "index out of range");
+        let siblings = self.layers[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index >> level) ^ 1].clone())
+            .collect();
+        MerklePath { index, siblings }
+    }
+
+    /// Produce a single proof opening all of the entries at `indices`.  Nodes
+    /// which the verifier can compute from the opened leaves, or which are
+    /// shared between several authentication paths, are included only once.
+    pub fn open_many(&self, indices: &[usize]) -> MerkleMultiProof<H> {
+        assert!(indices.iter().all(|&i| i < self.len), "index out of range");
+        let mut known: BTreeSet<usize> = indices.iter().copied().collect();
+        let mut nodes = Vec::new();
+        for layer in &self.layers[..self.depth()] {
+            for &i in &known {
+                if !known.contains(&(i ^ 1)) {
+                    nodes.push(layer[i ^ 1].clone());
+                }
+            }
+            known = known.iter().map(|i| i >> 1).collect();
+        }
+        MerkleMultiProof { indices: indices.to_vec(), nodes }
+    }
+}
+
+/// Authentication path for a single entry of a committed vector.
+pub struct MerklePath<H: MerkleHash> {
+    pub index: usize,
+    pub siblings: Vec<H::Digest>,
+}
+
+impl<H: MerkleHash> Clone for MerklePath<H> {
+    fn clone(&self) -> Self {
+        MerklePath { index: self.index, siblings: self.siblings.clone() }
+    }
+}
+
+impl<H: MerkleHash> fmt::Display for MerklePath<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let siblings: Vec<String> = self.siblings.iter().map(|d| d.to_string()).collect();
+        write!(f, "[{}]", siblings.join(", "))
+    }
+}
+
+impl<H: MerkleHash> MerklePath<H> {
+    /// Check that `value` is the entry at this path's index of the vector
+    /// committed to by `root`.
+    pub fn verify<T: CanonicalSerialize>(&self, root: &H::Digest, value: &T) -> bool {
+        let computed = self.siblings
+            .iter()
+            .enumerate()
+            .fold(H::hash_value(value), |node, (level, sibling)| {
+                if (self.index >> level) & 1 == 0 {
+                    H::compress(&node, sibling)
+                } else {
+                    H::compress(sibling, &node)
+                }
+            });
+        computed == *root
+    }
+}
+
+/// Batched authentication paths for several entries of a committed vector,
+/// with shared nodes deduplicated.
+pub struct MerkleMultiProof<H: MerkleHash> {
+    pub indices: Vec<usize>,
+    pub nodes: Vec<H::Digest>,
+}
+
+impl<H: MerkleHash> Clone for MerkleMultiProof<H> {
+    fn clone(&self) -> Self {
+        MerkleMultiProof { indices: self.indices.clone(), nodes: self.nodes.clone() }
+    }
+}
+
+impl<H: MerkleHash> fmt::Display for MerkleMultiProof<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<String> = self.nodes.iter().map(|d| d.to_string()).collect();
+        write!(f, "[{}]", nodes.join(", "))
+    }
+}
+
+impl<H: MerkleHash> MerkleMultiProof<H> {
+    /// Check that `values` are the entries at this proof's indices of the
+    /// vector of length `len` committed to by `root`.
+    pub fn verify<T: CanonicalSerialize>(&self, root: &H::Digest, len: usize, values: &[T]) -> bool {
+        if values.len() != self.indices.len() || self.indices.iter().any(|&i| i >= len) {
+            return false;
+        }
+        let depth = len.next_power_of_two().ilog2() as usize;
+
+        // known nodes of the current level, sorted by index
+        let mut known: Vec<(usize, H::Digest)> = Vec::new();
+        for (&i, value) in self.indices.iter().zip(values) {
+            let digest = H::hash_value(value);
+            match known.iter().find(|(j, _)| *j == i) {
+                Some((_, d)) if *d != digest => return false,
+                Some(_) => (),
+                None => known.push((i, digest)),
+            }
+        }
+        known.sort_by_key(|(i, _)| *i);
+
+        let mut proof_nodes = self.nodes.iter();
+        for _ in 0..depth {
+            let mut next: Vec<(usize, H::Digest)> = Vec::new();
+            let mut pos = 0;
+            while pos < known.len() {
+                let (i, node) = &known[pos];
+                let parent = if pos + 1 < known.len() && known[pos + 1].0 == (i ^ 1) {
+                    // both children known, so the sibling is not in the proof
+                    pos += 1;
+                    H::compress(node, &known[pos].1)
+                } else {
+                    let sibling = match proof_nodes.next() {
+                        Some(d) => d,
+                        None => return false,
+                    };
+                    if i & 1 == 0 { H::compress(node, sibling) } else { H::compress(sibling, node) }
+                };
+                next.push((i >> 1, parent));
+                pos += 1;
+            }
+            known = next;
+        }
+        proof_nodes.next().is_none() && known.len() == 1 && known[0].1 == *root
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::small_fields::F13;
+    use ark_test_curves::bls12_381::Fr;
+
+    fn random_vector<T: UniformRand>(n: usize) -> Vec<T> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| T::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_open_verify() {
+        let values: Vec<F13> = random_vector(6);
+        let tree = MerkleTree::<ToyHash>::new(&values);
+        let root = tree.root();
+        assert_eq!(tree.depth(), 3);
+
+        for (i, value) in values.iter().enumerate() {
+            let path = tree.open(i);
+            assert!(path.verify(&root, value));
+        }
+
+        let values: Vec<Fr> = random_vector(8);
+        let tree = MerkleTree::<Sha256Hash>::new(&values);
+        let path = tree.open(5);
+        assert!(path.verify(&tree.root(), &values[5]));
+        assert!(!path.verify(&tree.root(), &values[4]));
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        // a leaf whose serialization is the concatenation of two digests does
+        // not hash to their parent node
+        let (left, right) = (Sha256Hash::hash_bytes(b"left"), Sha256Hash::hash_bytes(b"right"));
+        let mut children = [0u8; 64];
+        children[..32].copy_from_slice(&left.0);
+        children[32..].copy_from_slice(&right.0);
+        assert_ne!(Sha256Hash::hash_value(&children), Sha256Hash::compress(&left, &right));
+        assert_eq!(Sha256Hash::hash_value(&children), Sha256Hash::hash_bytes(&[&[0x00], &children[..]].concat()));
+    }
+
+    #[test]
+    fn test_open_many() {
+        let values: Vec<Fr> = random_vector(16);
+        let tree = MerkleTree::<Sha256Hash>::new(&values);
+        let root = tree.root();
+
+        let indices = vec![3, 2, 9, 15, 9];
+        let opened: Vec<Fr> = indices.iter().map(|&i| values[i]).collect();
+        let proof = tree.open_many(&indices);
+        assert!(proof.verify(&root, values.len(), &opened));
+
+        // 2 and 3 are siblings and share the rest of their path, so the
+        // layers need 8 and 14; 0, 5 and 6; then 1; and nothing at the top
+        assert_eq!(proof.nodes.len(), 6);
+
+        // adjacent leaves only need the siblings above their common parent
+        let proof = tree.open_many(&[6, 7]);
+        assert_eq!(proof.nodes.len(), tree.depth() - 1);
+        assert!(proof.verify(&root, values.len(), &[values[6], values[7]]));
+
+        let mut tampered = opened.clone();
+        tampered[2] += Fr::from(1u64);
+        assert!(!proof.verify(&root, values.len(), &tampered));
+    }
+}
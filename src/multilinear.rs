@@ -0,0 +1,308 @@
+use std::iter;
+
+use ark_ff::Field;
+use rand::Rng;
+use ark_poly::{Polynomial,DenseMVPolynomial};
+use ark_poly::polynomial::multivariate::SparsePolynomial as MultiPoly;
+use ark_poly::polynomial::multivariate::{Term,SparseTerm};
+
+use crate::polynomials;
+
+// PAZK, Section 3.5
+// A function f: {0,1}^v -> F has a unique multilinear extension f~ to F^v,
+//   f~(r) = sum_{x in {0,1}^v} f(x) * eq(x, r),
+// where eq(x, r) = prod_i (x_i r_i + (1 - x_i)(1 - r_i)).  Dense multilinear
+// polynomials are represented by their table of 2^v evaluations over the
+// hypercube, with the entry at index sum_i x_i 2^i holding f(x_0, ..., x_{v-1}).
+
+/// Multilinear polynomial in `num_vars` variables, stored as its table of
+/// evaluations over the Boolean hypercube {0,1}^num_vars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenseMLE<F: Field> {
+    pub num_vars: usize,
+    pub evals: Vec<F>,
+}
+
+impl<F: Field> DenseMLE<F> {
+    pub fn new(num_vars: usize, evals: Vec<F>) -> DenseMLE<F> {
+        assert_eq!(evals.len(), 1 << num_vars, "evaluation table must have length 2^num_vars");
+        DenseMLE { num_vars, evals }
+    }
+
+    /// Multilinear polynomial with a uniformly random evaluation table.
+    pub fn rand(num_vars: usize, rng: &mut impl Rng) -> DenseMLE<F> {
+        DenseMLE::new(num_vars, (0..1 << num_vars).map(|_| F::rand(rng)).collect())
+    }
+
+    /// Compute the evaluation table of the multilinear extension of a function
+    /// f: {0,1}^v -> F, given on the coordinates (x_0, ..., x_{v-1}).
+    pub fn from_function(num_vars: usize, f: impl Fn(&[bool]) -> F) -> DenseMLE<F> {
+        let evals = (0..1usize << num_vars)
+            .map(|idx| {
+                let bits: Vec<bool> = (0..num_vars).map(|var| idx & (1 << var) != 0).collect();
+                f(&bits)
+            })
+            .collect();
+        DenseMLE { num_vars, evals }
+    }
+
+    /// Compute the evaluation table over {0,1}^v of a multivariate polynomial.
+    /// This is the multilinear extension of `poly` restricted to the hypercube,
+    /// which agrees with `poly` everywhere exactly when `poly` is multilinear.
+    pub fn from_multi_poly(poly: &MultiPoly<F, SparseTerm>) -> DenseMLE<F> {
+        let num_vars = poly.num_vars;
+        let evals = (0..1usize << num_vars)
+            .map(|idx| poly.evaluate(&hypercube_point(num_vars, idx)))
+            .collect();
+        DenseMLE { num_vars, evals }
+    }
+
+    /// Compute the coefficients of the multilinear polynomial with this
+    /// evaluation table, by Moebius inversion over the subsets of variables.
+    pub fn to_multi_poly(&self) -> MultiPoly<F, SparseTerm> {
+        let mut coeffs = self.evals.clone();
+        for var in 0..self.num_vars {
+            for idx in 0..coeffs.len() {
+                if idx & (1 << var) != 0 {
+                    let lower = coeffs[idx ^ (1 << var)];
+                    coeffs[idx] -= lower;
+                }
+            }
+        }
+        let terms = coeffs.into_iter()
+            .enumerate()
+            .filter(|(_, coeff)| !coeff.is_zero())
+            .map(|(idx, coeff)| {
+                let monom = (0..self.num_vars)
+                    .filter(|var| idx & (1 << var) != 0)
+                    .map(|var| (var, 1))
+                    .collect();
+                (coeff, SparseTerm::new(monom))
+            })
+            .collect();
+        MultiPoly::from_coefficients_vec(self.num_vars, terms)
+    }
+
+    /// Evaluate at an arbitrary point of F^v in O(2^v) field operations.
+    pub fn evaluate(&self, point: &[F]) -> F {
+        assert_eq!(point.len(), self.num_vars, "point has wrong number of coordinates");
+        let mut mle = self.clone();
+        for r in point.iter().rev() {
+            mle.fix_last_variable(*r);
+        }
+        mle.evals[0]
+    }
+
+    /// Sum of the evaluations over the hypercube.
+    pub fn sum(&self) -> F {
+        self.evals.iter().sum()
+    }
+
+    /// Fix the first variable x_0 to `r` in place, leaving a multilinear
+    /// polynomial in the variables x_1, ..., x_{v-1} (renumbered from 0).
+    pub fn fix_first_variable(&mut self, r: F) {
+        assert!(self.num_vars > 0, "no variables left to fix");
+        let half = self.evals.len() / 2;
+        for idx in 0..half {
+            let (lo, hi) = (self.evals[2 * idx], self.evals[2 * idx + 1]);
+            self.evals[idx] = lo + r * (hi - lo);
+        }
+        self.evals.truncate(half);
+        self.num_vars -= 1;
+    }
+
+    /// Fix the last variable x_{v-1} to `r` in place, leaving a multilinear
+    /// polynomial in the variables x_0, ..., x_{v-2}.
+    pub fn fix_last_variable(&mut self, r: F) {
+        assert!(self.num_vars > 0, "no variables left to fix");
+        let half = self.evals.len() / 2;
+        for idx in 0..half {
+            let (lo, hi) = (self.evals[idx], self.evals[idx + half]);
+            self.evals[idx] = lo + r * (hi - lo);
+        }
+        self.evals.truncate(half);
+        self.num_vars -= 1;
+    }
+}
+
+/// Product of multilinear polynomials in the same variables, kept in factored
+/// form so that each factor can be folded separately.  The product has degree
+/// equal to the number of factors in each variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProductMLE<F: Field> {
+    pub factors: Vec<DenseMLE<F>>,
+}
+
+impl<F: Field> ProductMLE<F> {
+    pub fn new(factors: Vec<DenseMLE<F>>) -> ProductMLE<F> {
+        assert!(!factors.is_empty(), "product must have at least one factor");
+        let num_vars = factors[0].num_vars;
+        assert!(factors.iter().all(|f| f.num_vars == num_vars), "factors must have the same number of variables");
+        ProductMLE { factors }
+    }
+
+    /// Product of `num_factors` random multilinear polynomials in `num_vars`
+    /// variables.
+    pub fn rand(num_vars: usize, num_factors: usize, rng: &mut impl Rng) -> ProductMLE<F> {
+        ProductMLE::new((0..num_factors).map(|_| DenseMLE::rand(num_vars, rng)).collect())
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.factors[0].num_vars
+    }
+
+    pub fn evaluate(&self, point: &[F]) -> F {
+        self.factors.iter().map(|f| f.evaluate(point)).product()
+    }
+
+    /// Expand the product into a single sparse polynomial.
+    pub fn to_multi_poly(&self) -> MultiPoly<F, SparseTerm> {
+        self.factors
+            .iter()
+            .skip(1)
+            .fold(self.factors[0].to_multi_poly(), |acc, f| {
+                polynomials::multiply(&acc, &f.to_multi_poly())
+            })
+    }
+
+    pub fn fix_last_variable(&mut self, r: F) {
+        for f in self.factors.iter_mut() {
+            f.fix_last_variable(r);
+        }
+    }
+}
+
+/// Number of variables needed to index `n` items by hypercube points, which
+/// is at least 1.
+pub fn index_bits(n: usize) -> usize {
+    (n.next_power_of_two().trailing_zeros() as usize).max(1)
+}
+
+/// Coordinates of the hypercube point with the given table index.
+pub fn hypercube_point<F: Field>(num_vars: usize, idx: usize) -> Vec<F> {
+    (0..num_vars)
+        .map(|var| if idx & (1 << var) != 0 { F::one() } else { F::zero() })
+        .collect()
+}
+
+/// Evaluate eq(x, r) = prod_i (x_i r_i + (1 - x_i)(1 - r_i)) at arbitrary x, r.
+pub fn eq_eval<F: Field>(x: &[F], r: &[F]) -> F {
+    assert_eq!(x.len(), r.len(), "points have different numbers of coordinates");
+    iter::zip(x, r)
+        .map(|(x_i, r_i)| *x_i * r_i + (F::one() - x_i) * (F::one() - r_i))
+        .product()
+}
+
+/// Compute the table of eq(x, r) over all x in {0,1}^v in O(2^v) field
+/// operations, i.e. the evaluations of the Lagrange basis polynomials at r.
+pub fn eq_table<F: Field>(r: &[F]) -> DenseMLE<F> {
+    let mut evals = vec![F::one()];
+    for r_i in r {
+        // entries with x_i = 0 are scaled by (1 - r_i), those with x_i = 1 by r_i
+        let hi: Vec<F> = evals.iter().map(|e| *e * r_i).collect();
+        for (lo, hi) in evals.iter_mut().zip(&hi) {
+            *lo -= hi;
+        }
+        evals.extend(hi);
+    }
+    DenseMLE { num_vars: r.len(), evals }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::polynomials::{partial_eval,partial_summation};
+    use crate::small_fields::F13 as F;
+
+    fn random_point(num_vars: usize) -> Vec<F> {
+        let mut rng = rand::thread_rng();
+        (0..num_vars).map(|_| F::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_multi_poly_conversion() {
+        let mle = DenseMLE::<F>::rand(4, &mut rand::thread_rng());
+        let poly = mle.to_multi_poly();
+        assert_eq!(DenseMLE::from_multi_poly(&poly), mle);
+
+        let point = random_point(4);
+        assert_eq!(mle.evaluate(&point), poly.evaluate(&point));
+    }
+
+    #[test]
+    fn test_from_function() {
+        // the extension of AND is x_0 x_1, and that of a table is the table
+        let and = DenseMLE::<F>::from_function(2, |x| if x[0] && x[1] { F::from(1u64) } else { F::from(0u64) });
+        let point = random_point(2);
+        assert_eq!(and.evaluate(&point), point[0] * point[1]);
+
+        let mle = DenseMLE::<F>::rand(3, &mut rand::thread_rng());
+        let index = |x: &[bool]| x.iter().enumerate().map(|(i, b)| (*b as usize) << i).sum::<usize>();
+        assert_eq!(DenseMLE::from_function(3, |x| mle.evals[index(x)]), mle);
+    }
+
+    #[test]
+    fn test_evaluate_and_sum() {
+        let mle = DenseMLE::<F>::rand(3, &mut rand::thread_rng());
+        let poly = mle.to_multi_poly();
+
+        // evaluation agrees with partial summation over singletons
+        let point = random_point(3);
+        let vals: Vec<Option<Vec<F>>> = point.iter().map(|r| Some(vec![*r])).collect();
+        let evaluation = partial_summation(&poly, &vals);
+        assert_eq!(DenseMLE::from_multi_poly(&evaluation).evals[0], mle.evaluate(&point));
+
+        // hypercube sum agrees with partial summation over {0, 1}
+        let vals = vec![Some(vec![F::from(0u64), F::from(1u64)]); 3];
+        let sum = partial_summation(&poly, &vals);
+        assert_eq!(DenseMLE::from_multi_poly(&sum).evals[0], mle.sum());
+    }
+
+    #[test]
+    fn test_fix_variables() {
+        let mle = DenseMLE::<F>::rand(3, &mut rand::thread_rng());
+        let poly = mle.to_multi_poly();
+        let r = F::rand(&mut rand::thread_rng());
+        let rest = random_point(2);
+
+        let mut first = mle.clone();
+        first.fix_first_variable(r);
+        let expected = partial_eval(&poly, r, 0).evaluate(&[vec![F::from(0u64)], rest.clone()].concat());
+        assert_eq!(first.evaluate(&rest), expected);
+
+        let mut last = mle.clone();
+        last.fix_last_variable(r);
+        let expected = partial_eval(&poly, r, 2).evaluate(&[rest.clone(), vec![F::from(0u64)]].concat());
+        assert_eq!(last.evaluate(&rest), expected);
+    }
+
+    #[test]
+    fn test_product() {
+        let product = ProductMLE::<F>::rand(3, 3, &mut rand::thread_rng());
+        let poly = product.to_multi_poly();
+        let point = random_point(3);
+        assert_eq!(product.evaluate(&point), poly.evaluate(&point));
+
+        let r = F::rand(&mut rand::thread_rng());
+        let mut fixed = product.clone();
+        fixed.fix_last_variable(r);
+        let expected = partial_eval(&poly, r, 2).evaluate(&[point[..2].to_vec(), vec![F::from(0u64)]].concat());
+        assert_eq!(fixed.evaluate(&point[..2]), expected);
+    }
+
+    #[test]
+    fn test_eq_table() {
+        let r = random_point(3);
+        let table = eq_table(&r);
+        for idx in 0..8 {
+            assert_eq!(table.evals[idx], eq_eval(&hypercube_point(3, idx), &r));
+        }
+
+        // f~(r) = sum_x f(x) eq(x, r)
+        let mle = DenseMLE::<F>::rand(3, &mut rand::thread_rng());
+        let expected: F = iter::zip(&mle.evals, &table.evals).map(|(f, e)| *f * e).sum();
+        assert_eq!(mle.evaluate(&r), expected);
+    }
+}
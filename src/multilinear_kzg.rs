@@ -0,0 +1,132 @@
+use ark_ec::Group;
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+
+use crate::group_utils;
+use crate::multilinear::{self,DenseMLE};
+
+// PAZK, Section 15.4
+// Multilinear KZG commitments (Papamanthou, Shi and Tamassia): a commitment to
+// a multilinear f in v variables is g^{f(tau)} for a secret point tau in F^v
+// fixed during a trusted setup.  For any point r, f decomposes as
+//   f(x) - f(r) = sum_j (x_j - r_j) q_j(x_0, ..., x_{j-1}),
+// where binding x_{v-1}, ..., x_{j+1} of f to r_{v-1}, ..., r_{j+1} leaves a
+// multilinear polynomial whose restrictions to x_j = 1 and x_j = 0 differ by
+// q_j.  An evaluation proof for f(r) = y consists of the commitments to the v
+// quotients q_j, checked with the pairing equation
+//   e(C - g^y, h) == prod_j e(g^{q_j(tau)}, h^{tau_j - r_j}).
+// Commitments are a single group element and proofs are v group elements, so
+// both are logarithmic in the size n = 2^v of the evaluation table, and the
+// verifier computes v + 1 pairings.
+//
+// Since g^{f(tau)} = prod_x (g^{eq(x, tau)})^{f(x)}, the setup publishes the
+// encodings of the Lagrange basis eq(., tau) over the hypercube of each
+// dimension up to v, and commitments are computed from evaluation tables.
+
+/// Structured reference string produced by the trusted setup.
+pub struct MultilinearKzgParams<E: Pairing> {
+    pub num_vars: usize,
+    /// `lagrange_g[j]` holds g^{eq(x, (tau_0, ..., tau_{j-1}))} for x in
+    /// {0,1}^j, for each j from 0 to v.
+    pub lagrange_g: Vec<Vec<E::G1>>,
+    pub h: E::G2,
+    /// h^{tau_j} for each j.
+    pub tau_h: Vec<E::G2>,
+}
+
+/// Run the trusted setup for multilinear polynomials in `tau.len()` variables
+/// using the standard generators of G1 and G2.  The toxic waste `tau` must be
+/// discarded afterwards, since anyone who knows it can forge opening proofs.
+pub fn setup<E: Pairing>(tau: &[E::ScalarField]) -> MultilinearKzgParams<E> {
+    let g = E::G1::generator();
+    let h = E::G2::generator();
+    let lagrange_g = (0..=tau.len())
+        .map(|j| multilinear::eq_table(&tau[..j]).evals.iter().map(|e| g * e).collect())
+        .collect();
+    MultilinearKzgParams {
+        num_vars: tau.len(),
+        lagrange_g,
+        h,
+        tau_h: tau.iter().map(|t| h * t).collect(),
+    }
+}
+
+/// Commit to `mle`, i.e. compute g^{mle(tau)} from the Lagrange basis in G1.
+pub fn commit<E: Pairing>(params: &MultilinearKzgParams<E>, mle: &DenseMLE<E::ScalarField>) -> E::G1 {
+    assert!(mle.num_vars <= params.num_vars, "too many variables for setup");
+    group_utils::msm(&params.lagrange_g[mle.num_vars], &mle.evals)
+}
+
+/// Evaluate `mle` at `point`, returning the evaluation together with a proof
+/// consisting of the commitments to the quotients q_0, ..., q_{v-1}.
+pub fn open<E: Pairing>(params: &MultilinearKzgParams<E>, mle: &DenseMLE<E::ScalarField>, point: &[E::ScalarField]) -> (E::ScalarField, Vec<E::G1>) {
+    assert_eq!(mle.num_vars, point.len(), "point has wrong number of variables");
+    let mut remainder = mle.clone();
+    let mut proof = vec![E::G1::zero(); point.len()];
+    for j in (0..point.len()).rev() {
+        let half = remainder.evals.len() / 2;
+        let quotient: Vec<_> = (0..half).map(|idx| remainder.evals[idx + half] - remainder.evals[idx]).collect();
+        proof[j] = group_utils::msm(&params.lagrange_g[j], &quotient);
+        remainder.fix_last_variable(point[j]);
+    }
+    (remainder.evals[0], proof)
+}
+
+/// Check an opening proof for the claim that the polynomial committed in
+/// `commitment` evaluates to `value` at `point`.
+pub fn verify<E: Pairing>(
+        params: &MultilinearKzgParams<E>,
+        commitment: E::G1,
+        point: &[E::ScalarField],
+        value: E::ScalarField,
+        proof: &[E::G1]) -> bool {
+    if point.len() != proof.len() || point.len() > params.num_vars {
+        return false;
+    }
+    let g = params.lagrange_g[0][0];
+    let lhs = E::pairing(commitment - g * value, params.h);
+    let shifts: Vec<E::G2> = params.tau_h.iter().zip(point).map(|(t, r)| *t - params.h * r).collect();
+    let rhs = E::multi_pairing(proof.iter().copied(), shifts);
+    lhs == rhs
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::One;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::{Bls12_381, Fr};
+    use crate::small_curves::SS67Pairing;
+    use crate::small_fields::F17;
+
+    #[test]
+    fn test_open_verify() {
+        let mut rng = rand::thread_rng();
+        let tau: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let params = setup::<Bls12_381>(&tau);
+        let mle = DenseMLE::<Fr>::rand(4, &mut rng);
+        let commitment = commit(&params, &mle);
+        assert_eq!(commitment, params.lagrange_g[0][0] * mle.evaluate(&tau));
+
+        let point: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        let (value, proof) = open(&params, &mle, &point);
+        assert_eq!(value, mle.evaluate(&point));
+        assert_eq!(proof.len(), 4);
+        assert!(verify(&params, commitment, &point, value, &proof));
+        assert!(!verify(&params, commitment, &point, value + Fr::one(), &proof));
+        assert!(!verify(&params, commitment, &point, value, &proof[1..]));
+    }
+
+    #[test]
+    fn test_small_pairing() {
+        let mut rng = rand::thread_rng();
+        let tau: Vec<F17> = (0..3).map(|_| F17::rand(&mut rng)).collect();
+        let params = setup::<SS67Pairing>(&tau);
+        let mle = DenseMLE::<F17>::rand(3, &mut rng);
+        let commitment = commit(&params, &mle);
+        let point: Vec<F17> = (0..3).map(|_| F17::rand(&mut rng)).collect();
+        let (value, proof) = open(&params, &mle, &point);
+        assert!(verify(&params, commitment, &point, value, &proof));
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+
+use ark_ff::Field;
+use ark_poly::{DenseMVPolynomial,DenseUVPolynomial};
+use ark_poly::polynomial::multivariate::SparsePolynomial as MultiPoly;
+use ark_poly::polynomial::multivariate::{Term,SparseTerm};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials;
+
+// Parser for polynomial expressions such as "x0^2 + 2*x0*x1 + 3*x1^2" or
+// "x^2 + 2xy + 3y^2", with the grammar
+//
+//   expr  := term (('+' | '-') term)*
+//   term  := power (['*'] power)*
+//   power := atom ['^' integer]
+//   atom  := integer | variable | '(' expr ')' | '-' atom
+//
+// Juxtaposition is multiplication, so "2xy" is not allowed but "2x y" and
+// "2x*y" are.  Variables are either indexed, as in x0, x1 or x_0, x_1, or
+// named from a list, in which case the i-th name refers to x_i.
+
+/// Variable names used by default, referring to x_0, x_1 and x_2.
+pub const DEFAULT_VARIABLES: [&str; 3] = ["x", "y", "z"];
+
+// limit on indexed variables, to reject typos like x1000000000
+const MAX_VARIABLES: usize = 64;
+
+/// Error in a polynomial expression, at the given byte offset of the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Display the line of the input containing the error, with a caret under
+    /// the position of the error.
+    pub fn show(&self, input: &str) -> String {
+        let start = input[..self.position].rfind('\n').map_or(0, |i| i + 1);
+        let end = input[self.position..].find('\n').map_or(input.len(), |i| self.position + i);
+        format!("{}\n{}^ {}", &input[start..end], " ".repeat(self.position - start), self.message)
+    }
+
+    /// Line and column of the error in the input, both counted from 1.
+    pub fn location(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        (line, column)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+/// Parse a multivariate polynomial, with variables given by `names` or in
+/// the indexed form x0, x1, ....  The number of variables is one more than
+/// the largest index used.
+pub fn parse_multi_poly<F: Field>(input: &str, names: &[&str]) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+    Parser::new(input, names, true)?.parse()
+}
+
+/// Names of the variables x_0, x_1, ... of a multivariate polynomial as they
+/// are written in `input`, where variables which do not occur are given in
+/// the indexed form x_i.
+pub fn variable_names(input: &str, names: &[&str]) -> Result<Vec<String>, ParseError> {
+    let mut result: Vec<Option<String>> = Vec::new();
+    for (token, _) in tokenize(input)? {
+        let Token::Identifier(name) = token else { continue };
+        let Some(index) = variable_index(&name, names, true) else { continue };
+        if index >= result.len() {
+            result.resize(index + 1, None);
+        }
+        result[index].get_or_insert(name);
+    }
+    Ok(result.into_iter()
+        .enumerate()
+        .map(|(index, name)| name.unwrap_or_else(|| format!("x_{}", index)))
+        .collect())
+}
+
+/// Parse a univariate polynomial in the variable `varname`.
+pub fn parse_univ_poly<F: Field>(input: &str, varname: &str) -> Result<UniPoly<F>, ParseError> {
+    let poly = Parser::new(input, &[varname], false)?.parse::<F>()?;
+    let mut coeffs = vec![F::zero(); poly.terms.iter().map(|(_, t)| t.degree() + 1).max().unwrap_or(0)];
+    for (coeff, term) in poly.terms {
+        coeffs[term.degree()] += coeff;
+    }
+    Ok(UniPoly::from_coefficients_vec(coeffs))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Integer(u64),
+    Identifier(String),
+    Plus,
+    Minus,
+    Star,
+    Caret,
+    LParen,
+    RParen,
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Integer(n) => write!(f, "{}", n),
+            Token::Identifier(name) => write!(f, "{}", name),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Caret => write!(f, "^"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+// split input into tokens together with their positions
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '^' => Token::Caret,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            c if c.is_ascii_digit() => {
+                let mut end = pos + 1;
+                while let Some((i, d)) = chars.peek().copied() {
+                    if !d.is_ascii_digit() { break; }
+                    end = i + 1;
+                    chars.next();
+                }
+                let value = input[pos..end].parse().map_err(|_| ParseError {
+                    position: pos,
+                    message: String::from("integer too large"),
+                })?;
+                Token::Integer(value)
+            }
+            c if c.is_alphabetic() => {
+                let mut end = pos + c.len_utf8();
+                while let Some((i, d)) = chars.peek().copied() {
+                    if !(d.is_alphanumeric() || d == '_') { break; }
+                    end = i + d.len_utf8();
+                    chars.next();
+                }
+                Token::Identifier(input[pos..end].to_string())
+            }
+            c => {
+                return Err(ParseError { position: pos, message: format!("unexpected character '{}'", c) });
+            }
+        };
+        tokens.push((token, pos));
+    }
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    names: &'a [&'a str],
+    indexed: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &str, names: &'a [&'a str], indexed: bool) -> Result<Parser<'a>, ParseError> {
+        Ok(Parser { tokens: tokenize(input)?, index: 0, names, indexed })
+    }
+
+    fn parse<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        let poly = self.expr()?;
+        match self.peek() {
+            Token::End => Ok(poly),
+            Token::RParen => Err(self.error(String::from("unmatched ')'"))),
+            token => Err(self.error(format!("expected operator before '{}'", token))),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.index].0.clone();
+        if token != Token::End { self.index += 1; }
+        token
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError { position: self.tokens[self.index].1, message }
+    }
+
+    fn expr<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        let mut poly = self.term()?;
+        loop {
+            match self.peek() {
+                Token::Plus => { self.next(); poly = &poly + &self.term()?; }
+                Token::Minus => { self.next(); poly = &poly - &self.term()?; }
+                _ => return Ok(poly),
+            }
+        }
+    }
+
+    fn term<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        let mut poly = self.factor()?;
+        loop {
+            match self.peek() {
+                Token::Star => { self.next(); }
+                // juxtaposition
+                Token::Integer(_) | Token::Identifier(_) | Token::LParen => {}
+                _ => return Ok(poly),
+            }
+            poly = polynomials::multiply(&poly, &self.factor()?);
+        }
+    }
+
+    // unary minus binds less tightly than '^', so -x^2 is -(x^2)
+    fn factor<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        if *self.peek() == Token::Minus {
+            self.next();
+            return Ok(-self.factor()?);
+        }
+        self.power()
+    }
+
+    fn power<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        let base = self.atom()?;
+        if *self.peek() != Token::Caret {
+            return Ok(base);
+        }
+        self.next();
+        let Token::Integer(exp) = self.peek().clone() else {
+            return Err(self.error(String::from("expected integer exponent after '^'")));
+        };
+        self.next();
+
+        // square and multiply
+        let mut result = constant(F::one());
+        let mut square = base;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = polynomials::multiply(&result, &square);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                square = polynomials::multiply(&square, &square);
+            }
+        }
+        Ok(result)
+    }
+
+    fn atom<F: Field>(&mut self) -> Result<MultiPoly<F, SparseTerm>, ParseError> {
+        let position = self.tokens[self.index].1;
+        match self.next() {
+            Token::Integer(n) => Ok(constant(F::from(n))),
+            Token::Identifier(name) => {
+                let index = self.variable(&name).ok_or(ParseError {
+                    position,
+                    message: format!("unknown variable '{}'", name),
+                })?;
+                Ok(MultiPoly::from_coefficients_vec(index + 1, vec![(F::one(), SparseTerm::new(vec![(index, 1)]))]))
+            }
+            Token::LParen => {
+                let poly = self.expr()?;
+                if *self.peek() != Token::RParen {
+                    return Err(self.error(String::from("expected ')'")));
+                }
+                self.next();
+                Ok(poly)
+            }
+            token => {
+                Err(ParseError { position, message: format!("expected expression but found {}", describe(&token)) })
+            }
+        }
+    }
+
+    fn variable(&self, name: &str) -> Option<usize> {
+        variable_index(name, self.names, self.indexed)
+    }
+}
+
+/// Index of a variable given by one of `names`, or if `indexed` in the form
+/// x0, x1, ... or x_0, x_1, ....
+pub(crate) fn variable_index(name: &str, names: &[&str], indexed: bool) -> Option<usize> {
+    if let Some(index) = names.iter().position(|n| *n == name) {
+        return Some(index);
+    }
+    if !indexed { return None; }
+    let digits = name.strip_prefix('x')?;
+    let digits = digits.strip_prefix('_').unwrap_or(digits);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|index| *index < MAX_VARIABLES)
+}
+
+/// Whitespace separated words of a line together with their byte offsets,
+/// for reporting errors in line based formats.
+pub(crate) fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |word| (word.as_ptr() as usize - line.as_ptr() as usize, word))
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::End => token.to_string(),
+        _ => format!("'{}'", token),
+    }
+}
+
+fn constant<F: Field>(value: F) -> MultiPoly<F, SparseTerm> {
+    MultiPoly::from_coefficients_vec(0, vec![(value, SparseTerm::new(vec![]))])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_fields::F13 as F;
+
+    #[test]
+    fn test_parse_multi_poly() {
+        let expected = polynomials::construct_poly(2, vec![
+            (1, vec![(0, 2)]),
+            (2, vec![(0, 1), (1, 1)]),
+            (3, vec![(1, 2)]),
+        ]);
+        let inputs = [
+            "x0^2 + 2*x0*x1 + 3*x1^2",
+            "x^2 + 2x y + 3y^2",
+            "x_0 x_0 + 2 * x_1 * x_0 + 3 * x_1 ^ 2",
+            "(x + y)^2 + 2y^2",
+            "(x + 3y)(x - y) + 6y^2",
+        ];
+        for input in inputs {
+            assert_eq!(parse_multi_poly::<F>(input, &DEFAULT_VARIABLES), Ok(expected.clone()), "{}", input);
+        }
+
+        let poly = parse_multi_poly::<F>("a*b - 12b", &["a", "b"]).unwrap();
+        let expected = polynomials::construct_poly(2, vec![(1, vec![(0, 1), (1, 1)]), (1, vec![(1, 1)])]);
+        assert_eq!(poly, expected);
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        let cases = [
+            ("-x^2", polynomials::construct_poly(1, vec![(12, vec![(0, 2)])])),
+            ("-2x^3", polynomials::construct_poly(1, vec![(11, vec![(0, 3)])])),
+            ("x - -y", polynomials::construct_poly(2, vec![(1, vec![(0, 1)]), (1, vec![(1, 1)])])),
+            ("(-x)^2", polynomials::construct_poly(1, vec![(1, vec![(0, 2)])])),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_multi_poly::<F>(input, &DEFAULT_VARIABLES), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_variable_names() {
+        assert_eq!(variable_names("x^2 + 2x y", &DEFAULT_VARIABLES), Ok(vec![String::from("x"), String::from("y")]));
+        assert_eq!(variable_names("x0 + x_2", &DEFAULT_VARIABLES),
+            Ok(vec![String::from("x0"), String::from("x_1"), String::from("x_2")]));
+    }
+
+    #[test]
+    fn test_parse_univ_poly() {
+        let poly = parse_univ_poly::<F>("6*t^2 + 2t + 1", "t").unwrap();
+        let expected = UniPoly::from_coefficients_vec(vec![F::from(1u64), F::from(2u64), F::from(6u64)]);
+        assert_eq!(poly, expected);
+        assert!(parse_univ_poly::<F>("x0 + 1", "t").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let cases = [
+            ("x^2 + ", 6, "expected expression but found end of input"),
+            ("x^2 + 2*w", 8, "unknown variable 'w'"),
+            ("(x + y", 6, "expected ')'"),
+            ("x + y)", 5, "unmatched ')'"),
+            ("x^y", 2, "expected integer exponent after '^'"),
+            ("x + $", 4, "unexpected character '$'"),
+            ("2xy", 1, "unknown variable 'xy'"),
+        ];
+        for (input, position, message) in cases {
+            let error = parse_multi_poly::<F>(input, &DEFAULT_VARIABLES).unwrap_err();
+            assert_eq!(error, ParseError { position, message: String::from(message) }, "{}", input);
+        }
+    }
+}
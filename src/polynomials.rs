@@ -1,41 +1,135 @@
 use std::cmp;
-use std::iter::Iterator;
+use std::fmt;
+use std::iter::{self,Iterator};
 
-use ark_ff::Zero;
-use ark_ff::fields::{Field,Fp64,FpConfig};
+use ark_ff::fields::{Field,PrimeField,Fp64,FpConfig};
 
-use ark_poly::{Polynomial,DenseMVPolynomial};
+use ark_poly::{Polynomial,DenseMVPolynomial,DenseUVPolynomial};
 use ark_poly::polynomial::multivariate::SparsePolynomial as MultiPoly;
 use ark_poly::polynomial::multivariate::{Term,SparseTerm};
 use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
 
-use crate::small_fields;
 
+/// Options for displaying polynomials.
+#[derive(Clone, Debug)]
+pub struct PolyFormat {
+    /// Names of the variables x_0, x_1, ...; variables without a name are
+    /// displayed by index.
+    pub variables: Vec<String>,
+    /// Display coefficients as centered residues in (-p/2, p/2).
+    pub signed: bool,
+    /// Produce LaTeX source rather than plain text.
+    pub latex: bool,
+}
+
+impl Default for PolyFormat {
+    fn default() -> Self {
+        PolyFormat::new(&[])
+    }
+}
+
+impl PolyFormat {
+    pub fn new(variables: &[&str]) -> PolyFormat {
+        PolyFormat {
+            variables: variables.iter().map(|v| v.to_string()).collect(),
+            signed: true,
+            latex: false,
+        }
+    }
+
+    pub fn univariate<F: PrimeField>(&self, poly: &UniPoly<F>) -> String {
+        let terms = poly.coeffs
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(exp, coeff)| (*coeff, vec![(0, exp)]));
+        self.join_terms(terms)
+    }
+
+    pub fn multivariate<F: PrimeField>(&self, poly: &MultiPoly<F, SparseTerm>) -> String {
+        let terms = poly.terms
+            .iter()
+            .rev()
+            .map(|(coeff, term)| (*coeff, term.to_vec()));
+        self.join_terms(terms)
+    }
 
-pub fn format_univ_poly<T: FpConfig<1>>(poly: &UniPoly<Fp64<T>>, varname: &str) -> String {
-    if poly.coeffs.len() == 0 || !poly.coeffs.iter().any(|coeff| *coeff != Fp64::zero()) {
-        String::from("0")
+    fn variable(&self, index: usize) -> String {
+        match (self.variables.get(index), self.latex) {
+            (Some(name), _) => name.clone(),
+            (None, false) => format!("x_{}", index),
+            (None, true) => format!("x_{{{}}}", index),
+        }
+    }
+
+    fn monomial(&self, powers: &[(usize, usize)]) -> String {
+        let factors: Vec<String> = powers
+            .iter()
+            .filter(|(_, exp)| *exp > 0)
+            .map(|(var, exp)| match (*exp, self.latex) {
+                (1, _) => self.variable(*var),
+                (_, false) => format!("{}^{}", self.variable(*var), exp),
+                (_, true) => format!("{}^{{{}}}", self.variable(*var), exp),
+            })
+            .collect();
+        factors.join(if self.latex { " " } else { "*" })
+    }
+
+    fn join_terms<F: PrimeField>(&self, terms: impl Iterator<Item = (F, Vec<(usize, usize)>)>) -> String {
+        let mut result = String::new();
+        for (coeff, powers) in terms.filter(|(coeff, _)| !coeff.is_zero()) {
+            let negative = self.signed && is_negative(coeff);
+            let coeff = if negative { -coeff } else { coeff };
+            let monomial = self.monomial(&powers);
+            let term = match (monomial.is_empty(), coeff.is_one()) {
+                (true, _) => format!("{}", coeff.into_bigint()),
+                (false, true) => monomial,
+                (false, false) if self.latex => format!("{} {}", coeff.into_bigint(), monomial),
+                (false, false) => format!("{}*{}", coeff.into_bigint(), monomial),
+            };
+            result += match (result.is_empty(), negative) {
+                (true, false) => "",
+                (true, true) => "-",
+                (false, false) => " + ",
+                (false, true) => " - ",
+            };
+            result += &term;
+        }
+        if result.is_empty() { String::from("0") } else { result }
+    }
+}
+
+// whether the centered residue of x is negative
+fn is_negative<F: PrimeField>(x: F) -> bool {
+    x.into_bigint() > F::MODULUS_MINUS_ONE_DIV_TWO
+}
+
+/// Display a field element, as a centered residue in (-p/2, p/2) if `signed`.
+pub fn format_field<F: PrimeField>(x: F, signed: bool) -> String {
+    if signed && is_negative(x) {
+        format!("-{}", (-x).into_bigint())
     } else {
-        poly.coeffs
-        .iter()
-        .enumerate()
-        .rev()
-        .filter(|(_, coeff)| **coeff != Fp64::zero())
-        .map(|(exp, coeff)| {
-            let coeff = small_fields::to_u64(*coeff);
-            match (exp, coeff) {
-                (0, _) => format!("{}", coeff),
-                (1, 1) => format!("{}", varname),
-                (1, _) => format!("{}*{}", coeff, varname),
-                (_, 1) => format!("{}^{}", varname, exp),
-                (_, _) => format!("{}*{}^{}", coeff, varname, exp),
-            }
-        })
-        .collect::<Vec<_>>()
-        .join(" + ")
+        format!("{}", x.into_bigint())
     }
 }
 
+/// Display a point of F^n as a tuple (x_1, ..., x_n).
+pub fn format_point<F: PrimeField>(point: &[F]) -> String {
+    let coords: Vec<String> = point.iter().map(|x| format_field(*x, false)).collect();
+    format!("({})", coords.join(", "))
+}
+
+/// Display a univariate polynomial with coefficients as centered residues.
+pub fn format_univ_poly<F: PrimeField>(poly: &UniPoly<F>, varname: &str) -> String {
+    PolyFormat::new(&[varname]).univariate(poly)
+}
+
+/// Display a multivariate polynomial with coefficients as centered residues,
+/// naming the variables by `names` or by index.
+pub fn format_multi_poly<F: PrimeField>(poly: &MultiPoly<F, SparseTerm>, names: &[&str]) -> String {
+    PolyFormat::new(names).multivariate(poly)
+}
+
 /// Compute the sums of k-powers of the list of summands for k up to `max_exponent`.
 fn power_sums<F: Field>(max_exponent: usize, summands: &Vec<F>) -> Vec<F> {
     let mut powers: Vec<F> = vec![F::one(); summands.len()];
@@ -53,11 +147,11 @@ fn power_sums<F: Field>(max_exponent: usize, summands: &Vec<F>) -> Vec<F> {
 /// variable either remains unevaluated or is summed over a fixed set of values.
 /// Using a single value for the summation set is equivalent to evaluating the
 /// corresponding variable at this value.
-pub fn partial_summation<F: Field>(f: &MultiPoly<F, SparseTerm>, vals: &Vec<Option<Vec<F>>>) -> MultiPoly<F, SparseTerm> {
+pub fn partial_summation<F: Field>(f: &MultiPoly<F, SparseTerm>, vals: &[Option<Vec<F>>]) -> MultiPoly<F, SparseTerm> {
     let monom_terms: Vec< Option<Vec<F>> > =
         vals.iter()
             .map(|val| match val {
-                Some(summands) => Some(power_sums(f.degree(), &summands)),
+                Some(summands) => Some(power_sums(f.degree(), summands)),
                 None => None,
             })
             .collect();
@@ -84,11 +178,7 @@ pub fn partial_summation<F: Field>(f: &MultiPoly<F, SparseTerm>, vals: &Vec<Opti
                     .fold(*coeff, |acc, val| acc * val);
                 let new_term = SparseTerm::new(term
                     .iter()
-                    .filter(|(idx, _)| match &monom_terms[*idx] {
-                        None => true,
-                        _ => false,
-                    })
-                    .map(|tup| *tup )
+                    .filter(|(idx, _)| monom_terms[*idx].is_none()).copied()
                     .collect());
                 (new_coeff, new_term)
             })
@@ -98,7 +188,7 @@ pub fn partial_summation<F: Field>(f: &MultiPoly<F, SparseTerm>, vals: &Vec<Opti
 
 /// Compute the partial evaluation of `poly` by the specified field element and variable.
 pub fn partial_eval<F: Field>(poly: &MultiPoly<F, SparseTerm>, value: F, variable: usize) -> MultiPoly<F, SparseTerm> {
-    let vals = (0..poly.num_vars)
+    let vals: Vec<Option<Vec<F>>> = (0..poly.num_vars)
         .map(|n| {
             if n == variable {
                 Some(vec![value])
@@ -107,7 +197,153 @@ pub fn partial_eval<F: Field>(poly: &MultiPoly<F, SparseTerm>, value: F, variabl
             }
         })
         .collect();
-    partial_summation(&poly, &vals)
+    partial_summation(poly, &vals)
+}
+
+/// Compute the unique polynomial of degree less than `points.len()` taking
+/// the given values at the given (distinct) points.
+pub fn lagrange_interpolate<F: Field>(points: &[F], values: &[F]) -> UniPoly<F> {
+    assert_eq!(points.len(), values.len(), "need one value per interpolation point");
+    let weights = barycentric_weights(points);
+    let mut coeffs = vec![F::zero(); points.len()];
+    for (i, value) in values.iter().enumerate() {
+        // add value * w_i * prod_{k != i} (X - x_k)
+        let mut basis = vec![*value * weights[i]];
+        for (k, x_k) in points.iter().enumerate() {
+            if k == i { continue; }
+            let mut next = vec![F::zero(); basis.len() + 1];
+            for (exp, coeff) in basis.iter().enumerate() {
+                next[exp + 1] += coeff;
+                next[exp] -= *coeff * x_k;
+            }
+            basis = next;
+        }
+        for (coeff, term) in coeffs.iter_mut().zip(basis) {
+            *coeff += term;
+        }
+    }
+    UniPoly::from_coefficients_vec(coeffs)
+}
+
+/// Compute the barycentric weights w_i = 1 / prod_{k != i} (x_i - x_k) of a
+/// list of distinct interpolation points.
+pub fn barycentric_weights<F: Field>(points: &[F]) -> Vec<F> {
+    points.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            points.iter()
+                .enumerate()
+                .filter(|(k, _)| *k != i)
+                .map(|(_, x_k)| *x_i - x_k)
+                .product::<F>()
+                .inverse()
+                .expect("interpolation points must be distinct")
+        })
+        .collect()
+}
+
+/// Evaluate at `r` the polynomial of degree less than `points.len()` taking
+/// the given values at the given points, using the barycentric formula
+///   p(r) = l(r) * sum_i w_i y_i / (r - x_i),  l(r) = prod_i (r - x_i),
+/// in O(n) operations once the weights are known.
+pub fn barycentric_evaluate<F: Field>(points: &[F], weights: &[F], values: &[F], r: F) -> F {
+    if let Some(i) = points.iter().position(|x_i| *x_i == r) {
+        return values[i];
+    }
+    let l: F = points.iter().map(|x_i| r - x_i).product();
+    let sum: F = points.iter()
+        .zip(weights)
+        .zip(values)
+        .map(|((x_i, w_i), y_i)| *w_i * y_i * (r - x_i).inverse().unwrap())
+        .sum();
+    l * sum
+}
+
+/// Evaluate at `r` the polynomial of degree at most d with the given values
+/// g(0), g(1), ..., g(d), as sent in sum-check messages.
+pub fn evaluate_from_evaluations<F: Field>(values: &[F], r: F) -> F {
+    let points = integer_points(values.len());
+    barycentric_evaluate(&points, &barycentric_weights(&points), values, r)
+}
+
+/// Interpolate the polynomial of degree at most d with the given values
+/// g(0), g(1), ..., g(d).
+pub fn interpolate_evaluations<F: Field>(values: &[F]) -> UniPoly<F> {
+    lagrange_interpolate(&integer_points(values.len()), values)
+}
+
+/// The field elements 0, 1, ..., n - 1.
+pub fn integer_points<F: Field>(n: usize) -> Vec<F> {
+    (0..n as u64).map(F::from).collect()
+}
+
+/// Low-degree extension of a vector: the evaluations over `domain` of the
+/// polynomial of degree less than `points.len()` taking the given values at
+/// the given points.
+pub fn low_degree_extension<F: Field>(points: &[F], values: &[F], domain: &[F]) -> Vec<F> {
+    assert_eq!(points.len(), values.len(), "need one value per interpolation point");
+    let weights = barycentric_weights(points);
+    domain.iter()
+        .map(|r| barycentric_evaluate(points, &weights, values, *r))
+        .collect()
+}
+
+// PAZK, Section 2.1
+// The Reed-Solomon encoding of a message (a_0, ..., a_{k-1}) over a domain of
+// n >= k distinct points is the list of evaluations of p(X) = sum_i a_i X^i
+// over the domain.  Two distinct codewords agree in fewer than k positions, so
+// any nonzero number of errors up to n - k is detected by checking that the
+// codeword is consistent with a polynomial of degree less than k.
+
+/// Reed-Solomon encoding of `message`, as coefficients, over `domain`.
+pub fn rs_encode<F: Field>(message: &[F], domain: &[F]) -> Vec<F> {
+    assert!(message.len() <= domain.len(), "message is longer than the domain");
+    domain.iter()
+        .map(|x| message.iter().rev().fold(F::zero(), |acc, a| acc * x + a))
+        .collect()
+}
+
+/// Position at which a received word disagrees with the polynomial of degree
+/// less than the message length interpolating its first values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodingError {
+    pub position: usize,
+}
+
+impl fmt::Display for DecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "not a codeword: value at position {} is inconsistent with the others", self.position)
+    }
+}
+
+/// Decode a Reed-Solomon codeword over `domain` to a message of length
+/// `message_len`, detecting any errors in at most n - k positions.  The
+/// message is recovered from the first `message_len` values, and each other
+/// value is checked against it.
+pub fn rs_decode<F: Field>(codeword: &[F], domain: &[F], message_len: usize) -> Result<Vec<F>, DecodingError> {
+    assert_eq!(codeword.len(), domain.len(), "need one value per domain point");
+    assert!(message_len <= domain.len(), "message is longer than the domain");
+    let mut message = lagrange_interpolate(&domain[..message_len], &codeword[..message_len]).coeffs;
+    message.resize(message_len, F::zero());
+    let expected = rs_encode(&message, domain);
+    match iter::zip(codeword, &expected).position(|(y, z)| y != z) {
+        Some(position) => Err(DecodingError { position }),
+        None => Ok(message),
+    }
+}
+
+/// Compute the product of two multivariate polynomials.
+pub fn multiply<F: Field>(f: &MultiPoly<F, SparseTerm>, g: &MultiPoly<F, SparseTerm>) -> MultiPoly<F, SparseTerm> {
+    let terms = f.terms
+        .iter()
+        .flat_map(|(f_coeff, f_term)| {
+            g.terms.iter().map(move |(g_coeff, g_term)| {
+                let monom = f_term.iter().chain(g_term.iter()).copied().collect();
+                (*f_coeff * g_coeff, SparseTerm::new(monom))
+            })
+        })
+        .collect();
+    MultiPoly::from_coefficients_vec(cmp::max(f.num_vars, g.num_vars), terms)
 }
 
 /// Restrict a multivariate polynomial to the univariate polynomial obtained by
@@ -183,6 +419,69 @@ mod tests {
         assert_eq!(ps, ps_by_hand);
     }
 
+    #[test]
+    fn test_format() {
+        let poly: MultiPoly<Fq, SparseTerm> = construct_poly(2, vec![
+            (1, vec![(0, 2)]),
+            (2, vec![(0, 1), (1, 1)]),
+            (3, vec![(1, 2)]),
+            (4, vec![]),
+        ]);
+        assert_eq!(format_multi_poly(&poly, &["x", "y"]), "x^2 + 2*x*y - 2*y^2 - 1");
+        assert_eq!(format_multi_poly(&poly, &[]), "x_0^2 + 2*x_0*x_1 - 2*x_1^2 - 1");
+
+        let latex = PolyFormat { latex: true, ..PolyFormat::default() };
+        assert_eq!(latex.multivariate(&poly), "x_{0}^{2} + 2 x_{0} x_{1} - 2 x_{1}^{2} - 1");
+        let unsigned = PolyFormat { signed: false, ..PolyFormat::new(&["x", "y"]) };
+        assert_eq!(unsigned.multivariate(&poly), "x^2 + 2*x*y + 3*y^2 + 4");
+
+        let uni = UniPoly::from_coefficients_vec(vec![Fq::from(0u64), -Fq::from(1u64), Fq::from(1u64)]);
+        assert_eq!(format_univ_poly(&uni, "t"), "t^2 - t");
+        assert_eq!(format_univ_poly(&UniPoly::<Fq>::from_coefficients_vec(vec![]), "t"), "0");
+        assert_eq!(format_field(-Fq::from(2u64), true), "-2");
+        assert_eq!(format_field(Fq::from(0u64), true), "0");
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let mut rng = rand::thread_rng();
+        let poly = UniPoly::<Fq>::from_coefficients_vec(vec![Fq::from(3u64), Fq::from(1u64), Fq::from(4u64)]);
+        let points: Vec<Fq> = (1..=3u64).map(Fq::from).collect();
+        let values: Vec<Fq> = points.iter().map(|x| poly.evaluate(x)).collect();
+        assert_eq!(lagrange_interpolate(&points, &values), poly);
+
+        let weights = barycentric_weights(&points);
+        let r = Fq::rand(&mut rng);
+        assert_eq!(barycentric_evaluate(&points, &weights, &values, r), poly.evaluate(&r));
+
+        let evals: Vec<Fq> = integer_points(3).iter().map(|x| poly.evaluate(x)).collect();
+        assert_eq!(interpolate_evaluations(&evals), poly);
+        assert_eq!(evaluate_from_evaluations(&evals, r), poly.evaluate(&r));
+    }
+
+    #[test]
+    fn test_reed_solomon() {
+        use crate::small_fields::F13;
+        let domain: Vec<F13> = integer_points(7);
+        let message: Vec<F13> = [2u64, 0, 5].into_iter().map(F13::from).collect();
+        let codeword = rs_encode(&message, &domain);
+        let poly = UniPoly::from_coefficients_slice(&message);
+        assert!(iter::zip(&domain, &codeword).all(|(x, y)| poly.evaluate(x) == *y));
+        assert_eq!(rs_decode(&codeword, &domain, 3), Ok(message.clone()));
+
+        // the extension of any 3 values of the codeword is the whole codeword
+        assert_eq!(low_degree_extension(&domain[4..], &codeword[4..], &domain), codeword);
+
+        // up to n - k = 4 errors are detected
+        let mut corrupted = codeword.clone();
+        corrupted[5] += F13::from(1u64);
+        assert_eq!(rs_decode(&corrupted, &domain, 3), Err(DecodingError { position: 5 }));
+        for position in [0, 1, 6] {
+            corrupted[position] += F13::from(3u64);
+        }
+        assert!(rs_decode(&corrupted, &domain, 3).is_err());
+    }
+
     #[test]
     fn test_partial_summation() {
         let test_vectors = vec![
@@ -278,28 +577,15 @@ mod tests {
         let result = UniPoly { coeffs: vec![Fq::zero(), Fq::zero(), Fq::one()] };
         assert_eq!(uni, result, "f(x, y) = x^2 + 2xy + 3y^2 restricted to x is g(x) = x^2");
 
-        let partial = partial_summation(&poly, &vec![Some(vec![Fq::from(2)]), None]);
+        let partial = partial_summation(&poly, &[Some(vec![Fq::from(2)]), None]);
         let uni = into_univariate(&partial, 1);
         let result: UniPoly<Fq> = UniPoly { coeffs: vec![Fq::from(4), Fq::from(4), Fq::from(3)]};
         assert_eq!(uni, result, "f(x, y) = 3y^2 + 4y + 4 restricted to y is itself as a univariate polynomial");
     }
 
     fn construct_vals(vals_spec: Vec<Option<Vec<u64>>>) -> Vec<Option<Vec<Fq>>> {
-    vals_spec.into_iter()
-        .map(|var_option| {
-            match var_option {
-                None => None,
-                Some(vec) => {
-                    Some(
-                        vec.into_iter()
-                            .map(|x| {
-                                Fq::from(x)
-                            })
-                            .collect()
-                    )
-                }
-            }
-        })
-        .collect()
+        vals_spec.into_iter()
+            .map(|var_option| var_option.map(|vec| vec.into_iter().map(Fq::from).collect()))
+            .collect()
     }
 }
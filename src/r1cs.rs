@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ark_ff::{Field,PrimeField};
+use ndarray::Array2;
+
+use crate::polynomials;
+use crate::multilinear::{self,DenseMLE};
+use crate::circuit::{Circuit,Operation};
+
+// PAZK, Chapter 8
+// A rank-1 constraint system (R1CS) consists of m x n matrices A, B and C, and
+// is satisfied by a vector z in F^n when
+//   (A z) ∘ (B z) = C z,
+// with ∘ the entrywise product, so that row i is the constraint
+//   <A_i, z> * <B_i, z> = <C_i, z>.
+// The vector z = (1, x, w) consists of the constant 1, the public inputs x and
+// the witness w, so that the constraints can express affine relations.
+//
+// An arithmetic circuit is converted to an R1CS by keeping every gate as a
+// linear combination of the entries of z.  Sums are linear and need no
+// constraints, while a product of k gates needs k - 1 constraints, each
+// introducing a witness entry for a partial product.  The public inputs are
+// the circuit inputs followed by the circuit outputs, and each output is tied
+// to its linear combination by one more constraint with B_i = (1, 0, ..., 0).
+
+/// Sparse matrix stored as (row, column, value) triplets.  Entries with the
+/// same position are added together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMatrix<F> {
+    pub num_rows: usize,
+    pub num_cols: usize,
+    pub entries: Vec<(usize, usize, F)>,
+}
+
+impl<F: Field> SparseMatrix<F> {
+    pub fn new(num_rows: usize, num_cols: usize, entries: Vec<(usize, usize, F)>) -> SparseMatrix<F> {
+        assert!(entries.iter().all(|(i, j, _)| *i < num_rows && *j < num_cols), "entry out of bounds");
+        SparseMatrix { num_rows, num_cols, entries }
+    }
+
+    /// Product of the matrix with the vector `z`.
+    pub fn mul_vector(&self, z: &[F]) -> Vec<F> {
+        assert_eq!(z.len(), self.num_cols, "vector has the wrong length");
+        let mut result = vec![F::zero(); self.num_rows];
+        for (i, j, value) in &self.entries {
+            result[*i] += *value * z[*j];
+        }
+        result
+    }
+
+    /// Number of variables of the multilinear extension M~(x, y) in the row
+    /// index x.
+    pub fn row_bits(&self) -> usize {
+        multilinear::index_bits(self.num_rows)
+    }
+
+    /// Number of variables of M~(x, y) in the column index y.
+    pub fn col_bits(&self) -> usize {
+        multilinear::index_bits(self.num_cols)
+    }
+
+    /// Table of M~(r_x, y) over the hypercube in y, in time linear in the
+    /// number of entries and the padded size.
+    pub fn fix_rows(&self, r_x: &[F]) -> DenseMLE<F> {
+        let eq_x = multilinear::eq_table(r_x);
+        let mut evals = vec![F::zero(); 1 << self.col_bits()];
+        for (i, j, value) in &self.entries {
+            evals[*j] += eq_x.evals[*i] * value;
+        }
+        DenseMLE::new(self.col_bits(), evals)
+    }
+
+    /// Evaluate M~(r_x, r_y) from the nonzero entries.
+    pub fn evaluate_mle(&self, r_x: &[F], r_y: &[F]) -> F {
+        let (eq_x, eq_y) = (multilinear::eq_table(r_x), multilinear::eq_table(r_y));
+        self.entries.iter().map(|(i, j, value)| eq_x.evals[*i] * eq_y.evals[*j] * value).sum()
+    }
+
+    pub fn to_dense(&self) -> Array2<F> {
+        let mut dense = Array2::from_elem((self.num_rows, self.num_cols), F::zero());
+        for (i, j, value) in &self.entries {
+            dense[(*i, *j)] += *value;
+        }
+        dense
+    }
+}
+
+/// Rank-1 constraint system over z = (1, x, w), with `num_public` public
+/// inputs x and `num_witness` witness entries w.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1CS<F> {
+    pub a: SparseMatrix<F>,
+    pub b: SparseMatrix<F>,
+    pub c: SparseMatrix<F>,
+    pub num_public: usize,
+    pub num_witness: usize,
+}
+
+/// Constraint which is not satisfied, with the values of <A_i, z>, <B_i, z>
+/// and <C_i, z>.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unsatisfied<F> {
+    pub row: usize,
+    pub a: F,
+    pub b: F,
+    pub c: F,
+}
+
+impl<F: PrimeField> fmt::Display for Unsatisfied<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "constraint {} fails: {} * {} != {}", self.row,
+            polynomials::format_field(self.a, true),
+            polynomials::format_field(self.b, true),
+            polynomials::format_field(self.c, true))
+    }
+}
+
+impl<F: Field> R1CS<F> {
+    pub fn new(a: SparseMatrix<F>, b: SparseMatrix<F>, c: SparseMatrix<F>, num_public: usize) -> R1CS<F> {
+        assert!(a.num_rows == b.num_rows && b.num_rows == c.num_rows, "matrices must have the same number of rows");
+        assert!(a.num_cols == b.num_cols && b.num_cols == c.num_cols, "matrices must have the same number of columns");
+        assert!(num_public < a.num_cols, "z must have room for the constant and the public inputs");
+        let num_witness = a.num_cols - 1 - num_public;
+        R1CS { a, b, c, num_public, num_witness }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.a.num_rows
+    }
+
+    /// Length of z = (1, x, w).
+    pub fn num_variables(&self) -> usize {
+        self.a.num_cols
+    }
+
+    /// The vector z = (1, x, w).
+    pub fn z_vector(&self, public: &[F], witness: &[F]) -> Vec<F> {
+        assert_eq!(public.len(), self.num_public, "wrong number of public inputs");
+        assert_eq!(witness.len(), self.num_witness, "wrong witness length");
+        let mut z = Vec::with_capacity(self.num_variables());
+        z.push(F::one());
+        z.extend_from_slice(public);
+        z.extend_from_slice(witness);
+        z
+    }
+
+    /// Check every constraint, reporting the first one which fails.
+    pub fn is_satisfied(&self, public: &[F], witness: &[F]) -> Result<(), Unsatisfied<F>> {
+        let z = self.z_vector(public, witness);
+        let (az, bz, cz) = (self.a.mul_vector(&z), self.b.mul_vector(&z), self.c.mul_vector(&z));
+        match (0..self.num_constraints()).find(|i| az[*i] * bz[*i] != cz[*i]) {
+            Some(row) => Err(Unsatisfied { row, a: az[row], b: bz[row], c: cz[row] }),
+            None => Ok(()),
+        }
+    }
+
+    /// R1CS for a circuit, with public inputs the circuit inputs followed by
+    /// the outputs.  The circuit must be valid.
+    pub fn from_circuit(circuit: &Circuit<F>) -> R1CS<F> {
+        compile(circuit, None).0
+    }
+}
+
+/// Public inputs and witness satisfying `R1CS::from_circuit(circuit)` for the
+/// given circuit inputs.
+pub fn circuit_assignment<F: Field>(circuit: &Circuit<F>, inputs: &[F]) -> (Vec<F>, Vec<F>) {
+    let values = circuit.evaluate(inputs);
+    let mut public = inputs.to_vec();
+    public.extend(circuit.output_values(&values));
+    let (_, witness) = compile(circuit, Some(&values));
+    (public, witness)
+}
+
+// linear combination of the entries of z, by index
+type Combination<F> = BTreeMap<usize, F>;
+
+// walk the circuit building the constraints, and the witness if the gate
+// values are given
+fn compile<F: Field>(circuit: &Circuit<F>, values: Option<&[F]>) -> (R1CS<F>, Vec<F>) {
+    let num_inputs = circuit.num_inputs();
+    let num_public = num_inputs + circuit.outputs.len();
+    let single = |j: usize| Combination::from([(j, F::one())]);
+
+    let mut constraints: Vec<[Combination<F>; 3]> = Vec::new();
+    let mut witness = Vec::new();
+    let mut num_witness = 0;
+    let mut combinations: Vec<Combination<F>> = Vec::with_capacity(circuit.gates.len());
+    let mut next_input = 1;
+    for op in &circuit.gates {
+        let combination = match op {
+            Operation::Input => {
+                next_input += 1;
+                single(next_input - 1)
+            }
+            Operation::Const(c) => Combination::from([(0, *c)]),
+            Operation::Add(operands) => {
+                let mut sum = Combination::new();
+                for operand in operands {
+                    for (j, value) in &combinations[*operand] {
+                        *sum.entry(*j).or_insert(F::zero()) += *value;
+                    }
+                }
+                sum.retain(|_, value| !value.is_zero());
+                sum
+            }
+            Operation::Mul(operands) => {
+                let mut product = combinations[operands[0]].clone();
+                let mut product_value = values.map(|values| values[operands[0]]);
+                for operand in &operands[1..] {
+                    let j = 1 + num_public + num_witness;
+                    num_witness += 1;
+                    constraints.push([product, combinations[*operand].clone(), single(j)]);
+                    if let (Some(values), Some(value)) = (values, product_value.as_mut()) {
+                        *value *= values[*operand];
+                        witness.push(*value);
+                    }
+                    product = single(j);
+                }
+                product
+            }
+        };
+        combinations.push(combination);
+    }
+    for (k, output) in circuit.outputs.iter().enumerate() {
+        constraints.push([combinations[*output].clone(), single(0), single(1 + num_inputs + k)]);
+    }
+
+    let num_cols = 1 + num_public + num_witness;
+    let matrix = |m: usize| SparseMatrix::new(constraints.len(), num_cols, constraints.iter()
+        .enumerate()
+        .flat_map(|(i, row)| row[m].iter().map(move |(j, value)| (i, *j, *value)))
+        .collect());
+    (R1CS::new(matrix(0), matrix(1), matrix(2), num_public), witness)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::circuit_parser;
+    use crate::mat_mult;
+    use crate::small_fields::F13 as F;
+
+    fn field_vec(values: &[u64]) -> Vec<F> {
+        values.iter().map(|x| F::from(*x)).collect()
+    }
+
+    #[test]
+    fn test_is_satisfied() {
+        // x^3 + x + 5 = y with z = (1, x, y, x^2, x^3)
+        let one = F::from(1u64);
+        let a = SparseMatrix::new(3, 5, vec![(0, 1, one), (1, 3, one), (2, 4, one), (2, 1, one), (2, 0, F::from(5u64))]);
+        let b = SparseMatrix::new(3, 5, vec![(0, 1, one), (1, 1, one), (2, 0, one)]);
+        let c = SparseMatrix::new(3, 5, vec![(0, 3, one), (1, 4, one), (2, 2, one)]);
+        let r1cs = R1CS::new(a, b, c, 2);
+        assert_eq!(r1cs.num_witness, 2);
+        assert_eq!(r1cs.a.to_dense()[(2, 0)], F::from(5u64));
+
+        // the extension agrees with that of the dense matrix
+        let mut rng = rand::thread_rng();
+        let r_x: Vec<F> = (0..2).map(|_| F::rand(&mut rng)).collect();
+        let r_y: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+        let point: Vec<F> = r_x.iter().chain(&r_y).cloned().collect();
+        let mle = mat_mult::matrix_mle(&r1cs.a.to_dense());
+        assert_eq!(r1cs.a.evaluate_mle(&r_x, &r_y), mle.evaluate(&point));
+        assert_eq!(r1cs.a.fix_rows(&r_x).evaluate(&r_y), mle.evaluate(&point));
+
+        assert_eq!(r1cs.is_satisfied(&field_vec(&[3, 35]), &field_vec(&[9, 27])), Ok(()));
+        assert_eq!(r1cs.is_satisfied(&field_vec(&[3, 35]), &field_vec(&[9, 26])),
+            Err(Unsatisfied { row: 1, a: F::from(9u64), b: F::from(3u64), c: F::from(26u64) }));
+        let failure = r1cs.is_satisfied(&field_vec(&[3, 34]), &field_vec(&[9, 27])).unwrap_err();
+        assert_eq!(failure.row, 2);
+        assert_eq!(failure.to_string(), "constraint 2 fails: -4 * 1 != -5");
+    }
+
+    #[test]
+    fn test_from_circuit() {
+        let circuit = circuit_parser::parse_circuit::<F>("\
+input x y
+k = const -3
+t = mul x y k
+s = add x y t x
+p = mul s s
+output p s
+").unwrap();
+        let r1cs = R1CS::from_circuit(&circuit);
+        // two constraints for t, one for p and one for each output
+        assert_eq!(r1cs.num_constraints(), 5);
+        assert_eq!((r1cs.num_public, r1cs.num_witness), (4, 3));
+
+        let inputs = field_vec(&[2, 5]);
+        let (public, witness) = circuit_assignment(&circuit, &inputs);
+        let values = circuit.evaluate(&inputs);
+        assert_eq!(public[2..], circuit.output_values(&values));
+        assert_eq!(r1cs.is_satisfied(&public, &witness), Ok(()));
+
+        // a wrong output fails the constraint tying it to its gate
+        let mut wrong = public.clone();
+        wrong[3] += F::from(1u64);
+        assert_eq!(r1cs.is_satisfied(&wrong, &witness).unwrap_err().row, 4);
+
+        // and a wrong partial product fails the constraint defining it
+        let mut wrong = witness.clone();
+        wrong[0] += F::from(1u64);
+        assert_eq!(r1cs.is_satisfied(&public, &wrong).unwrap_err().row, 0);
+    }
+}
@@ -0,0 +1,495 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::DenseMVPolynomial;
+use ark_poly::polynomial::multivariate::SparsePolynomial as MultiPoly;
+use ark_poly::polynomial::multivariate::{Term,SparseTerm};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials;
+use crate::poly_parser::{self,ParseError};
+use crate::sum_check::{self,SumCheckPolynomial,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 4.2
+// #SAT: the number of satisfying assignments of a Boolean formula phi in v
+// variables is the sum over {0,1}^v of its arithmetization g, obtained by
+// replacing
+//   NOT x   -->  1 - x
+//   x AND y -->  x * y
+//   x OR y  -->  x + y - x * y
+// so that g agrees with phi on the hypercube.  The prover claims the count and
+// proves it with the sum-check protocol.  The degree of g in each variable is
+// at most the number of occurrences of that variable in phi, and the verifier
+// evaluates g at a point by evaluating the arithmetized formula gate by gate.
+
+/// Boolean formula over the variables x_0, x_1, ....
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Formula {
+    Const(bool),
+    Var(usize),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    /// Number of variables, i.e. one more than the largest index used.
+    pub fn num_vars(&self) -> usize {
+        match self {
+            Formula::Const(_) => 0,
+            Formula::Var(i) => i + 1,
+            Formula::Not(a) => a.num_vars(),
+            Formula::And(a, b) | Formula::Or(a, b) => a.num_vars().max(b.num_vars()),
+        }
+    }
+
+    pub fn evaluate(&self, assignment: &[bool]) -> bool {
+        match self {
+            Formula::Const(b) => *b,
+            Formula::Var(i) => assignment[*i],
+            Formula::Not(a) => !a.evaluate(assignment),
+            Formula::And(a, b) => a.evaluate(assignment) && b.evaluate(assignment),
+            Formula::Or(a, b) => a.evaluate(assignment) || b.evaluate(assignment),
+        }
+    }
+
+    /// Count the satisfying assignments in {0,1}^num_vars by brute force, or
+    /// return `None` if there are 64 or more variables, so that the count
+    /// may not fit in a u64.
+    pub fn count_satisfying(&self, num_vars: usize) -> Option<u64> {
+        if num_vars >= 64 {
+            return None;
+        }
+        let count = (0..1u64 << num_vars)
+            .filter(|idx| {
+                let assignment: Vec<bool> = (0..num_vars).map(|i| idx & (1 << i) != 0).collect();
+                self.evaluate(&assignment)
+            })
+            .count();
+        Some(count as u64)
+    }
+
+    /// Expand the arithmetization of the formula into a polynomial in
+    /// `num_vars` variables.
+    pub fn arithmetize<F: Field>(&self, num_vars: usize) -> MultiPoly<F, SparseTerm> {
+        let mut poly = self.arithmetize_rec();
+        poly.num_vars = poly.num_vars.max(num_vars);
+        poly
+    }
+
+    fn arithmetize_rec<F: Field>(&self) -> MultiPoly<F, SparseTerm> {
+        let one = || MultiPoly::from_coefficients_vec(0, vec![(F::one(), SparseTerm::new(vec![]))]);
+        match self {
+            Formula::Const(true) => one(),
+            Formula::Const(false) => MultiPoly::from_coefficients_vec(0, vec![]),
+            Formula::Var(i) => MultiPoly::from_coefficients_vec(i + 1, vec![(F::one(), SparseTerm::new(vec![(*i, 1)]))]),
+            Formula::Not(a) => &one() - &a.arithmetize_rec(),
+            Formula::And(a, b) => polynomials::multiply(&a.arithmetize_rec(), &b.arithmetize_rec()),
+            Formula::Or(a, b) => {
+                let (a, b) = (a.arithmetize_rec(), b.arithmetize_rec());
+                &(&a + &b) - &polynomials::multiply(&a, &b)
+            }
+        }
+    }
+
+    /// Evaluate the arithmetization at a point, in time linear in the size
+    /// of the formula.
+    pub fn evaluate_arithmetized<F: Field>(&self, point: &[F]) -> F {
+        match self {
+            Formula::Const(b) => if *b { F::one() } else { F::zero() },
+            Formula::Var(i) => point[*i],
+            Formula::Not(a) => F::one() - a.evaluate_arithmetized(point),
+            Formula::And(a, b) => a.evaluate_arithmetized(point) * b.evaluate_arithmetized(point),
+            Formula::Or(a, b) => {
+                let (a, b) = (a.evaluate_arithmetized(point), b.evaluate_arithmetized(point));
+                a + b - a * b
+            }
+        }
+    }
+
+    /// Number of occurrences of each variable, which bounds the degree of the
+    /// arithmetization in that variable.
+    pub fn occurrences(&self, num_vars: usize) -> Vec<usize> {
+        let mut counts = vec![0; num_vars];
+        self.count_occurrences(&mut counts);
+        counts
+    }
+
+    fn count_occurrences(&self, counts: &mut [usize]) {
+        match self {
+            Formula::Const(_) => {}
+            Formula::Var(i) => counts[*i] += 1,
+            Formula::Not(a) => a.count_occurrences(counts),
+            Formula::And(a, b) | Formula::Or(a, b) => {
+                a.count_occurrences(counts);
+                b.count_occurrences(counts);
+            }
+        }
+    }
+
+    // binding strength, for placing parentheses when displaying
+    fn precedence(&self) -> usize {
+        match self {
+            Formula::Or(_, _) => 0,
+            Formula::And(_, _) => 1,
+            _ => 2,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter, operand: &Formula) -> fmt::Result {
+        if operand.precedence() < self.precedence() {
+            write!(f, "({})", operand)
+        } else {
+            write!(f, "{}", operand)
+        }
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Formula::Const(b) => write!(f, "{}", if *b { 1 } else { 0 }),
+            Formula::Var(i) => write!(f, "x_{}", i),
+            Formula::Not(a) => { write!(f, "!")?; self.fmt_operand(f, a) }
+            Formula::And(a, b) => {
+                self.fmt_operand(f, a)?;
+                write!(f, " & ")?;
+                self.fmt_operand(f, b)
+            }
+            Formula::Or(a, b) => {
+                self.fmt_operand(f, a)?;
+                write!(f, " | ")?;
+                self.fmt_operand(f, b)
+            }
+        }
+    }
+}
+
+/// Parse a Boolean formula such as "(x | !y) & (y | z)", with operators `!`
+/// or `~` (NOT), `&` (AND) and `|` (OR) in decreasing order of precedence,
+/// constants 0 and 1, and variables given by `names` or as x0, x1, ....
+pub fn parse_formula(input: &str, names: &[&str]) -> Result<Formula, ParseError> {
+    let mut parser = FormulaParser { input, pos: 0, names };
+    let formula = parser.or()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(formula),
+        Some(')') => Err(parser.error(String::from("unmatched ')'"))),
+        Some(c) => Err(parser.error(format!("expected operator before '{}'", c))),
+    }
+}
+
+struct FormulaParser<'a> {
+    input: &'a str,
+    pos: usize,
+    names: &'a [&'a str],
+}
+
+impl<'a> FormulaParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError { position: self.pos, message }
+    }
+
+    fn or(&mut self) -> Result<Formula, ParseError> {
+        let mut formula = self.and()?;
+        self.skip_whitespace();
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            formula = Formula::Or(Box::new(formula), Box::new(self.and()?));
+            self.skip_whitespace();
+        }
+        Ok(formula)
+    }
+
+    fn and(&mut self) -> Result<Formula, ParseError> {
+        let mut formula = self.not()?;
+        self.skip_whitespace();
+        while self.peek() == Some('&') {
+            self.pos += 1;
+            formula = Formula::And(Box::new(formula), Box::new(self.not()?));
+            self.skip_whitespace();
+        }
+        Ok(formula)
+    }
+
+    fn not(&mut self) -> Result<Formula, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('!') | Some('~') => {
+                self.pos += 1;
+                Ok(Formula::Not(Box::new(self.not()?)))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let formula = self.or()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(self.error(String::from("expected ')'")));
+                }
+                self.pos += 1;
+                Ok(formula)
+            }
+            Some('0') => { self.pos += 1; Ok(Formula::Const(false)) }
+            Some('1') => { self.pos += 1; Ok(Formula::Const(true)) }
+            Some(c) if c.is_alphabetic() => {
+                let start = self.pos;
+                while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+                    self.pos += c.len_utf8();
+                }
+                let name = &self.input[start..self.pos];
+                poly_parser::variable_index(name, self.names, true)
+                    .map(Formula::Var)
+                    .ok_or(ParseError { position: start, message: format!("unknown variable '{}'", name) })
+            }
+            Some(c) => Err(self.error(format!("expected formula but found '{}'", c))),
+            None => Err(self.error(String::from("expected formula but found end of input"))),
+        }
+    }
+}
+
+/// Parse a CNF formula in DIMACS format, returning the formula and the number
+/// of variables declared in the header.  DIMACS variable i refers to x_{i-1}.
+/// Parsing stops at a line starting with '%', which SATLIB files use to begin
+/// a trailer.
+pub fn parse_dimacs(input: &str) -> Result<(Formula, usize), ParseError> {
+    let mut num_vars = None;
+    let mut clauses: Vec<Formula> = Vec::new();
+    let mut clause: Option<Formula> = None;
+
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        if trimmed.starts_with('%') {
+            break;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('c') {
+            continue;
+        }
+        if trimmed.starts_with('p') {
+            let fields: Vec<&str> = trimmed.split_whitespace().collect();
+            match fields.as_slice() {
+                ["p", "cnf", vars, _] => {
+                    num_vars = vars.parse::<usize>().ok();
+                    if num_vars.is_none() {
+                        return Err(ParseError { position: line_start, message: String::from("invalid number of variables") });
+                    }
+                }
+                _ => return Err(ParseError { position: line_start, message: String::from("expected header 'p cnf VARIABLES CLAUSES'") }),
+            }
+            continue;
+        }
+        let Some(declared) = num_vars else {
+            return Err(ParseError { position: line_start, message: String::from("clause before header 'p cnf VARIABLES CLAUSES'") });
+        };
+        for (word_offset, word) in poly_parser::words(line) {
+            let position = line_start + word_offset;
+            let literal: i64 = word.parse().map_err(|_| ParseError {
+                position,
+                message: format!("invalid literal '{}'", word),
+            })?;
+            if literal == 0 {
+                clauses.push(clause.take().unwrap_or(Formula::Const(false)));
+                continue;
+            }
+            let var = literal.unsigned_abs() as usize;
+            if var > declared {
+                return Err(ParseError { position, message: format!("variable {} exceeds declared number {}", var, declared) });
+            }
+            let lit = if literal > 0 {
+                Formula::Var(var - 1)
+            } else {
+                Formula::Not(Box::new(Formula::Var(var - 1)))
+            };
+            clause = Some(match clause.take() {
+                Some(c) => Formula::Or(Box::new(c), Box::new(lit)),
+                None => lit,
+            });
+        }
+    }
+    if clause.is_some() {
+        return Err(ParseError { position: input.len(), message: String::from("last clause is not terminated by 0") });
+    }
+    let Some(num_vars) = num_vars else {
+        return Err(ParseError { position: 0, message: String::from("missing header 'p cnf VARIABLES CLAUSES'") });
+    };
+    let formula = clauses.into_iter()
+        .reduce(|a, b| Formula::And(Box::new(a), Box::new(b)))
+        .unwrap_or(Formula::Const(true));
+    Ok((formula, num_vars))
+}
+
+/// Arithmetization of a formula as a sum-check polynomial, which is never
+/// expanded: round messages are computed by evaluating the arithmetized
+/// formula gate by gate at every point of {0,1}^j x {0, ..., d}, as for
+/// `BruteForcePolynomial`.  Expanding the product of the clauses of a CNF
+/// formula instead can produce exponentially many monomials.
+#[derive(Clone)]
+pub struct ArithmetizedFormula<F: Field> {
+    pub formula: Arc<Formula>,
+    degrees: Vec<usize>,
+    // challenges r_{j+1}, ..., r_{v-1} bound so far
+    bound: Vec<F>,
+}
+
+impl<F: Field> ArithmetizedFormula<F> {
+    pub fn new(formula: Arc<Formula>, num_vars: usize) -> ArithmetizedFormula<F> {
+        let degrees = formula.occurrences(num_vars);
+        ArithmetizedFormula { formula, degrees, bound: Vec::new() }
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for ArithmetizedFormula<F> {
+    fn num_vars(&self) -> usize {
+        self.degrees.len() - self.bound.len()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        self.degrees[..self.num_vars()].to_vec()
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polynomials::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    fn round_evaluations(&self) -> Vec<F> {
+        let j = self.num_vars() - 1;
+        sum_check::brute_force_evaluations(self.degrees[j], j, &self.bound, |point| self.formula.evaluate_arithmetized(point))
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.bound.insert(0, r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        let full_point = [point, &self.bound].concat();
+        self.formula.evaluate_arithmetized(&full_point)
+    }
+}
+
+pub struct SatProver {
+    pub formula: Arc<Formula>,
+    pub num_vars: usize,
+}
+
+impl<F: PrimeField> IP<Data<F>> for SatProver {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        log.write(String::from("P counts satisfying assignments by brute force"));
+        let count = self.formula.count_satisfying(self.num_vars).expect("too many variables to count");
+        let data = Data::Scalar(F::from(count));
+        log.write(format!("P --> (count = {})", data));
+        ch.send(data);
+
+        log.write(String::from("P evaluates arithmetized formula g gate by gate for each message"));
+        let mut poly = ArithmetizedFormula::new(self.formula.clone(), self.num_vars);
+        sum_check::prove_rounds(&mut poly, MessageEncoding::Coefficients, &ch, &log);
+    }
+}
+
+pub struct SatVerifier {
+    pub formula: Arc<Formula>,
+    pub num_vars: usize,
+}
+
+impl<F: PrimeField> IP<Data<F>> for SatVerifier {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        let count = ch.receive().to_scalar().unwrap();
+
+        log.write(String::from("V bounds degree of g in each variable by its number of occurrences"));
+        let degrees = self.formula.occurrences(self.num_vars);
+        let oracle = |point: &[F]| {
+            log.write(String::from("V evaluates arithmetized formula g(r) gate by gate"));
+            self.formula.evaluate_arithmetized(point)
+        };
+        // failures are recorded in the log
+        let _ = sum_check::verify_with_oracle(count, &degrees, oracle, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::Polynomial;
+    use ark_std::UniformRand;
+    use crate::small_fields::F251 as F;
+    use crate::poly_parser::DEFAULT_VARIABLES;
+
+    #[test]
+    fn test_parse_formula() {
+        let formula = parse_formula("(x | !y) & ~(z & x) | 0", &DEFAULT_VARIABLES).unwrap();
+        assert_eq!(formula.to_string(), "(x_0 | !x_1) & !(x_2 & x_0) | 0");
+        assert_eq!(parse_formula(&formula.to_string(), &[]), Ok(formula));
+
+        let error = parse_formula("x & (y | w)", &DEFAULT_VARIABLES).unwrap_err();
+        assert_eq!(error, ParseError { position: 9, message: String::from("unknown variable 'w'") });
+        let error = parse_formula("x & (y | z", &DEFAULT_VARIABLES).unwrap_err();
+        assert_eq!(error, ParseError { position: 10, message: String::from("expected ')'") });
+    }
+
+    #[test]
+    fn test_parse_dimacs() {
+        let input = "c example\np cnf 3 2\n1 -2 0\n2 3 0\n";
+        let (formula, num_vars) = parse_dimacs(input).unwrap();
+        assert_eq!(num_vars, 3);
+        assert_eq!(formula, parse_formula("(x0 | !x1) & (x1 | x2)", &[]).unwrap());
+
+        // SATLIB trailer
+        let (formula, _) = parse_dimacs("p cnf 2 1\n1 2 0\n%\n0\n\n").unwrap();
+        assert_eq!(formula, parse_formula("x0 | x1", &[]).unwrap());
+
+        let error = parse_dimacs("p cnf 2 1\n1 -3 0\n").unwrap_err();
+        assert_eq!(error, ParseError { position: 12, message: String::from("variable 3 exceeds declared number 2") });
+    }
+
+    #[test]
+    fn test_arithmetization() {
+        let formula = parse_formula("(x | y | !z) & (!x | z) & (y | z)", &DEFAULT_VARIABLES).unwrap();
+        let poly = formula.arithmetize::<F>(3);
+
+        // arithmetization agrees with the formula on the hypercube
+        for idx in 0..8 {
+            let assignment: Vec<bool> = (0..3).map(|i| idx & (1 << i) != 0).collect();
+            let point: Vec<F> = assignment.iter().map(|b| F::from(*b)).collect();
+            assert_eq!(poly.evaluate(&point), F::from(formula.evaluate(&assignment)));
+        }
+        assert_eq!(poly.hypercube_sum(), F::from(formula.count_satisfying(3).unwrap()));
+        assert_eq!(formula.count_satisfying(64), None);
+
+        let point: Vec<F> = (0..3).map(|_| F::rand(&mut rand::thread_rng())).collect();
+        assert_eq!(formula.evaluate_arithmetized(&point), poly.evaluate(&point));
+
+        let degrees = polynomials::variable_degrees(&poly);
+        let bounds = formula.occurrences(3);
+        assert!(degrees.iter().zip(&bounds).all(|(d, b)| d <= b));
+    }
+
+    #[test]
+    fn test_arithmetized_formula() {
+        // round messages agree with those of the expanded arithmetization
+        let formula = parse_formula("(x | y | !z) & (!x | z) & (y | z | x)", &DEFAULT_VARIABLES).unwrap();
+        let mut expanded = formula.arithmetize::<F>(3);
+        let mut lazy = ArithmetizedFormula::<F>::new(Arc::new(formula), 3);
+        assert_eq!(lazy.hypercube_sum(), expanded.hypercube_sum());
+        for _ in 0..3 {
+            assert_eq!(lazy.round_polynomial(), expanded.round_polynomial());
+            let r = F::rand(&mut rand::thread_rng());
+            lazy.fix_last_variable(r);
+            expanded.fix_last_variable(r);
+        }
+        assert_eq!(lazy.oracle_evaluation(&[]), expanded.oracle_evaluation(&[]));
+    }
+}
@@ -1,12 +1,16 @@
-use ark_ff::fields::{Field};
+use ark_ff::fields::{Field,PrimeField};
+use ark_ff::{BitIteratorBE,One,Zero};
 
-use crate::small_fields::{F13, F17};
+use crate::small_fields::{F13, F17, F67, F4489};
 
 use ark_ec::{
     models::CurveConfig,
     short_weierstrass::{self, *},
+    pairing::{Pairing, MillerLoopOutput, PairingOutput},
+    AffineRepr, CurveGroup,
 };
 use ark_ff::MontFp;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
 
 
 // C17: y^2 = x^3 + 2x + 4 over F13, with 17 elements
@@ -53,3 +57,189 @@ pub const G1_GENERATOR_X: F13 = MontFp!("7");
 /// G1_GENERATOR_Y = 7
 #[rustfmt::skip]
 pub const G1_GENERATOR_Y: F13 = MontFp!("7");
+
+
+// SS67: y^2 = x^3 + x over F67, a supersingular curve with 68 = 4 * 17 elements
+//
+// Since 67 = 3 mod 4, the curve has embedding degree 2: the subgroup of order
+// 17 embeds into the multiplicative group of F4489 = F67[i]/(i^2 + 1) via the
+// reduced Tate pairing.  The distortion map (x, y) -> (-x, i*y) sends points of
+// the order 17 subgroup outside of it, so the pairing e(P, Q) = t(P, phi(Q))
+// is non-degenerate on the subgroup itself, and we use it for both G1 and G2.
+
+pub type SS67Affine = Affine<SS67Config>;
+pub type SS67Projective = Projective<SS67Config>;
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SS67Config;
+
+impl CurveConfig for SS67Config {
+    type BaseField = F67;
+    type ScalarField = F17;
+
+    /// COFACTOR = 4
+    const COFACTOR: &'static [u64] = &[0x0000000000000004];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r = 13
+    #[rustfmt::skip]
+    const COFACTOR_INV: F17 = MontFp!("13");
+}
+
+impl short_weierstrass::SWCurveConfig for SS67Config {
+    /// COEFF_A = 1
+    const COEFF_A: F67 = MontFp!("1");
+
+    /// COEFF_B = 0
+    #[rustfmt::skip]
+    const COEFF_B: F67 = MontFp!("0");
+
+    /// AFFINE_GENERATOR_COEFFS = (SS67_GENERATOR_X, SS67_GENERATOR_Y)
+    const GENERATOR: SS67Affine = SS67Affine::new_unchecked(SS67_GENERATOR_X, SS67_GENERATOR_Y);
+
+    #[inline(always)]
+    fn mul_by_a(elem: Self::BaseField) -> Self::BaseField {
+        elem
+    }
+}
+
+/// SS67_GENERATOR_X = 9
+#[rustfmt::skip]
+pub const SS67_GENERATOR_X: F67 = MontFp!("9");
+
+/// SS67_GENERATOR_Y = 1
+#[rustfmt::skip]
+pub const SS67_GENERATOR_Y: F67 = MontFp!("1");
+
+/// Exponent (67^2 - 1)/17 of the final exponentiation of the reduced Tate pairing
+const SS67_FINAL_EXPONENT: u64 = (67 * 67 - 1) / 17;
+
+/// Symmetric pairing on the order 17 subgroup of SS67, with values in the
+/// order 17 subgroup of the multiplicative group of F4489.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SS67Pairing;
+
+/// Pairing input, which requires no preprocessing beyond conversion to affine
+/// coordinates.
+#[derive(Clone, Debug, Default, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SS67Prepared(pub SS67Affine);
+
+impl From<SS67Affine> for SS67Prepared {
+    fn from(p: SS67Affine) -> Self { SS67Prepared(p) }
+}
+
+impl From<&SS67Affine> for SS67Prepared {
+    fn from(p: &SS67Affine) -> Self { SS67Prepared(*p) }
+}
+
+impl From<SS67Projective> for SS67Prepared {
+    fn from(p: SS67Projective) -> Self { SS67Prepared(p.into_affine()) }
+}
+
+impl From<&SS67Projective> for SS67Prepared {
+    fn from(p: &SS67Projective) -> Self { SS67Prepared(p.into_affine()) }
+}
+
+impl Pairing for SS67Pairing {
+    type BaseField = F67;
+    type ScalarField = F17;
+    type G1 = SS67Projective;
+    type G1Affine = SS67Affine;
+    type G1Prepared = SS67Prepared;
+    type G2 = SS67Projective;
+    type G2Affine = SS67Affine;
+    type G2Prepared = SS67Prepared;
+    type TargetField = F4489;
+
+    fn multi_miller_loop(
+        a: impl IntoIterator<Item = impl Into<Self::G1Prepared>>,
+        b: impl IntoIterator<Item = impl Into<Self::G2Prepared>>,
+    ) -> MillerLoopOutput<Self> {
+        let product = a.into_iter()
+            .zip(b)
+            .map(|(p, q)| ss67_miller_loop(&p.into().0, &q.into().0))
+            .product();
+        MillerLoopOutput(product)
+    }
+
+    fn final_exponentiation(mlo: MillerLoopOutput<Self>) -> Option<PairingOutput<Self>> {
+        Some(PairingOutput(mlo.0.pow([SS67_FINAL_EXPONENT])))
+    }
+}
+
+/// Compute Miller's function f_{17,P} evaluated at the distorted point phi(Q).
+/// Vertical lines take values in F67 at phi(Q), so they are omitted since they
+/// are sent to 1 by the final exponentiation.
+fn ss67_miller_loop(p: &SS67Affine, q: &SS67Affine) -> F4489 {
+    if p.is_zero() || q.is_zero() {
+        return F4489::one();
+    }
+    // phi(Q) = (-x, i*y)
+    let (x_q, y_q) = (-q.x, F4489::new(F67::zero(), q.y));
+
+    // evaluate at phi(Q) the line through (x, y) with the given slope
+    let line = |x: F67, y: F67, slope: F67| {
+        y_q - F4489::new(y + slope * (x_q - x), F67::zero())
+    };
+
+    let mut f = F4489::one();
+    let mut t = *p;
+    for bit in BitIteratorBE::without_leading_zeros(F17::MODULUS).skip(1) {
+        // doubling step, using the tangent line at T
+        f.square_in_place();
+        if !t.is_zero() {
+            if t.y.is_zero() {
+                t = SS67Affine::zero();
+            } else {
+                let slope = (t.x.square() * F67::from(3u64) + F67::one()) / t.y.double();
+                f *= line(t.x, t.y, slope);
+                t = (t + t).into_affine();
+            }
+        }
+        // addition step, using the chord through T and P
+        if bit {
+            if t.is_zero() {
+                t = *p;
+            } else if t.x != p.x {
+                let slope = (p.y - t.y) / (p.x - t.x);
+                f *= line(t.x, t.y, slope);
+                t = (t + p).into_affine();
+            } else {
+                t = (t + p).into_affine();
+            }
+        }
+    }
+    f
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::Group;
+
+    #[test]
+    fn test_ss67_generator() {
+        let g = SS67Projective::generator();
+        assert!(g.into_affine().is_on_curve());
+        assert!(!g.is_zero());
+        assert!((g * F17::from(17u64)).is_zero());
+    }
+
+    #[test]
+    fn test_ss67_bilinearity() {
+        let g = SS67Projective::generator();
+        let e_gg = SS67Pairing::pairing(g, g);
+
+        // non-degenerate, with values of order 17
+        assert_ne!(e_gg.0, F4489::one());
+        assert_eq!(e_gg.0.pow([17u64]), F4489::one());
+
+        // e(g^a, g^b) = e(g, g)^(ab) for every pair of scalars
+        for a in 0..17u64 {
+            for b in 0..17u64 {
+                let lhs = SS67Pairing::pairing(g * F17::from(a), g * F17::from(b));
+                assert_eq!(lhs.0, e_gg.0.pow([a * b]), "e(g^{a}, g^{b}) != e(g, g)^{}", a * b);
+            }
+        }
+    }
+}
@@ -1,5 +1,9 @@
+// `MontConfig` derives from ark-ff 0.4 emit their impls inside a const block
+#![allow(non_local_definitions)]
+
 use ark_ff::biginteger::BigInteger64;
-use ark_ff::fields::{Fp64,MontConfig,MontBackend,FpConfig};
+use ark_ff::{MontFp,PrimeField};
+use ark_ff::fields::{Fp64,MontConfig,MontBackend,FpConfig,Fp2,Fp2Config};
 
 #[derive(MontConfig)]
 #[modulus="5"]
@@ -19,12 +23,52 @@ pub type F13 = Fp64<MontBackend<F13Config, 1>>;
 pub struct F17Config {}
 pub type F17 = Fp64<MontBackend<F17Config, 1>>;
 
+#[derive(MontConfig)]
+#[modulus="67"]
+#[generator="2"]
+pub struct F67Config {}
+pub type F67 = Fp64<MontBackend<F67Config, 1>>;
+
 #[derive(MontConfig)]
 #[modulus="251"]
 #[generator="6"]
 pub struct F251Config {}
 pub type F251 = Fp64<MontBackend<F251Config, 1>>;
 
+// F257 has multiplicative group of order 256 = 2^8, so it contains the
+// evaluation domains of size up to 256 used by FFTs and FRI
+
+#[derive(MontConfig)]
+#[modulus="257"]
+#[generator="3"]
+pub struct F257Config {}
+pub type F257 = Fp64<MontBackend<F257Config, 1>>;
+
+// F4489 = F67[i]/(i^2 + 1), the quadratic extension of F67; since 67 = 3 mod 4,
+// -1 is a quadratic non-residue in F67
+
+pub struct F4489Config;
+pub type F4489 = Fp2<F4489Config>;
+
+impl Fp2Config for F4489Config {
+    type Fp = F67;
+
+    /// NONRESIDUE = -1
+    const NONRESIDUE: F67 = MontFp!("-1");
+
+    /// Coefficients for the Frobenius automorphism, (-1)^((67^k - 1)/2)
+    const FROBENIUS_COEFF_FP2_C1: &'static [F67] = &[
+        MontFp!("1"),
+        MontFp!("-1"),
+    ];
+}
+
+/// Size of a prime field whose modulus fits in 64 bits.
+pub fn field_size<F: PrimeField>() -> u64 {
+    assert!(F::MODULUS_BIT_SIZE <= 64, "modulus does not fit in 64 bits");
+    F::MODULUS.as_ref()[0]
+}
+
 pub fn to_u64<T: FpConfig<1>> (n: Fp64<T>) -> u64 {
     bigint64_value(T::into_bigint(n))
 }
@@ -0,0 +1,369 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials;
+use crate::r1cs::R1CS;
+use crate::multilinear::{self,DenseMLE,ProductMLE};
+use crate::sum_check::{self,SumCheckPolynomial,SumCheckFailure,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Chapter 8, following Spartan (Setty, 2020)
+// An argument that z = (1, x, w) satisfies an R1CS with m x n matrices A, B
+// and C, in which the verifier only needs a single evaluation of the
+// multilinear extension of the witness.  Padding to 2^s rows and 2^t columns,
+// the constraints say that the function
+//   F(x) = Az~(x) * Bz~(x) - Cz~(x),  with Mz~(x) = sum_y M~(x, y) z~(y),
+// vanishes on {0,1}^s.  F is not multilinear, so to test this the verifier
+// picks a random tau and checks that the multilinear extension of F on the
+// hypercube vanishes at tau, by sum-check applied to
+//   g(x) = eq(tau, x) * (Az~(x) * Bz~(x) - Cz~(x)),
+// which has degree 3 in each variable and sum 0.  At the end the verifier
+// needs Az~(r_x), Bz~(r_x) and Cz~(r_x) at the random point r_x, which the
+// prover claims as v_A, v_B and v_C.  The verifier checks these claims all at
+// once by picking random rho_A, rho_B, rho_C and running a second sum-check
+// for
+//   rho_A v_A + rho_B v_B + rho_C v_C
+//     = sum_y (rho_A A~(r_x, y) + rho_B B~(r_x, y) + rho_C C~(r_x, y)) z~(y),
+// which ends with a random point r_y.  The verifier evaluates the sparse
+// matrices A~, B~, C~ at (r_x, r_y) itself, and z~(r_y) as the sum of the
+// extension of (1, x, 0) and that of the witness W~, obtained by one oracle
+// query.  In a succinct argument the oracle is replaced by a polynomial
+// commitment to W~.
+//
+// As in GKR the verifier sends the last challenge of the first sum-check,
+// since the prover needs all of r_x to continue.
+
+/// The polynomial g(x) = eq(tau, x) (Az~(x) Bz~(x) - Cz~(x)) of the first
+/// sum-check, stored as the tables of its four multilinear factors.  It has
+/// degree 3 in each variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintPolynomial<F: Field> {
+    pub eq: DenseMLE<F>,
+    pub az: DenseMLE<F>,
+    pub bz: DenseMLE<F>,
+    pub cz: DenseMLE<F>,
+}
+
+impl<F: Field> ConstraintPolynomial<F> {
+    pub fn new(r1cs: &R1CS<F>, z: &[F], tau: &[F]) -> ConstraintPolynomial<F> {
+        let s = r1cs.a.row_bits();
+        let table = |values: Vec<F>| {
+            let mut evals = values;
+            evals.resize(1 << s, F::zero());
+            DenseMLE::new(s, evals)
+        };
+        ConstraintPolynomial {
+            eq: multilinear::eq_table(tau),
+            az: table(r1cs.a.mul_vector(z)),
+            bz: table(r1cs.b.mul_vector(z)),
+            cz: table(r1cs.c.mul_vector(z)),
+        }
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for ConstraintPolynomial<F> {
+    fn num_vars(&self) -> usize {
+        self.eq.num_vars
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![3; self.num_vars()]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polynomials::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    // as for ProductMLE, each factor is extended linearly in the last variable
+    // from the two halves of its table
+    fn round_evaluations(&self) -> Vec<F> {
+        let half = self.eq.evals.len() / 2;
+        let mut evals = vec![F::zero(); 4];
+        for idx in 0..half {
+            let tables = [&self.eq, &self.az, &self.bz, &self.cz];
+            let mut values = tables.map(|f| f.evals[idx]);
+            let diffs = tables.map(|f| f.evals[idx + half] - f.evals[idx]);
+            for eval in evals.iter_mut() {
+                *eval += values[0] * (values[1] * values[2] - values[3]);
+                for (value, diff) in values.iter_mut().zip(&diffs) {
+                    *value += diff;
+                }
+            }
+        }
+        evals
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        for f in [&mut self.eq, &mut self.az, &mut self.bz, &mut self.cz] {
+            f.fix_last_variable(r);
+        }
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.eq.evaluate(point) * (self.az.evaluate(point) * self.bz.evaluate(point) - self.cz.evaluate(point))
+    }
+}
+
+/// Multilinear extension W~ of the witness, as the table of z = (1, x, w)
+/// padded to 2^t entries with the constant and public inputs set to zero.
+pub fn witness_mle<F: Field>(r1cs: &R1CS<F>, witness: &[F]) -> DenseMLE<F> {
+    let mut evals = vec![F::zero(); 1 << r1cs.a.col_bits()];
+    evals[1 + r1cs.num_public..r1cs.num_variables()].copy_from_slice(witness);
+    DenseMLE::new(r1cs.a.col_bits(), evals)
+}
+
+/// Multilinear extension of (1, x, 0, ..., 0) at `r_y`, computed from the
+/// public inputs in time linear in their number.
+pub fn public_evaluation<F: Field>(public: &[F], r_y: &[F]) -> F {
+    std::iter::once(F::one())
+        .chain(public.iter().cloned())
+        .enumerate()
+        .map(|(j, value)| value * multilinear::eq_eval(&multilinear::hypercube_point(r_y.len(), j), r_y))
+        .sum()
+}
+
+/// Check of the Spartan verifier which failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpartanFailure<F> {
+    /// A round of the sum-check over the constraints failed.
+    Constraints(SumCheckFailure<F>),
+    /// The final check value of that sum-check differs from
+    /// eq(tau, r_x) (v_A v_B - v_C) for the claimed v_A, v_B, v_C.
+    Claims { evaluation: F, check_value: F },
+    /// A round of the sum-check for the matrix-vector products failed.
+    Products(SumCheckFailure<F>),
+    /// The final check value of that sum-check differs from the evaluation
+    /// computed with the matrices and the witness oracle.
+    Witness { evaluation: F, check_value: F },
+}
+
+impl<F: PrimeField> fmt::Display for SpartanFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpartanFailure::Constraints(failure) => write!(f, "sum-check over the constraints: {}", failure),
+            SpartanFailure::Claims { evaluation, check_value } => {
+                write!(f, "eq(tau, r_x) (v_A v_B - v_C) = {} but final check value is {}",
+                    evaluation.into_bigint(), check_value.into_bigint())
+            }
+            SpartanFailure::Products(failure) => write!(f, "sum-check for the matrix-vector products: {}", failure),
+            SpartanFailure::Witness { evaluation, check_value } => {
+                write!(f, "M~(r_x, r_y) z~(r_y) = {} but final check value is {}",
+                    evaluation.into_bigint(), check_value.into_bigint())
+            }
+        }
+    }
+}
+
+fn reject<F: PrimeField>(ch: &Channel<Data<F>>, log: &Log, failure: SpartanFailure<F>) -> SpartanFailure<F> {
+    log.write(format!("V check fails: {}", failure));
+    let decision = Data::Decision(false);
+    log.write(format!("V --> ({})", decision));
+    ch.send(decision);
+    failure
+}
+
+// point (r_0, ..., r_{v-1}) from the challenges r_{v-1}, ..., r_1 received
+// during sum-check and the last challenge r_0
+fn sum_check_point<F: Field>(r_0: F, challenges: Vec<F>) -> Vec<F> {
+    std::iter::once(r_0).chain(challenges.into_iter().rev()).collect()
+}
+
+/// Prover side of the argument that (1, public, witness) satisfies the R1CS.
+pub fn prove<F: PrimeField>(r1cs: &R1CS<F>, public: &[F], witness: &[F], ch: &Channel<Data<F>>, log: &Log) {
+    let z = r1cs.z_vector(public, witness);
+    let Some(tau) = ch.receive().to_evaluations() else { return };
+
+    log.write(String::from("P runs sum-check over the constraints"));
+    let mut poly = ConstraintPolynomial::new(r1cs, &z, &tau);
+    let Some(challenges) = sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, ch, log) else { return };
+    let Some(r_0) = ch.receive().to_scalar() else { return };
+    let r_x = sum_check_point(r_0, challenges);
+
+    poly.fix_last_variable(r_0);
+    let data = Data::Evaluations(vec![poly.az.evals[0], poly.bz.evals[0], poly.cz.evals[0]]);
+    log.write(format!("P --> ((v_A, v_B, v_C) = {})", data));
+    ch.send(data);
+
+    let Some(rho) = ch.receive().to_evaluations() else { return };
+    log.write(String::from("P runs sum-check for the matrix-vector products"));
+    let mut rows = [&r1cs.a, &r1cs.b, &r1cs.c]
+        .map(|matrix| matrix.fix_rows(&r_x));
+    for (row, rho) in rows.iter_mut().zip(&rho) {
+        row.evals.iter_mut().for_each(|e| *e *= rho);
+    }
+    let combined = (0..1 << r1cs.a.col_bits()).map(|j| rows.iter().map(|row| row.evals[j]).sum()).collect();
+    let mut z_table = z;
+    z_table.resize(1 << r1cs.a.col_bits(), F::zero());
+    let mut poly = ProductMLE::new(vec![
+        DenseMLE::new(r1cs.a.col_bits(), combined),
+        DenseMLE::new(r1cs.a.col_bits(), z_table),
+    ]);
+    if sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, ch, log).is_none() { return };
+    ch.receive();
+}
+
+/// Verifier side of the argument that the R1CS is satisfied with the given
+/// public inputs, with `witness_oracle` evaluating the witness extension W~.
+pub fn verify<F: PrimeField>(
+        r1cs: &R1CS<F>,
+        public: &[F],
+        witness_oracle: impl FnOnce(&[F]) -> F,
+        ch: &Channel<Data<F>>,
+        log: &Log) -> Result<(), SpartanFailure<F>> {
+    let mut rng = rand::thread_rng();
+    let (s, t) = (r1cs.a.row_bits(), r1cs.a.col_bits());
+
+    log.write(String::from("V picks tau uniformly at random"));
+    let tau: Vec<F> = (0..s).map(|_| F::rand(&mut rng)).collect();
+    log.write(format!("V --> (tau = {})", polynomials::format_point(&tau)));
+    ch.send(Data::Evaluations(tau.clone()));
+
+    log.write(String::from("V runs sum-check over the constraints with claimed sum 0"));
+    let (r_x, check_value) = sum_check::verify_rounds(F::zero(), &vec![3; s], ch, log)
+        .map_err(SpartanFailure::Constraints)?;
+    let data = Data::Scalar(r_x[0]);
+    log.write(format!("V --> (r_0 = {}) so that P can evaluate at r_x", data));
+    ch.send(data);
+
+    let claims = ch.receive().to_evaluations().unwrap();
+    let (v_a, v_b, v_c) = (claims[0], claims[1], claims[2]);
+    log.write(String::from("V checks final check value equals eq(tau, r_x) (v_A v_B - v_C)"));
+    let evaluation = multilinear::eq_eval(&tau, &r_x) * (v_a * v_b - v_c);
+    if evaluation != check_value {
+        return Err(reject(ch, log, SpartanFailure::Claims { evaluation, check_value }));
+    }
+
+    log.write(String::from("V picks rho_A, rho_B, rho_C uniformly at random"));
+    let rho: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+    log.write(format!("V --> (rho = {})", polynomials::format_point(&rho)));
+    ch.send(Data::Evaluations(rho.clone()));
+
+    let claim = rho[0] * v_a + rho[1] * v_b + rho[2] * v_c;
+    log.write(String::from("V runs sum-check for the matrix-vector products with claimed sum rho_A v_A + rho_B v_B + rho_C v_C"));
+    let (r_y, check_value) = sum_check::verify_rounds(claim, &vec![2; t], ch, log)
+        .map_err(SpartanFailure::Products)?;
+
+    log.write(String::from("V evaluates A~, B~, C~ at (r_x, r_y) from the nonzero entries"));
+    let matrix = [&r1cs.a, &r1cs.b, &r1cs.c].iter()
+        .zip(&rho)
+        .map(|(matrix, rho)| *rho * matrix.evaluate_mle(&r_x, &r_y))
+        .sum::<F>();
+    log.write(String::from("V evaluates z~(r_y) from the public inputs and a single query to W~"));
+    let z = public_evaluation(public, &r_y) + witness_oracle(&r_y);
+    let evaluation = matrix * z;
+    log.write(String::from("V checks final check value equals M~(r_x, r_y) z~(r_y)"));
+    if evaluation != check_value {
+        return Err(reject(ch, log, SpartanFailure::Witness { evaluation, check_value }));
+    }
+
+    let decision = Data::Decision(true);
+    log.write(format!("V --> ({})", decision));
+    ch.send(decision);
+    Ok(())
+}
+
+pub struct SpartanProver<F: Field> {
+    pub r1cs: Arc<R1CS<F>>,
+    pub public: Arc<Vec<F>>,
+    pub witness: Arc<Vec<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for SpartanProver<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        prove(&self.r1cs, &self.public, &self.witness, &ch, &log);
+    }
+}
+
+/// Verifier with oracle access to the extension of the witness.
+pub struct SpartanVerifier<F: Field> {
+    pub r1cs: Arc<R1CS<F>>,
+    pub public: Arc<Vec<F>>,
+    pub witness: Arc<DenseMLE<F>>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for SpartanVerifier<F> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        let oracle = |point: &[F]| self.witness.evaluate(point);
+        // failures are recorded in the log
+        let _ = verify(&self.r1cs, &self.public, oracle, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use ark_test_curves::bls12_381::Fr;
+    use crate::circuit_parser;
+    use crate::r1cs;
+    use crate::small_fields::F13;
+    use crate::test_utils;
+
+    const CUBIC: &str = "\
+input x y
+k = const -3
+t = mul x y k
+s = add x y t x
+p = mul s s
+output p s
+";
+
+    fn run<F: PrimeField>(r1cs: R1CS<F>, public: Vec<F>, prover_witness: Vec<F>, witness: Vec<F>) -> Result<(), SpartanFailure<F>> {
+        let r1cs = Arc::new(r1cs);
+        let public = Arc::new(public);
+        let oracle = witness_mle(&r1cs, &witness);
+        let prover = SpartanProver { r1cs: r1cs.clone(), public: public.clone(), witness: Arc::new(prover_witness) };
+        test_utils::run_against(prover, |ch, log| verify(&r1cs, &public, |point| oracle.evaluate(point), ch, log))
+    }
+
+    #[test]
+    fn test_constraint_polynomial() {
+        let circuit = circuit_parser::parse_circuit::<F13>(CUBIC).unwrap();
+        let r1cs = R1CS::from_circuit(&circuit);
+        let (public, mut witness) = r1cs::circuit_assignment(&circuit, &[F13::from(2u64), F13::from(5u64)]);
+        let tau: Vec<F13> = (0..r1cs.a.row_bits()).map(|_| F13::rand(&mut rand::thread_rng())).collect();
+        let poly = ConstraintPolynomial::new(&r1cs, &r1cs.z_vector(&public, &witness), &tau);
+        assert_eq!(poly.hypercube_sum(), F13::from(0u64));
+
+        // the sum is the extension of the constraint errors at tau
+        witness[1] += F13::from(1u64);
+        let z = r1cs.z_vector(&public, &witness);
+        let poly = ConstraintPolynomial::new(&r1cs, &z, &tau);
+        let (az, bz, cz) = (poly.az.clone(), poly.bz.clone(), poly.cz.clone());
+        let errors = az.evals.iter().zip(&bz.evals).zip(&cz.evals).map(|((a, b), c)| *a * b - c).collect();
+        assert_eq!(poly.hypercube_sum(), DenseMLE::new(r1cs.a.row_bits(), errors).evaluate(&tau));
+
+        // and z~ is split into its public and witness parts
+        let r_y: Vec<F13> = (0..r1cs.a.col_bits()).map(|_| F13::rand(&mut rand::thread_rng())).collect();
+        let mut z_table = z;
+        z_table.resize(1 << r1cs.a.col_bits(), F13::from(0u64));
+        assert_eq!(public_evaluation(&public, &r_y) + witness_mle(&r1cs, &witness).evaluate(&r_y),
+            DenseMLE::new(r1cs.a.col_bits(), z_table).evaluate(&r_y));
+    }
+
+    #[test]
+    fn test_spartan() {
+        let circuit = circuit_parser::parse_circuit::<F13>(CUBIC).unwrap();
+        let r1cs = R1CS::from_circuit(&circuit);
+        let (public, witness) = r1cs::circuit_assignment(&circuit, &[F13::from(2u64), F13::from(5u64)]);
+        assert_eq!(run(r1cs, public, witness.clone(), witness), Ok(()));
+
+        // an unsatisfying witness is caught in the first sum-check
+        let circuit = circuit_parser::parse_circuit::<Fr>(CUBIC).unwrap();
+        let r1cs = R1CS::from_circuit(&circuit);
+        let (public, mut witness) = r1cs::circuit_assignment(&circuit, &[Fr::from(2u64), Fr::from(5u64)]);
+        witness[0] += Fr::from(1u64);
+        let result = run(r1cs.clone(), public.clone(), witness.clone(), witness.clone());
+        assert!(matches!(result, Err(SpartanFailure::Constraints(SumCheckFailure::Sum { .. }))));
+
+        // and a prover using a satisfying witness other than the one in the
+        // oracle is caught at the end
+        let (_, satisfying) = r1cs::circuit_assignment(&circuit, &[Fr::from(2u64), Fr::from(5u64)]);
+        let result = run(r1cs, public, satisfying, witness);
+        assert!(matches!(result, Err(SpartanFailure::Witness { .. })));
+    }
+}
@@ -0,0 +1,733 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::{Polynomial,DenseUVPolynomial};
+use ark_poly::polynomial::multivariate::{SparsePolynomial as MultiPoly, SparseTerm};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials as polys;
+use crate::multilinear::{DenseMLE,ProductMLE};
+use crate::group_utils;
+use crate::small_fields;
+use crate::ip::{IP,Channel,Log,Embeds};
+
+// PAZK, Section 4.1
+// The sum-check protocol, for proving that the sum of a v-variate polynomial g
+// over the hypercube {0,1}^v equals a claimed value.  In each round the prover
+// sends the univariate polynomial obtained by summing out all but one of the
+// remaining variables, and the verifier binds that variable to a random
+// challenge.  At the end the verifier checks a single evaluation of g.
+//
+// Variables are bound from the last, x_{v-1}, down to x_0, and the message in
+// the round binding x_j is called g_j.
+
+/// Polynomial which a sum-check prover knows how to handle, and for which the
+/// verifier has oracle access to evaluations.
+pub trait SumCheckPolynomial<F: Field>: Clone + Send + Sync {
+    fn num_vars(&self) -> usize;
+
+    /// Degree of the polynomial in each variable separately.
+    fn variable_degrees(&self) -> Vec<usize>;
+
+    /// Univariate polynomial in the last variable x_{v-1}, obtained by summing
+    /// over {0,1} in each of the other variables.
+    fn round_polynomial(&self) -> UniPoly<F>;
+
+    /// Bind the last variable x_{v-1} to `r`, leaving a polynomial in the
+    /// variables x_0, ..., x_{v-2}.
+    fn fix_last_variable(&mut self, r: F);
+
+    /// Evaluations g(0), ..., g(d) of the round polynomial g, where d is the
+    /// degree in the last variable.
+    fn round_evaluations(&self) -> Vec<F> {
+        let d = self.variable_degrees()[self.num_vars() - 1];
+        let g = self.round_polynomial();
+        (0..=d as u64).map(|t| g.evaluate(&F::from(t))).collect()
+    }
+
+    /// Evaluation at a point of F^v, as provided to the verifier by an oracle.
+    fn oracle_evaluation(&self, point: &[F]) -> F;
+
+    /// Readable form of the polynomial for the log, if there is one.
+    fn format(&self) -> Option<String> {
+        None
+    }
+
+    /// Sum of the evaluations over the hypercube.
+    fn hypercube_sum(&self) -> F {
+        if self.num_vars() == 0 {
+            self.oracle_evaluation(&[])
+        } else {
+            let g = self.round_polynomial();
+            g.evaluate(&F::zero()) + g.evaluate(&F::one())
+        }
+    }
+}
+
+impl<F: PrimeField> SumCheckPolynomial<F> for MultiPoly<F, SparseTerm> {
+    fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        polys::variable_degrees(self)
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        let j = self.num_vars - 1;
+        let vals: Vec<Option<Vec<F>>> = (0..self.num_vars)
+            .map(|n| if n < j { Some(vec![F::zero(), F::one()]) } else { None })
+            .collect();
+        let partial = polys::partial_summation(self, &vals);
+        let uni = polys::into_univariate(&partial, j);
+        // strip leading zero coefficients
+        UniPoly::from_coefficients_vec(uni.coeffs)
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        let j = self.num_vars - 1;
+        *self = polys::partial_eval(self, r, j);
+        // x_j no longer appears in any term
+        self.num_vars = j;
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        Polynomial::evaluate(self, &point.to_vec())
+    }
+
+    fn format(&self) -> Option<String> {
+        Some(polys::format_multi_poly(self, &[]))
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for DenseMLE<F> {
+    fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![1; self.num_vars]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        // g(X) = (1 - X) * sum_x f(x, 0) + X * sum_x f(x, 1)
+        let (lo, hi) = self.evals.split_at(self.evals.len() / 2);
+        let sum_lo: F = lo.iter().sum();
+        let sum_hi: F = hi.iter().sum();
+        UniPoly::from_coefficients_vec(vec![sum_lo, sum_hi - sum_lo])
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        DenseMLE::fix_last_variable(self, r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.evaluate(point)
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for ProductMLE<F> {
+    fn num_vars(&self) -> usize {
+        ProductMLE::num_vars(self)
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![self.factors.len(); self.num_vars()]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polys::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    // PAZK, Section 4.6.3
+    // Writing f_i(x, X) = f_i(x, 0) + X * (f_i(x, 1) - f_i(x, 0)) for each
+    // factor, g(t) = sum_x prod_i f_i(x, t) is computed for t = 0, ..., d
+    // directly from the two halves of each table, in O(d^2 2^v) operations.
+    fn round_evaluations(&self) -> Vec<F> {
+        let d = self.factors.len();
+        let half = self.factors[0].evals.len() / 2;
+        let mut evals = vec![F::zero(); d + 1];
+        let mut values = vec![F::zero(); d];
+        let mut diffs = vec![F::zero(); d];
+        for idx in 0..half {
+            for (i, f) in self.factors.iter().enumerate() {
+                values[i] = f.evals[idx];
+                diffs[i] = f.evals[idx + half] - f.evals[idx];
+            }
+            for eval in evals.iter_mut() {
+                *eval += values.iter().product::<F>();
+                for (value, diff) in values.iter_mut().zip(&diffs) {
+                    *value += diff;
+                }
+            }
+        }
+        evals
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        ProductMLE::fix_last_variable(self, r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.evaluate(point)
+    }
+}
+
+/// Sum-check polynomial whose round messages are computed by brute force,
+/// evaluating g at every point of {0,1}^j x {0, ..., d} x {r_{j+1}, ...,
+/// r_{v-1}} and interpolating, with no symbolic manipulation.  This is only
+/// practical for few variables, but shows exactly what the prover computes.
+#[derive(Clone)]
+pub struct BruteForcePolynomial<F: Field> {
+    pub polynomial: MultiPoly<F, SparseTerm>,
+    degrees: Vec<usize>,
+    // challenges r_{j+1}, ..., r_{v-1} bound so far
+    bound: Vec<F>,
+}
+
+impl<F: Field> BruteForcePolynomial<F> {
+    pub fn new(polynomial: MultiPoly<F, SparseTerm>) -> BruteForcePolynomial<F> {
+        let degrees = polys::variable_degrees(&polynomial);
+        BruteForcePolynomial { polynomial, degrees, bound: Vec::new() }
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for BruteForcePolynomial<F> {
+    fn num_vars(&self) -> usize {
+        self.polynomial.num_vars - self.bound.len()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        self.degrees[..self.num_vars()].to_vec()
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polys::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    fn round_evaluations(&self) -> Vec<F> {
+        let j = self.num_vars() - 1;
+        brute_force_evaluations(self.degrees[j], j, &self.bound, |point| self.polynomial.evaluate(&point.to_vec()))
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.bound.insert(0, r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        let full_point = [point, &self.bound].concat();
+        self.polynomial.evaluate(&full_point)
+    }
+}
+
+/// Evaluations g_j(0), ..., g_j(d) of the round polynomial binding x_j, for
+/// a polynomial g given by `evaluate` whose variables x_{j+1}, ... are bound
+/// to `bound`, computed by evaluating g at every point of {0,1}^j x {0, ...,
+/// d} x {bound}.
+pub fn brute_force_evaluations<F: Field>(degree: usize, j: usize, bound: &[F], evaluate: impl Fn(&[F]) -> F) -> Vec<F> {
+    let mut point = vec![F::zero(); j + 1 + bound.len()];
+    point[j + 1..].copy_from_slice(bound);
+    (0..=degree as u64)
+        .map(|t| {
+            point[j] = F::from(t);
+            (0..1usize << j)
+                .map(|idx| {
+                    for (var, x) in point[..j].iter_mut().enumerate() {
+                        *x = if idx & (1 << var) != 0 { F::one() } else { F::zero() };
+                    }
+                    evaluate(&point)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Representation of the round polynomials sent by the prover.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageEncoding {
+    /// Coefficients of g_j.
+    #[default]
+    Coefficients,
+    /// Evaluations g_j(0), ..., g_j(d_j).
+    Evaluations,
+}
+
+/// Prover side of the sum-check rounds, over a channel which may be shared
+/// with an enclosing protocol.  Sends g_j for each j from v-1 down to 0,
+/// binding x_j to the challenge received after each message except the last.
+/// Returns the challenges r_{v-1}, ..., r_1 in the order received, or `None`
+/// if the verifier rejected.
+pub fn prove_rounds<F: PrimeField, P: SumCheckPolynomial<F>, M: Embeds<Data<F>>>(
+        poly: &mut P,
+        encoding: MessageEncoding,
+        ch: &Channel<M>,
+        log: &Log) -> Option<Vec<F>> {
+    let num_vars = poly.num_vars();
+    let mut challenges = Vec::with_capacity(num_vars);
+
+    for j in (0..num_vars).rev() {
+        // compute univariate restriction and send to verifier
+        log.write(format!("P computes univariate polynomial g_{}", j));
+        let data = match encoding {
+            MessageEncoding::Coefficients => Data::Polynomial(poly.round_polynomial()),
+            MessageEncoding::Evaluations => Data::Evaluations(poly.round_evaluations()),
+        };
+        log.write(format!("P --> (g_{} = {})    {}", j, data, alternate_form(j, &data)));
+        ch.send_as(data);
+
+        // wait for random challenge, except for last challenge
+        if j > 0 {
+            let data: Data<F> = ch.receive_as()?;
+            if let Data::Decision(false) = data { return None; }
+            let challenge = data.to_scalar().unwrap();
+
+            // restrict according to random challenge
+            log.write(format!("P computes partial evaluation at x_{} = r_{}", j, j));
+            poly.fix_last_variable(challenge);
+            if let Some(partial) = poly.format() {
+                log.write(format!("P has g({}) = {}", partial_point(j, num_vars), partial));
+            }
+            challenges.push(challenge);
+        }
+    }
+
+    Some(challenges)
+}
+
+/// Verifier side of the sum-check rounds, over a channel which may be shared
+/// with an enclosing protocol.  On success returns the point r = (r_0, ...,
+/// r_{v-1}) and the final check value, which the caller must compare with an
+/// evaluation of the polynomial at r; the last challenge r_0 is not sent to
+/// the prover.  On failure sends a rejection and returns the failed check.
+pub fn verify_rounds<F: PrimeField, M: Embeds<Data<F>>>(
+        claimed_sum: F,
+        degrees: &[usize],
+        ch: &Channel<M>,
+        log: &Log) -> Result<(Vec<F>, F), SumCheckFailure<F>> {
+    let mut rng = rand::thread_rng();
+
+    let zero = F::zero();
+    let one  = F::one();
+
+    // let current check value equal to claimed sum
+    let mut check_value = claimed_sum;
+    let mut challenges: Vec<F> = Vec::with_capacity(degrees.len());
+
+    for j in (0..degrees.len()).rev() {
+        // wait for univariate restriction
+        let Some(data) = ch.receive_as::<Data<F>>() else {
+            return Err(reject(ch, log, SumCheckFailure::Malformed { round: j }));
+        };
+
+        log.write(format!("V checks g_{} has small enough degree", j));
+        let degree = match &data {
+            Data::Polynomial(uni) => uni.degree(),
+            Data::Evaluations(evals) => evals.len().saturating_sub(1),
+            _ => return Err(reject(ch, log, SumCheckFailure::Malformed { round: j })),
+        };
+        if degree > degrees[j] {
+            return Err(reject(ch, log, SumCheckFailure::Degree { round: j, degree, bound: degrees[j] }));
+        }
+        if let Data::Evaluations(evals) = &data {
+            if !distinct_points::<F>(evals.len()) {
+                return Err(reject(ch, log, SumCheckFailure::Encoding { round: j, count: evals.len() }));
+            }
+        }
+
+        log.write(format!("V checks g_{} sums to check value", j));
+        let sum = evaluate_message(&data, zero) + evaluate_message(&data, one);
+        if sum != check_value {
+            return Err(reject(ch, log, SumCheckFailure::Sum { round: j, sum, check_value }));
+        }
+
+        log.write(format!("V picks r_{} uniformly at random", j));
+        let challenge = F::rand(&mut rng);
+
+        if let Data::Evaluations(_) = data {
+            log.write(format!("V updates check value to g_{}(r_{}) by barycentric interpolation", j, j));
+        } else {
+            log.write(format!("V updates check value to g_{}(r_{})", j, j));
+        }
+        check_value = evaluate_message(&data, challenge);
+
+        // record challenge for later reference
+        challenges.push(challenge);
+
+        // send random challenge to prover, except for last challenge
+        let data = Data::Scalar(challenge);
+        if j > 0 {
+            log.write(format!("V --> (r_{} = {})", j, data));
+            ch.send_as(data);
+        } else {
+            log.write(format!("V has (r_0 = {}) but does not send to P", data));
+        }
+    }
+
+    challenges.reverse();
+    Ok((challenges, check_value))
+}
+
+/// Complete verifier of the sum-check protocol for `polynomial`: runs the
+/// rounds, checks the final check value against a single oracle evaluation,
+/// and sends the decision.  Returns the failed check on rejection.
+pub fn verify<F: PrimeField, P: SumCheckPolynomial<F>, M: Embeds<Data<F>>>(
+        polynomial: &P,
+        claimed_sum: F,
+        ch: &Channel<M>,
+        log: &Log) -> Result<(), SumCheckFailure<F>> {
+    let degrees = polynomial.variable_degrees();
+    let oracle = |point: &[F]| {
+        log.write(String::from("V evaluates g(r) with a single oracle query"));
+        polynomial.oracle_evaluation(point)
+    };
+    verify_with_oracle(claimed_sum, &degrees, oracle, ch, log)
+}
+
+/// Complete verifier of the sum-check protocol for a polynomial with the
+/// given degree bounds, which the verifier can evaluate using `oracle`.
+pub fn verify_with_oracle<F: PrimeField, M: Embeds<Data<F>>>(
+        claimed_sum: F,
+        degrees: &[usize],
+        oracle: impl FnOnce(&[F]) -> F,
+        ch: &Channel<M>,
+        log: &Log) -> Result<(), SumCheckFailure<F>> {
+    let (point, check_value) = verify_rounds(claimed_sum, degrees, ch, log)?;
+
+    // evaluate polynomial at vector of challenge points
+    let evaluation = oracle(&point);
+
+    // accept if the oracle evaluation equals the final check value
+    // otherwise reject
+    log.write(String::from("V checks that oracle evaluation equals final check value"));
+    if evaluation != check_value {
+        return Err(reject(ch, log, SumCheckFailure::Oracle { evaluation, check_value }));
+    }
+
+    let decision = Data::Decision(true);
+    log.write(format!("V --> ({})", decision));
+    ch.send_as(decision);
+    Ok(())
+}
+
+/// Check of the sum-check verifier which failed, with the round in which it
+/// failed given by the index j of the message g_j.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SumCheckFailure<F> {
+    /// g_j has higher degree than g has in x_j.
+    Degree { round: usize, degree: usize, bound: usize },
+    /// g_j is sent as evaluations at 0, 1, ..., count - 1, which are not
+    /// distinct since count exceeds the size of the field.
+    Encoding { round: usize, count: usize },
+    /// g_j(0) + g_j(1) differs from the check value.
+    Sum { round: usize, sum: F, check_value: F },
+    /// g(r) differs from the final check value.
+    Oracle { evaluation: F, check_value: F },
+    /// The message in place of g_j is not a univariate polynomial.
+    Malformed { round: usize },
+}
+
+impl<F: PrimeField> fmt::Display for SumCheckFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SumCheckFailure::Degree { round, degree, bound } => {
+                write!(f, "g_{} has degree {} but g has degree {} in x_{}", round, degree, bound, round)
+            }
+            SumCheckFailure::Encoding { round, count } => {
+                write!(f, "g_{} is sent as {} evaluations but the field has fewer elements", round, count)
+            }
+            SumCheckFailure::Sum { round, sum, check_value } => {
+                write!(f, "g_{}(0) + g_{}(1) = {} but check value is {}",
+                    round, round, sum.into_bigint(), check_value.into_bigint())
+            }
+            SumCheckFailure::Oracle { evaluation, check_value } => {
+                write!(f, "g(r) = {} but final check value is {}",
+                    evaluation.into_bigint(), check_value.into_bigint())
+            }
+            SumCheckFailure::Malformed { round } => write!(f, "g_{} is not sent as a univariate polynomial", round),
+        }
+    }
+}
+
+// arguments x_0, ..., x_{j-1}, r_j, ..., r_{v-1} of a partial evaluation
+fn partial_point(j: usize, num_vars: usize) -> String {
+    let vars = (0..j).map(|i| format!("x_{}", i));
+    let challenges = (j..num_vars).map(|i| format!("r_{}", i));
+    vars.chain(challenges).collect::<Vec<_>>().join(", ")
+}
+
+// whether 0, 1, ..., n - 1 are distinct elements of F, so that n evaluations
+// at these points determine a polynomial of degree less than n
+fn distinct_points<F: PrimeField>(n: usize) -> bool {
+    F::MODULUS_BIT_SIZE > 64 || n as u64 <= small_fields::field_size::<F>()
+}
+
+// evaluate the round polynomial sent in either encoding
+fn evaluate_message<F: PrimeField>(data: &Data<F>, r: F) -> F {
+    match data {
+        Data::Polynomial(uni) => uni.evaluate(&r),
+        Data::Evaluations(evals) => polys::evaluate_from_evaluations(evals, r),
+        _ => panic!("expected round polynomial"),
+    }
+}
+
+// describe the round polynomial in the encoding which was not sent, so that
+// both representations appear side by side in the log
+fn alternate_form<F: PrimeField>(j: usize, data: &Data<F>) -> String {
+    match data {
+        Data::Polynomial(uni) => {
+            let evals: Vec<_> = polys::integer_points::<F>(uni.coeffs.len().max(1))
+                .iter()
+                .map(|t| uni.evaluate(t).into_bigint())
+                .collect();
+            format!("[g_{}(0..={}) = {}]", j, evals.len() - 1, group_utils::list_vec(&evals, ", "))
+        }
+        Data::Evaluations(evals) if distinct_points::<F>(evals.len()) => {
+            let uni = polys::interpolate_evaluations(evals);
+            format!("[g_{} = {}]", j, polys::format_univ_poly(&uni, "x"))
+        }
+        _ => String::new(),
+    }
+}
+
+fn reject<F: PrimeField, M: Embeds<Data<F>>>(ch: &Channel<M>, log: &Log, failure: SumCheckFailure<F>) -> SumCheckFailure<F> {
+    log.write(format!("V check fails: {}", failure));
+    let data = Data::Decision(false);
+    log.write(format!("V --> ({})", data));
+    ch.send_as(data);
+    failure
+}
+
+pub struct SumCheckProver<P> {
+    pub polynomial: Arc<P>,
+    pub encoding: MessageEncoding,
+}
+
+impl<F: PrimeField, P: SumCheckPolynomial<F>> IP<Data<F>> for SumCheckProver<P> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        let mut poly = (*self.polynomial).clone();
+        prove_rounds(&mut poly, self.encoding, &ch, &log);
+    }
+}
+
+pub struct SumCheckVerifier<F, P> {
+    pub polynomial: Arc<P>,
+    pub claimed_sum: F,
+}
+
+impl<F: PrimeField, P: SumCheckPolynomial<F>> IP<Data<F>> for SumCheckVerifier<F, P> {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        // failures are recorded in the log
+        let _ = verify(&*self.polynomial, self.claimed_sum, &ch, &log);
+    }
+}
+
+
+#[derive(Clone)]
+pub enum Data<F: Field> {
+    Scalar(F),
+    Polynomial(UniPoly<F>),
+    Evaluations(Vec<F>),
+    Decision(bool),
+}
+
+impl<F: Field> Data<F> {
+    pub fn to_scalar(&self) -> Option<F> {
+        if let Data::Scalar(x) = self { Some(*x) } else { None }
+    }
+
+    pub fn to_polynomial(&self) -> Option<UniPoly<F>> {
+        if let Data::Polynomial(p) = self { Some(p.clone()) } else { None }
+    }
+
+    pub fn to_evaluations(&self) -> Option<Vec<F>> {
+        if let Data::Evaluations(evals) = self { Some(evals.clone()) } else { None }
+    }
+
+    pub fn to_decision(&self) -> Option<bool> {
+        if let Data::Decision(d) = self { Some(*d) } else { None }
+    }
+}
+
+impl<F: PrimeField> fmt::Display for Data<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Data::Scalar(x) => {
+                write!(f, "{}", x.into_bigint())
+            }
+            Data::Polynomial(p) => {
+                write!(f, "{}", polys::format_univ_poly(p, "x"))
+            }
+            Data::Evaluations(evals) => {
+                let evals: Vec<_> = evals.iter().map(|e| e.into_bigint()).collect();
+                write!(f, "[{}]", group_utils::list_vec(&evals, ", "))
+            }
+            Data::Decision(b) => {
+                if *b { write!(f, "Accept") } else { write!(f, "Reject") }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use rand::Rng;
+    use ark_poly::DenseMVPolynomial;
+    use ark_poly::polynomial::multivariate::Term;
+    use crate::small_fields::{F5, F13 as F};
+    use crate::test_utils;
+
+    // run the sum-check protocol between two threads, returning the decision
+    fn run<P: SumCheckPolynomial<F> + 'static>(poly: P, encoding: MessageEncoding, claimed_sum: F) -> bool {
+        run_with_prover(poly.clone(), poly, encoding, claimed_sum).is_ok()
+    }
+
+    fn run_with_prover<P: SumCheckPolynomial<F> + 'static>(
+            prover_poly: P,
+            poly: P,
+            encoding: MessageEncoding,
+            claimed_sum: F) -> Result<(), SumCheckFailure<F>> {
+        let prover = SumCheckProver { polynomial: Arc::new(prover_poly), encoding };
+        test_utils::run_against(prover, |ch, log| verify(&poly, claimed_sum, ch, log))
+    }
+
+    #[test]
+    fn test_multi_poly() {
+        // f(x, y) = x^2 + 2xy + 3y^2 sums to 10 over {0,1}^2
+        let poly = polys::construct_poly(2, vec![
+            (1, vec![(0, 2)]),
+            (2, vec![(0, 1), (1, 1)]),
+            (3, vec![(1, 2)]),
+        ]);
+        assert_eq!(poly.hypercube_sum(), F::from(10u64));
+        for encoding in [MessageEncoding::Coefficients, MessageEncoding::Evaluations] {
+            assert!(run(poly.clone(), encoding, F::from(10u64)));
+            assert!(!run(poly.clone(), encoding, F::from(11u64)));
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let poly = polys::construct_poly(2, vec![
+            (1, vec![(0, 2)]),
+            (2, vec![(0, 1), (1, 1)]),
+            (3, vec![(1, 2)]),
+        ]);
+        let result = run_with_prover(poly.clone(), poly.clone(), MessageEncoding::Coefficients, F::from(11u64));
+        assert_eq!(result, Err(SumCheckFailure::Sum { round: 1, sum: F::from(10u64), check_value: F::from(11u64) }));
+
+        // prover for a polynomial of higher degree in x_1
+        let other = polys::construct_poly(2, vec![(1, vec![(1, 3)]), (12, vec![(1, 1)])]);
+        let other = &poly + &other;
+        let result = run_with_prover(other, poly.clone(), MessageEncoding::Coefficients, F::from(10u64));
+        assert_eq!(result, Err(SumCheckFailure::Degree { round: 1, degree: 3, bound: 2 }));
+
+        // prover for g + x_0^2 + 10x_0 + 1, which has the same sum over the
+        // hypercube, but differs from g at every point as x^2 + 10x + 1 has
+        // no roots in F13
+        let other = polys::construct_poly(2, vec![(1, vec![(0, 2)]), (10, vec![(0, 1)]), (1, vec![])]);
+        let other = &poly + &other;
+        let result = run_with_prover(other, poly, MessageEncoding::Evaluations, F::from(10u64));
+        assert!(matches!(result, Err(SumCheckFailure::Oracle { .. })));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let poly = polys::construct_poly(2, vec![(1, vec![(0, 1), (1, 1)])]);
+        let prover = test_utils::ScriptedProver(vec![Data::Scalar(F::from(1u64))]);
+        let result = test_utils::run_against(prover, |ch, log| verify(&poly, F::from(1u64), ch, log));
+        assert_eq!(result, Err(SumCheckFailure::Malformed { round: 1 }));
+    }
+
+    #[test]
+    fn test_degree_exceeds_field() {
+        // g(x_0) = x_0^5 over F5 is sent as 6 evaluations at 0, ..., 5 = 0
+        let poly: MultiPoly<F5, SparseTerm> = polys::construct_poly(1, vec![(1, vec![(0, 5)])]);
+        let prover = SumCheckProver { polynomial: Arc::new(poly.clone()), encoding: MessageEncoding::Evaluations };
+        let result = test_utils::run_against(prover, |ch, log| verify(&poly, F5::from(1u64), ch, log));
+        assert_eq!(result, Err(SumCheckFailure::Encoding { round: 0, count: 6 }));
+    }
+
+    fn random_poly<G: PrimeField>(num_vars: usize, num_terms: usize) -> MultiPoly<G, SparseTerm> {
+        let mut rng = rand::thread_rng();
+        let terms = (0..num_terms)
+            .map(|_| {
+                let monom = (0..num_vars).map(|var| (var, rng.gen_range(0..=2))).collect();
+                (G::rand(&mut rng), SparseTerm::new(monom))
+            })
+            .collect();
+        MultiPoly::from_coefficients_vec(num_vars, terms)
+    }
+
+    // compare round messages of the brute force and symbolic provers
+    fn check_brute_force<G: PrimeField>(poly: MultiPoly<G, SparseTerm>) {
+        let mut rng = rand::thread_rng();
+        let mut symbolic = poly.clone();
+        let mut brute_force = BruteForcePolynomial::new(poly.clone());
+        let mut point = Vec::new();
+        for _ in 0..poly.num_vars {
+            assert_eq!(brute_force.round_polynomial(), symbolic.round_polynomial());
+            let r = G::rand(&mut rng);
+            SumCheckPolynomial::fix_last_variable(&mut symbolic, r);
+            brute_force.fix_last_variable(r);
+            point.insert(0, r);
+        }
+        assert_eq!(brute_force.oracle_evaluation(&[]), poly.oracle_evaluation(&point));
+    }
+
+    #[test]
+    fn test_brute_force() {
+        for num_vars in 1..=4 {
+            check_brute_force(random_poly::<F5>(num_vars, 6));
+            check_brute_force(random_poly::<F>(num_vars, 6));
+        }
+
+        let poly = random_poly::<F>(3, 6);
+        let sum = poly.hypercube_sum();
+        assert_eq!(BruteForcePolynomial::new(poly.clone()).hypercube_sum(), sum);
+        assert!(run(BruteForcePolynomial::new(poly.clone()), MessageEncoding::Evaluations, sum));
+        assert!(!run(BruteForcePolynomial::new(poly), MessageEncoding::Coefficients, sum + F::from(1u64)));
+    }
+
+    #[test]
+    fn test_dense_mle() {
+        let mut rng = rand::thread_rng();
+        let mle = DenseMLE::<F>::rand(4, &mut rng);
+        let mut poly = mle.to_multi_poly();
+        let mut mle_copy = mle.clone();
+
+        // both representations give the same round polynomials
+        for _ in 0..4 {
+            assert_eq!(mle_copy.round_polynomial(), poly.round_polynomial());
+            let r = F::rand(&mut rng);
+            mle_copy.fix_last_variable(r);
+            SumCheckPolynomial::fix_last_variable(&mut poly, r);
+        }
+
+        assert!(run(mle.clone(), MessageEncoding::Coefficients, mle.sum()));
+        assert!(!run(mle.clone(), MessageEncoding::Coefficients, mle.sum() + F::from(1u64)));
+    }
+
+    #[test]
+    fn test_product_mle() {
+        let mut rng = rand::thread_rng();
+        let product = ProductMLE::<F>::rand(3, 3, &mut rng);
+        let mut poly = product.to_multi_poly();
+        let mut product_copy = product.clone();
+
+        // linear-time round messages agree with the symbolic prover
+        for _ in 0..3 {
+            assert_eq!(product_copy.round_polynomial(), poly.round_polynomial());
+            let r = F::rand(&mut rng);
+            SumCheckPolynomial::fix_last_variable(&mut product_copy, r);
+            SumCheckPolynomial::fix_last_variable(&mut poly, r);
+        }
+
+        let sum = product.hypercube_sum();
+        assert!(run(product.clone(), MessageEncoding::Evaluations, sum));
+        assert!(!run(product, MessageEncoding::Evaluations, sum + F::from(1u64)));
+    }
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field,PrimeField,UniformRand};
+
+use crate::group_utils;
+use crate::bulletproof;
+use crate::multilinear_kzg::{self,MultilinearKzgParams};
+use crate::multilinear::{DenseMLE,ProductMLE};
+use crate::sum_check::{self,SumCheckFailure,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Chapter 7
+// Compiling the sum-check IP into a succinct argument with a polynomial
+// commitment.  The verifier of the IP ends by evaluating g(r) with a single
+// oracle query, which requires it to hold all of g.  Instead, the prover
+// commits to g before the protocol begins, and at the end sends the claimed
+// value g(r) together with an opening proof that it is consistent with the
+// commitment.  The verifier's work and the communication are then the
+// O(v d) field elements of sum-check plus the size of the commitment and of
+// one opening proof.
+//
+// Here g = f_1 * ... * f_k is a product of multilinear polynomials, each
+// committed with multilinear KZG.  The prover sends the values f_i(r), the
+// verifier checks that their product is the final check value, and then the
+// k openings at the same point r are batched into one: for random gamma the
+// commitment to sum_i gamma^i f_i is computed by homomorphism, and is opened
+// at r to the value sum_i gamma^i f_i(r).  The last sum-check challenge r_0 is
+// sent to the prover, which needs all of r to open.
+//
+// With n = 2^v the size of the evaluation tables, the commitments are k group
+// elements and the opening proof v, so the proof has size O(v k) and the
+// verifier does O(v k) work including v + 1 pairings: both are
+// polylogarithmic in n, at the cost of a trusted setup.
+
+/// Messages of the argument, which carry those of the sum-check rounds, and
+/// for the zero-knowledge variant those of the inner product argument for
+/// the masking polynomial.
+#[derive(Clone)]
+pub enum Message<E: Pairing> {
+    Commitments(Vec<E::G1>),
+    SumCheck(Data<E::ScalarField>),
+    Opening(Vec<E::G1>),
+    Mask(bulletproof::Data<E::G1>),
+}
+
+impl<E: Pairing> From<Data<E::ScalarField>> for Message<E> {
+    fn from(data: Data<E::ScalarField>) -> Message<E> {
+        Message::SumCheck(data)
+    }
+}
+
+impl<E: Pairing> TryFrom<Message<E>> for Data<E::ScalarField> {
+    type Error = ();
+
+    fn try_from(message: Message<E>) -> Result<Self, ()> {
+        if let Message::SumCheck(data) = message { Ok(data) } else { Err(()) }
+    }
+}
+
+impl<E: Pairing> From<bulletproof::Data<E::G1>> for Message<E> {
+    fn from(data: bulletproof::Data<E::G1>) -> Message<E> {
+        Message::Mask(data)
+    }
+}
+
+impl<E: Pairing> TryFrom<Message<E>> for bulletproof::Data<E::G1> {
+    type Error = ();
+
+    fn try_from(message: Message<E>) -> Result<Self, ()> {
+        if let Message::Mask(data) = message { Ok(data) } else { Err(()) }
+    }
+}
+
+impl<E: Pairing> fmt::Display for Message<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Commitments(commitments) => write!(f, "[{}]", group_utils::list_vec(commitments, ", ")),
+            Message::SumCheck(data) => write!(f, "{}", data),
+            Message::Opening(proof) => write!(f, "[{}]", group_utils::list_vec(proof, ", ")),
+            Message::Mask(data) => write!(f, "{}", data),
+        }
+    }
+}
+
+/// Communication of the argument for a product of `num_factors` multilinear
+/// polynomials in `num_vars` variables, as the numbers of group elements and
+/// field elements sent by the prover: a commitment to each factor and the v
+/// quotient commitments of the opening, and the sum-check messages and the
+/// values of the factors.
+pub fn proof_size(num_vars: usize, num_factors: usize) -> (usize, usize) {
+    (num_factors + num_vars, num_vars * (num_factors + 1) + num_factors)
+}
+
+/// Check of the verifier which failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentFailure<F> {
+    /// A round of sum-check failed.
+    SumCheck(SumCheckFailure<F>),
+    /// The value at r given by the claimed evaluations differs from the final
+    /// check value.
+    Claims { evaluation: F, check_value: F },
+    /// The opening proof for the batched claims was rejected.
+    Opening,
+    /// The prover sent a message of the wrong form instead of the expected
+    /// one.
+    Malformed { expected: &'static str },
+}
+
+impl<F: PrimeField> fmt::Display for ArgumentFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgumentFailure::SumCheck(failure) => write!(f, "sum-check: {}", failure),
+            ArgumentFailure::Claims { evaluation, check_value } => {
+                write!(f, "claimed evaluations give g(r) = {} but final check value is {}",
+                    evaluation.into_bigint(), check_value.into_bigint())
+            }
+            ArgumentFailure::Opening => write!(f, "opening proof rejected"),
+            ArgumentFailure::Malformed { expected } => write!(f, "malformed message, expected {}", expected),
+        }
+    }
+}
+
+/// Record a failed check in the log and send the rejection.
+pub fn reject<E: Pairing>(ch: &Channel<Message<E>>, log: &Log, failure: ArgumentFailure<E::ScalarField>) -> ArgumentFailure<E::ScalarField> {
+    log.write(format!("V check fails: {}", failure));
+    let decision = Data::Decision(false);
+    log.write(format!("V --> ({})", decision));
+    ch.send_as(decision);
+    failure
+}
+
+// sum_i gamma^i x_i
+fn batch<T: Copy + std::ops::Mul<F, Output=T> + std::ops::Add<Output=T>, F: Field>(values: &[T], gamma: F) -> T {
+    values.iter().rev().skip(1).fold(*values.last().unwrap(), |acc, x| acc * gamma + *x)
+}
+
+/// Prover side of the argument that the product polynomial sums to its
+/// hypercube sum, with the factors committed using `params`.
+pub fn prove<E: Pairing>(params: &MultilinearKzgParams<E>, polynomial: &ProductMLE<E::ScalarField>, ch: &Channel<Message<E>>, log: &Log) {
+    log.write(format!("P commits to the {} factors of g with multilinear KZG", polynomial.factors.len()));
+    let commitments: Vec<_> = polynomial.factors.iter().map(|f| multilinear_kzg::commit(params, f)).collect();
+    let message = Message::Commitments(commitments);
+    log.write(format!("P --> (commitments = {})", message));
+    ch.send(message);
+
+    let mut poly = polynomial.clone();
+    let Some(challenges) = sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, ch, log) else { return };
+    let Some(r_0) = ch.receive_as::<Data<_>>().and_then(|data| data.to_scalar()) else { return };
+    let point: Vec<_> = std::iter::once(r_0).chain(challenges.into_iter().rev()).collect();
+
+    poly.fix_last_variable(r_0);
+    let data = Data::Evaluations(poly.factors.iter().map(|f| f.evals[0]).collect());
+    log.write(format!("P --> (f_i(r) = {})", data));
+    ch.send_as(data);
+
+    prove_openings(params, &polynomial.factors, &point, ch, log);
+}
+
+/// Prover side of the batched opening of the committed `factors` at `point`,
+/// after their values there have been sent.
+pub fn prove_openings<E: Pairing>(
+        params: &MultilinearKzgParams<E>,
+        factors: &[DenseMLE<E::ScalarField>],
+        point: &[E::ScalarField],
+        ch: &Channel<Message<E>>,
+        log: &Log) {
+    let Some(gamma) = ch.receive_as::<Data<_>>().and_then(|data| data.to_scalar()) else { return };
+    log.write(String::from("P opens sum_i gamma^i f_i at r"));
+    let num_vars = point.len();
+    let evals = (0..1 << num_vars)
+        .map(|idx| batch(&factors.iter().map(|f| f.evals[idx]).collect::<Vec<_>>(), gamma))
+        .collect();
+    let combined = DenseMLE::new(num_vars, evals);
+    let (_, proof) = multilinear_kzg::open(params, &combined, point);
+    let message = Message::Opening(proof);
+    log.write(format!("P --> (quotient commitments = {})", message));
+    ch.send(message);
+}
+
+/// Verifier side of the argument that a product of `num_factors` committed
+/// multilinear polynomials in `num_vars` variables sums to `claimed_sum`.
+/// The verifier never sees the polynomials themselves.
+pub fn verify<E: Pairing>(
+        params: &MultilinearKzgParams<E>,
+        claimed_sum: E::ScalarField,
+        num_vars: usize,
+        num_factors: usize,
+        ch: &Channel<Message<E>>,
+        log: &Log) -> Result<(), ArgumentFailure<E::ScalarField>> {
+    let commitments = match ch.receive() {
+        Message::Commitments(commitments) if commitments.len() == num_factors => commitments,
+        _ => return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "a commitment to each factor" })),
+    };
+
+    let degrees = vec![num_factors; num_vars];
+    let (point, check_value) = sum_check::verify_rounds(claimed_sum, &degrees, ch, log)
+        .map_err(ArgumentFailure::SumCheck)?;
+    let data = Data::Scalar(point[0]);
+    log.write(format!("V --> (r_0 = {}) so that P can open at r", data));
+    ch.send_as(data);
+
+    let values = match ch.receive_as::<Data<_>>().and_then(|data| data.to_evaluations()) {
+        Some(values) if values.len() == num_factors => values,
+        _ => return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "the value of each factor at r" })),
+    };
+    log.write(String::from("V checks final check value equals f_1(r) ... f_k(r)"));
+    let evaluation = values.iter().product();
+    if evaluation != check_value {
+        return Err(reject(ch, log, ArgumentFailure::Claims { evaluation, check_value }));
+    }
+
+    verify_openings(params, &commitments, &point, &values, ch, log)?;
+
+    let (group_elements, field_elements) = proof_size(num_vars, num_factors);
+    log.write(format!("V received {} group elements and {} field elements, instead of the {} field elements of the factors needed to evaluate g(r) itself",
+        group_elements, field_elements, num_factors << num_vars));
+    Ok(())
+}
+
+/// Verifier side of the batched opening of the polynomials committed in
+/// `commitments` at `point` to the claimed `values`.  Sends the decision.
+pub fn verify_openings<E: Pairing>(
+        params: &MultilinearKzgParams<E>,
+        commitments: &[E::G1],
+        point: &[E::ScalarField],
+        values: &[E::ScalarField],
+        ch: &Channel<Message<E>>,
+        log: &Log) -> Result<(), ArgumentFailure<E::ScalarField>> {
+    let mut rng = rand::thread_rng();
+    log.write(String::from("V picks gamma uniformly at random to batch the openings"));
+    let gamma = E::ScalarField::rand(&mut rng);
+    let data = Data::Scalar(gamma);
+    log.write(format!("V --> (gamma = {})", data));
+    ch.send_as(data);
+
+    let Message::Opening(proof) = ch.receive() else {
+        return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "an opening proof" }));
+    };
+    log.write(String::from("V computes the commitment to sum_i gamma^i f_i by homomorphism"));
+    let combined = batch(commitments, gamma);
+    log.write(format!("V checks the opening with {} pairings", point.len() + 1));
+    if !multilinear_kzg::verify(params, combined, point, batch(values, gamma), &proof) {
+        return Err(reject(ch, log, ArgumentFailure::Opening));
+    }
+    let decision = Data::Decision(true);
+    log.write(format!("V --> ({})", decision));
+    ch.send_as(decision);
+    Ok(())
+}
+
+pub struct SumCheckArgumentProver<E: Pairing> {
+    pub params: Arc<MultilinearKzgParams<E>>,
+    pub polynomial: Arc<ProductMLE<E::ScalarField>>,
+}
+
+impl<E: Pairing> IP<Message<E>> for SumCheckArgumentProver<E> {
+    fn execute(&self, ch: Channel<Message<E>>, log: Log) {
+        prove(&self.params, &self.polynomial, &ch, &log);
+    }
+}
+
+/// Verifier holding only the claimed sum and the shape of the polynomial.
+pub struct SumCheckArgumentVerifier<E: Pairing> {
+    pub params: Arc<MultilinearKzgParams<E>>,
+    pub claimed_sum: E::ScalarField,
+    pub num_vars: usize,
+    pub num_factors: usize,
+}
+
+impl<E: Pairing> IP<Message<E>> for SumCheckArgumentVerifier<E> {
+    fn execute(&self, ch: Channel<Message<E>>, log: Log) {
+        // failures are recorded in the log
+        let _ = verify(&self.params, self.claimed_sum, self.num_vars, self.num_factors, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_test_curves::bls12_381::{Bls12_381,Fr};
+    use crate::small_curves::SS67Pairing;
+    use crate::small_fields::F17;
+    use crate::sum_check::SumCheckPolynomial;
+    use crate::test_utils;
+
+    fn run<E: Pairing>(polynomial: ProductMLE<E::ScalarField>, claimed_sum: E::ScalarField) -> Result<(), ArgumentFailure<E::ScalarField>> {
+        let mut rng = rand::thread_rng();
+        let num_vars = polynomial.num_vars();
+        let tau: Vec<_> = (0..num_vars).map(|_| E::ScalarField::rand(&mut rng)).collect();
+        let params = Arc::new(multilinear_kzg::setup::<E>(&tau));
+        let num_factors = polynomial.factors.len();
+        let prover = SumCheckArgumentProver { params: params.clone(), polynomial: Arc::new(polynomial) };
+        test_utils::run_against(prover, |ch, log| verify(&params, claimed_sum, num_vars, num_factors, ch, log))
+    }
+
+    #[test]
+    fn test_batch() {
+        let values: Vec<F17> = [3u64, 5, 7].into_iter().map(F17::from).collect();
+        let gamma = F17::from(2u64);
+        assert_eq!(batch(&values, gamma), F17::from(3u64 + 10 + 28));
+    }
+
+    #[test]
+    fn test_argument() {
+        let polynomial = ProductMLE::<F17>::rand(3, 2, &mut rand::thread_rng());
+        let sum = polynomial.hypercube_sum();
+        assert_eq!(run::<SS67Pairing>(polynomial, sum), Ok(()));
+
+        let polynomial = ProductMLE::<Fr>::rand(3, 2, &mut rand::thread_rng());
+        let sum = polynomial.hypercube_sum();
+        assert_eq!(run::<Bls12_381>(polynomial.clone(), sum), Ok(()));
+        let result = run::<Bls12_381>(polynomial, sum + Fr::from(1u64));
+        assert!(matches!(result, Err(ArgumentFailure::SumCheck(SumCheckFailure::Sum { .. }))));
+
+        let failure = ArgumentFailure::Claims { evaluation: F17::from(0u64), check_value: F17::from(5u64) };
+        assert_eq!(failure.to_string(), "claimed evaluations give g(r) = 0 but final check value is 5");
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut rng = rand::thread_rng();
+        let tau: Vec<F17> = (0..2).map(|_| F17::rand(&mut rng)).collect();
+        let params = multilinear_kzg::setup::<SS67Pairing>(&tau);
+        let verify = |messages| {
+            let prover = test_utils::ScriptedProver(messages);
+            test_utils::run_against(prover, |ch, log| verify(&params, F17::from(0u64), 2, 2, ch, log))
+        };
+
+        // one commitment for two factors
+        let commitment = params.lagrange_g[0][0];
+        let result = verify(vec![Message::Commitments(vec![commitment])]);
+        assert_eq!(result, Err(ArgumentFailure::Malformed { expected: "a commitment to each factor" }));
+
+        // a scalar in place of the first round polynomial
+        let messages = vec![Message::Commitments(vec![commitment; 2]), Message::SumCheck(Data::Scalar(F17::from(0u64)))];
+        assert_eq!(verify(messages), Err(ArgumentFailure::SumCheck(SumCheckFailure::Malformed { round: 1 })));
+
+        // zero round polynomials pass sum-check for the claimed sum 0 whatever
+        // the challenges, followed by a scalar in place of the values at r
+        let zero = F17::from(0u64);
+        let mut messages = vec![Message::Commitments(vec![commitment; 2])];
+        messages.extend(vec![Message::SumCheck(Data::Evaluations(vec![zero; 3])); 2]);
+        let result = verify([messages.clone(), vec![Message::SumCheck(Data::Scalar(zero))]].concat());
+        assert_eq!(result, Err(ArgumentFailure::Malformed { expected: "the value of each factor at r" }));
+
+        // consistent values at r followed by a commitment in place of the
+        // opening proof
+        messages.push(Message::SumCheck(Data::Evaluations(vec![zero; 2])));
+        let result = verify([messages, vec![Message::Commitments(vec![])]].concat());
+        assert_eq!(result, Err(ArgumentFailure::Malformed { expected: "an opening proof" }));
+    }
+
+    #[test]
+    fn test_proof_size() {
+        // commitments to 2 factors and 11 quotients in the opening
+        assert_eq!(proof_size(11, 2), (2 + 11, 11 * 3 + 2));
+    }
+}
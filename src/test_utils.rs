@@ -0,0 +1,29 @@
+use std::thread;
+
+use crate::ip::{IP,Channel,Log};
+
+// Fixtures shared by the protocol tests.
+
+/// Run `prover` in its own thread against the verifier side `verify` in this
+/// one, returning the verifier's result.
+pub fn run_against<T: Clone + Send + 'static, R>(
+        prover: impl IP<T> + Send + 'static,
+        verify: impl FnOnce(&Channel<T>, &Log) -> R) -> R {
+    let (ch_p, ch_v) = Channel::gen();
+    let handle = thread::spawn(move || prover.execute(ch_p, Log::new()));
+    let result = verify(&ch_v, &Log::new());
+    handle.join().unwrap();
+    result
+}
+
+/// Prover which sends `messages` in turn without reading the verifier's
+/// replies, for checking how verifiers handle malformed messages.
+pub struct ScriptedProver<T>(pub Vec<T>);
+
+impl<T: Clone> IP<T> for ScriptedProver<T> {
+    fn execute(&self, ch: Channel<T>, _log: Log) {
+        for message in &self.0 {
+            ch.send(message.clone());
+        }
+    }
+}
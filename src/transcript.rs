@@ -0,0 +1,57 @@
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Sha256,Digest};
+
+// PAZK, Section 5.1
+// Fiat-Shamir transformation: the verifier's random challenges are replaced by
+// hashes of the transcript of all prover messages sent so far, so that the
+// prover can compute the challenges by itself and produce a non-interactive
+// proof.
+
+/// Running hash of a protocol transcript, from which challenges are derived.
+#[derive(Clone)]
+pub struct Transcript {
+    state: [u8; 32],
+}
+
+impl Transcript {
+    /// Start a transcript, with a label separating different protocols.
+    pub fn new(label: &[u8]) -> Transcript {
+        Transcript { state: Sha256::digest(label).into() }
+    }
+
+    pub fn append_bytes(&mut self, bytes: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.state);
+        hasher.update(bytes);
+        self.state = hasher.finalize().into();
+    }
+
+    /// Append a field element, group element, or other serializable value.
+    pub fn append<T: CanonicalSerialize>(&mut self, value: &T) {
+        let mut bytes = Vec::new();
+        value.serialize_compressed(&mut bytes).unwrap();
+        self.append_bytes(&bytes);
+    }
+
+    fn challenge_bytes(&mut self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.state);
+        hasher.update(b"challenge");
+        let challenge: [u8; 32] = hasher.finalize().into();
+        self.append_bytes(&challenge);
+        challenge
+    }
+
+    /// Derive a field element challenge from the transcript so far.
+    pub fn challenge_scalar<F: PrimeField>(&mut self) -> F {
+        F::from_le_bytes_mod_order(&self.challenge_bytes())
+    }
+
+    /// Derive a challenge index in the range 0..bound from the transcript so far.
+    pub fn challenge_index(&mut self, bound: usize) -> usize {
+        let bytes = self.challenge_bytes();
+        let value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        (value % bound as u64) as usize
+    }
+}
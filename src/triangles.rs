@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use ark_ff::{Field,PrimeField};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+
+use crate::polynomials;
+use crate::multilinear::{self,DenseMLE,ProductMLE};
+use crate::poly_parser::ParseError;
+use crate::sum_check::{self,SumCheckPolynomial,MessageEncoding,Data};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 4.3
+// Counting triangles: for a graph on n = 2^m vertices with adjacency matrix A,
+// viewed as a function A: {0,1}^m x {0,1}^m -> {0,1}, the number of triangles
+// is
+//   Delta = 1/6 sum_{i,j,k in {0,1}^m} A(i,j) A(j,k) A(i,k),
+// since each triangle is counted once for each ordering of its vertices.  The
+// prover claims Delta and proves that the sum is 6 Delta by applying
+// sum-check to the product g(X,Y,Z) = A~(X,Y) A~(Y,Z) A~(X,Z) of multilinear
+// extensions in 3m variables, which has degree 2 in each variable.  The
+// prover's work is O(n^3), while the verifier only evaluates A~ at three
+// points in O(n^2) time, which is linear in the size of the input.
+//
+// Vertex i is the point of {0,1}^m with bits x_0, ..., x_{m-1} the binary
+// digits of i, and the variables of g are X = (x_0, ..., x_{m-1}),
+// Y = (x_m, ..., x_{2m-1}) and Z = (x_{2m}, ..., x_{3m-1}).
+
+/// Undirected simple graph with vertices 0, ..., num_vertices - 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Graph {
+    pub num_vertices: usize,
+    pub adjacency: Vec<Vec<bool>>,
+}
+
+impl Graph {
+    /// Construct a graph from a list of edges, ignoring repeated edges.
+    pub fn new(num_vertices: usize, edges: &[(usize, usize)]) -> Graph {
+        let mut adjacency = vec![vec![false; num_vertices]; num_vertices];
+        for &(u, v) in edges {
+            assert!(u != v, "graph must not have loops");
+            adjacency[u][v] = true;
+            adjacency[v][u] = true;
+        }
+        Graph { num_vertices, adjacency }
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.adjacency.iter().flatten().filter(|a| **a).count() / 2
+    }
+
+    /// Number m of bits needed to index a vertex, which is at least 1.
+    pub fn log_vertices(&self) -> usize {
+        multilinear::index_bits(self.num_vertices)
+    }
+
+    /// Count the triangles by checking every triple of vertices.
+    pub fn count_triangles(&self) -> u64 {
+        let a = &self.adjacency;
+        let n = self.num_vertices;
+        let mut count = 0;
+        for i in 0..n {
+            for j in i + 1..n {
+                if !a[i][j] { continue; }
+                count += (j + 1..n).filter(|&k| a[i][k] && a[j][k]).count() as u64;
+            }
+        }
+        count
+    }
+
+    /// Multilinear extension of the adjacency matrix in 2m variables, padded
+    /// with isolated vertices to 2^m vertices, with A(i,j) at index i + 2^m j.
+    pub fn adjacency_mle<F: Field>(&self) -> DenseMLE<F> {
+        let m = self.log_vertices();
+        let mut evals = vec![F::zero(); 1 << (2 * m)];
+        for (i, row) in self.adjacency.iter().enumerate() {
+            for (j, a) in row.iter().enumerate() {
+                if *a { evals[i + (j << m)] = F::one(); }
+            }
+        }
+        DenseMLE::new(2 * m, evals)
+    }
+
+    /// The polynomial g(X,Y,Z) = A~(X,Y) A~(Y,Z) A~(X,Z) as a product of
+    /// multilinear polynomials in 3m variables, with tables of size n^3.
+    pub fn triangle_polynomial<F: Field>(&self) -> TrianglePolynomial<F> {
+        let m = self.log_vertices();
+        let adjacency = self.adjacency_mle::<F>();
+        let mask = (1 << m) - 1;
+        let factor = |select: fn(usize, usize, usize) -> (usize, usize)| {
+            let evals = (0..1usize << (3 * m))
+                .map(|idx| {
+                    let (u, v) = select(idx & mask, (idx >> m) & mask, idx >> (2 * m));
+                    adjacency.evals[u + (v << m)]
+                })
+                .collect();
+            DenseMLE::new(3 * m, evals)
+        };
+        TrianglePolynomial {
+            product: ProductMLE::new(vec![
+                factor(|i, j, _| (i, j)),
+                factor(|_, j, k| (j, k)),
+                factor(|i, _, k| (i, k)),
+            ]),
+        }
+    }
+}
+
+/// The product g(X,Y,Z) = A~(X,Y) A~(Y,Z) A~(X,Z), which has degree 2 rather
+/// than 3 in each variable since every variable occurs in only two factors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrianglePolynomial<F: Field> {
+    pub product: ProductMLE<F>,
+}
+
+impl<F: Field> SumCheckPolynomial<F> for TrianglePolynomial<F> {
+    fn num_vars(&self) -> usize {
+        self.product.num_vars()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![2; self.num_vars()]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polynomials::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    // the product prover computes evaluations at 0, ..., 3, of which the
+    // first three determine the round polynomial
+    fn round_evaluations(&self) -> Vec<F> {
+        let mut evals = self.product.round_evaluations();
+        evals.truncate(3);
+        evals
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.product.fix_last_variable(r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.product.evaluate(point)
+    }
+}
+
+/// Evaluate g(X,Y,Z) = A~(X,Y) A~(Y,Z) A~(X,Z) at a point of F^{3m} using
+/// three evaluations of the multilinear extension of the adjacency matrix.
+pub fn evaluate_triangle_polynomial<F: Field>(adjacency: &DenseMLE<F>, point: &[F]) -> F {
+    let m = adjacency.num_vars / 2;
+    let (x, y, z) = (&point[..m], &point[m..2 * m], &point[2 * m..]);
+    let a = |u: &[F], v: &[F]| adjacency.evaluate(&[u, v].concat());
+    a(x, y) * a(y, z) * a(x, z)
+}
+
+/// Parse a graph given as a list of edges, one per line as a pair of vertex
+/// indices "u v" starting from 0, with lines starting with '#' or '%' treated
+/// as comments.  The number of vertices is one more than the largest index.
+pub fn parse_edge_list(input: &str) -> Result<Graph, ParseError> {
+    let mut edges = Vec::new();
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('%') {
+            continue;
+        }
+        let position = line_start + line.find(trimmed).unwrap_or(0);
+        let vertices: Vec<usize> = trimmed.split_whitespace()
+            .map(|word| word.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| ParseError { position, message: format!("invalid edge '{}'", trimmed) })?;
+        match vertices.as_slice() {
+            [u, v] if u == v => {
+                return Err(ParseError { position, message: format!("loop at vertex {}", u) });
+            }
+            [u, v] => edges.push((*u, *v)),
+            _ => return Err(ParseError { position, message: String::from("expected edge as a pair of vertices 'u v'") }),
+        }
+    }
+    let num_vertices = edges.iter().map(|&(u, v)| u.max(v) + 1).max().unwrap_or(0);
+    Ok(Graph::new(num_vertices, &edges))
+}
+
+pub struct TriangleProver {
+    pub graph: Arc<Graph>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for TriangleProver {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        log.write(String::from("P counts triangles"));
+        let data = Data::Scalar(F::from(self.graph.count_triangles()));
+        log.write(format!("P --> (Delta = {})", data));
+        ch.send(data);
+
+        log.write(String::from("P builds evaluation tables of A~(X,Y), A~(Y,Z) and A~(X,Z)"));
+        let mut poly = self.graph.triangle_polynomial::<F>();
+        sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, &ch, &log);
+    }
+}
+
+pub struct TriangleVerifier {
+    pub graph: Arc<Graph>,
+}
+
+impl<F: PrimeField> IP<Data<F>> for TriangleVerifier {
+    fn execute(&self, ch: Channel<Data<F>>, log: Log) {
+        let count = ch.receive().to_scalar().unwrap();
+        log.write(String::from("V checks sum of g over the hypercube is 6 Delta"));
+
+        let degrees = vec![2; 3 * self.graph.log_vertices()];
+        let oracle = |point: &[F]| {
+            log.write(String::from("V evaluates A~ at (r_X, r_Y), (r_Y, r_Z) and (r_X, r_Z) from the adjacency matrix"));
+            evaluate_triangle_polynomial(&self.graph.adjacency_mle(), point)
+        };
+        // failures are recorded in the log
+        let _ = sum_check::verify_with_oracle(count * F::from(6u64), &degrees, oracle, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use crate::small_fields::F251 as F;
+
+    #[test]
+    fn test_count_triangles() {
+        // K4 with a pendant vertex and a separate triangle
+        let input = "# test graph\n0 1\n0 2\n0 3\n1 2\n1 3\n2 3\n3 4\n5 6\n6 7\n7 5\n1 0\n";
+        let graph = parse_edge_list(input).unwrap();
+        assert_eq!(graph.num_vertices, 8);
+        assert_eq!(graph.num_edges(), 10);
+        assert_eq!(graph.log_vertices(), 3);
+        assert_eq!(graph.count_triangles(), 5);
+
+        let poly = graph.triangle_polynomial::<F>();
+        assert_eq!(poly.num_vars(), 9);
+        assert_eq!(poly.variable_degrees(), vec![2; 9]);
+        assert_eq!(poly.hypercube_sum(), F::from(30u64));
+
+        let point: Vec<F> = (0..9).map(|_| F::rand(&mut rand::thread_rng())).collect();
+        assert_eq!(evaluate_triangle_polynomial(&graph.adjacency_mle(), &point), poly.oracle_evaluation(&point));
+    }
+
+    #[test]
+    fn test_parse_edge_list() {
+        let error = parse_edge_list("0 1\n2 2\n").unwrap_err();
+        assert_eq!(error, ParseError { position: 4, message: String::from("loop at vertex 2") });
+        let error = parse_edge_list("0 1\n  1 a\n").unwrap_err();
+        assert_eq!(error, ParseError { position: 6, message: String::from("invalid edge '1 a'") });
+        let error = parse_edge_list("0 1 2\n").unwrap_err();
+        assert_eq!(error, ParseError { position: 0, message: String::from("expected edge as a pair of vertices 'u v'") });
+    }
+}
@@ -0,0 +1,463 @@
+use std::sync::Arc;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field,UniformRand,Zero};
+use ark_poly::polynomial::univariate::DensePolynomial as UniPoly;
+use rand::Rng;
+
+use crate::group_utils;
+use crate::bulletproof;
+use crate::multilinear_kzg::{self,MultilinearKzgParams};
+use crate::multilinear::ProductMLE;
+use crate::polynomials as polys;
+use crate::sum_check::{self,SumCheckPolynomial,MessageEncoding,Data};
+use crate::sum_check_argument::{self,Message,ArgumentFailure,reject};
+use crate::ip::{IP,Channel,Log};
+
+// PAZK, Section 13.3
+// Zero-knowledge sum-check via a masking polynomial.  The messages of plain
+// sum-check are partial sums of g, which reveal information about g beyond
+// its sum.  Instead, the prover commits to g and to a random polynomial
+//   p(x) = a + p_0(x_0) + ... + p_{v-1}(x_{v-1})
+// with each p_i univariate of the same degree d as g in x_i and p_i(0) = 0,
+// and sends its sum P.  The verifier picks a random nonzero rho, and both run
+// sum-check for the claim that g + rho p sums to H + rho P.  In the round
+// binding x_j the mask contributes 2^j rho p_j(X) to the message, so with
+// the coefficients of p_j uniformly random each message is a uniformly random
+// polynomial among those consistent with the check value: the messages can be
+// simulated knowing only H, and are independent of g.
+//
+// At the end the verifier needs g(r) + rho p(r).  The prover sends the values
+// f_i(r) of the factors of g and p(r), and opens them against the
+// commitments: the factors with the batched multilinear KZG opening of the
+// succinct argument, and p with the inner product argument for its
+// coefficient vector against the vector of monomials (1, r_0, ..., r_0^d,
+// r_1, ...).
+//
+// The commitments here have no blinding factors and the values at r are sent
+// in the clear, so the evaluation g(r) is revealed; only the sum-check
+// messages are zero-knowledge.  Hiding the final evaluation as well needs
+// hiding commitments and a proof about committed values, as in Section 13.2.
+
+/// Masking polynomial p(x) = a + p_0(x_0) + ... + p_{v-1}(x_{v-1}), with
+/// `coeffs[i]` the coefficients of X, ..., X^d in p_i.  Binding the last
+/// variable to r leaves a polynomial of the same form, with p_{v-1}(r) added
+/// to the constant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaskingPolynomial<F: Field> {
+    pub constant: F,
+    pub coeffs: Vec<Vec<F>>,
+}
+
+impl<F: Field> MaskingPolynomial<F> {
+    pub fn new(constant: F, coeffs: Vec<Vec<F>>) -> MaskingPolynomial<F> {
+        let degree = coeffs.first().map_or(0, |c| c.len());
+        assert!(coeffs.iter().all(|c| c.len() == degree), "univariate terms must have the same degree");
+        MaskingPolynomial { constant, coeffs }
+    }
+
+    /// Random masking polynomial of degree `degree` in each variable.
+    pub fn rand(num_vars: usize, degree: usize, rng: &mut impl Rng) -> MaskingPolynomial<F> {
+        let coeffs = (0..num_vars).map(|_| (0..degree).map(|_| F::rand(rng)).collect()).collect();
+        MaskingPolynomial::new(F::rand(rng), coeffs)
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coeffs.first().map_or(0, |c| c.len())
+    }
+
+    // p_i(t), with no constant term
+    fn term(&self, i: usize, t: F) -> F {
+        self.coeffs[i].iter().rev().fold(F::zero(), |acc, c| (acc + c) * t)
+    }
+
+    /// Coefficient vector (a, coeffs[0], ..., coeffs[v-1]), padded with zeros
+    /// to `len`.
+    pub fn coefficient_vector(&self, len: usize) -> Vec<F> {
+        let mut vector: Vec<F> = std::iter::once(self.constant).chain(self.coeffs.concat()).collect();
+        assert!(vector.len() <= len, "too many coefficients");
+        vector.resize(len, F::zero());
+        vector
+    }
+}
+
+/// Vector of monomials (1, r_0, ..., r_0^d, r_1, ...) whose inner product with
+/// the coefficient vector is p(r), padded with zeros to `len`.
+pub fn monomial_vector<F: Field>(point: &[F], degree: usize, len: usize) -> Vec<F> {
+    let mut vector = vec![F::one()];
+    for r_i in point {
+        vector.extend((1..=degree as u64).map(|k| r_i.pow([k])));
+    }
+    assert!(vector.len() <= len, "too many coefficients");
+    vector.resize(len, F::zero());
+    vector
+}
+
+// 2^j as a field element
+fn power_of_two<F: Field>(j: usize) -> F {
+    F::from(2u64).pow([j as u64])
+}
+
+impl<F: Field> SumCheckPolynomial<F> for MaskingPolynomial<F> {
+    fn num_vars(&self) -> usize {
+        self.coeffs.len()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        vec![self.degree(); self.num_vars()]
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polys::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    // summing over x_0, ..., x_{j-1} gives
+    //   2^j (a + p_j(t)) + 2^{j-1} (p_0(1) + ... + p_{j-1}(1))
+    fn round_evaluations(&self) -> Vec<F> {
+        let j = self.num_vars() - 1;
+        let others: F = (0..j).map(|i| self.term(i, F::one())).sum();
+        let others = if j > 0 { power_of_two::<F>(j - 1) * others } else { F::zero() };
+        (0..=self.degree() as u64)
+            .map(|t| power_of_two::<F>(j) * (self.constant + self.term(j, F::from(t))) + others)
+            .collect()
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.constant += self.term(self.num_vars() - 1, r);
+        self.coeffs.pop();
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.constant + point.iter().enumerate().map(|(i, r_i)| self.term(i, *r_i)).sum::<F>()
+    }
+}
+
+/// Masked polynomial g + rho p on which sum-check is run.
+#[derive(Clone)]
+pub struct MaskedPolynomial<F: Field> {
+    pub polynomial: ProductMLE<F>,
+    pub mask: MaskingPolynomial<F>,
+    pub rho: F,
+}
+
+impl<F: Field> MaskedPolynomial<F> {
+    pub fn new(polynomial: ProductMLE<F>, mask: MaskingPolynomial<F>, rho: F) -> MaskedPolynomial<F> {
+        assert_eq!(polynomial.num_vars(), mask.num_vars(), "mask has wrong number of variables");
+        assert_eq!(polynomial.factors.len(), mask.degree(), "mask has wrong degree");
+        MaskedPolynomial { polynomial, mask, rho }
+    }
+}
+
+impl<F: Field> SumCheckPolynomial<F> for MaskedPolynomial<F> {
+    fn num_vars(&self) -> usize {
+        self.polynomial.num_vars()
+    }
+
+    fn variable_degrees(&self) -> Vec<usize> {
+        self.mask.variable_degrees()
+    }
+
+    fn round_polynomial(&self) -> UniPoly<F> {
+        polys::interpolate_evaluations(&self.round_evaluations())
+    }
+
+    fn round_evaluations(&self) -> Vec<F> {
+        let g = self.polynomial.round_evaluations();
+        let p = self.mask.round_evaluations();
+        g.iter().zip(&p).map(|(g_t, p_t)| *g_t + self.rho * p_t).collect()
+    }
+
+    fn fix_last_variable(&mut self, r: F) {
+        self.polynomial.fix_last_variable(r);
+        self.mask.fix_last_variable(r);
+    }
+
+    fn oracle_evaluation(&self, point: &[F]) -> F {
+        self.polynomial.evaluate(point) + self.rho * self.mask.oracle_evaluation(point)
+    }
+}
+
+/// Public parameters: the multilinear KZG reference string for the factors of
+/// g, and generators for committing to the coefficient vector of a mask of
+/// degree `degree`.
+pub struct ZkSumCheckParams<E: Pairing> {
+    pub kzg: MultilinearKzgParams<E>,
+    pub degree: usize,
+    pub mask_gens: Vec<E::G1>,
+}
+
+/// Sample the parameters, running the trusted setup of multilinear KZG with
+/// toxic waste drawn from `rng`.
+pub fn setup<E: Pairing>(num_vars: usize, degree: usize, rng: &mut impl Rng) -> ZkSumCheckParams<E> {
+    let len = (1 + num_vars * degree).next_power_of_two();
+    let tau: Vec<E::ScalarField> = (0..num_vars).map(|_| E::ScalarField::rand(rng)).collect();
+    ZkSumCheckParams { kzg: multilinear_kzg::setup(&tau), degree, mask_gens: group_utils::rand_gens(len, rng) }
+}
+
+/// Prover side of the zero-knowledge argument that the product polynomial
+/// sums to its hypercube sum.
+pub fn prove<E: Pairing>(params: &ZkSumCheckParams<E>, polynomial: &ProductMLE<E::ScalarField>, ch: &Channel<Message<E>>, log: &Log) {
+    let mut rng = rand::thread_rng();
+    let num_vars = polynomial.num_vars();
+
+    log.write(format!("P commits to the {} factors of g with multilinear KZG", polynomial.factors.len()));
+    let commitments: Vec<_> = polynomial.factors.iter().map(|f| multilinear_kzg::commit(&params.kzg, f)).collect();
+    let message = Message::Commitments(commitments);
+    log.write(format!("P --> (commitments = {})", message));
+    ch.send(message);
+
+    log.write(format!("P picks a random masking polynomial p of degree {} in each variable", params.degree));
+    let mask = MaskingPolynomial::rand(num_vars, params.degree, &mut rng);
+    let coeffs = mask.coefficient_vector(params.mask_gens.len());
+    let data = bulletproof::Data::Commitment(group_utils::msm(&params.mask_gens, &coeffs));
+    log.write(format!("P --> (commitment to p = {})", data));
+    ch.send_as(data);
+    let data = Data::Scalar(mask.hypercube_sum());
+    log.write(format!("P --> (P = {})", data));
+    ch.send_as(data);
+
+    let Some(rho) = ch.receive_as::<Data<_>>().and_then(|data| data.to_scalar()) else { return };
+    let mut poly = MaskedPolynomial::new(polynomial.clone(), mask.clone(), rho);
+    log.write(String::from("P runs sum-check on g + rho p"));
+    let Some(challenges) = sum_check::prove_rounds(&mut poly, MessageEncoding::Evaluations, ch, log) else { return };
+    let Some(r_0) = ch.receive_as::<Data<_>>().and_then(|data| data.to_scalar()) else { return };
+    let point: Vec<_> = std::iter::once(r_0).chain(challenges.into_iter().rev()).collect();
+
+    poly.fix_last_variable(r_0);
+    let mut values: Vec<_> = poly.polynomial.factors.iter().map(|f| f.evals[0]).collect();
+    values.push(poly.mask.constant);
+    let data = Data::Evaluations(values);
+    log.write(format!("P --> (f_i(r), p(r) = {})", data));
+    ch.send_as(data);
+
+    sum_check_argument::prove_openings(&params.kzg, &polynomial.factors, &point, ch, log);
+    match ch.receive_as::<Data<_>>().and_then(|data| data.to_decision()) {
+        Some(true) => (),
+        _ => return,
+    }
+    log.write(String::from("P opens p at r with the inner product argument"));
+    let monomials = monomial_vector(&point, params.degree, params.mask_gens.len());
+    bulletproof::prove(&params.mask_gens, &monomials, &coeffs, ch, log);
+}
+
+/// Verifier side of the zero-knowledge argument that a product of
+/// `num_factors` committed multilinear polynomials sums to `claimed_sum`.
+pub fn verify<E: Pairing>(
+        params: &ZkSumCheckParams<E>,
+        claimed_sum: E::ScalarField,
+        num_factors: usize,
+        ch: &Channel<Message<E>>,
+        log: &Log) -> Result<(), ArgumentFailure<E::ScalarField>> {
+    let mut rng = rand::thread_rng();
+    assert_eq!(num_factors, params.degree, "mask has wrong degree");
+    let commitments = match ch.receive() {
+        Message::Commitments(commitments) if commitments.len() == num_factors => commitments,
+        _ => return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "a commitment to each factor" })),
+    };
+    let Some(mask_commitment) = ch.receive_as::<bulletproof::Data<E::G1>>().and_then(|data| data.to_commitment()) else {
+        return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "a commitment to p" }));
+    };
+    let Some(mask_sum) = ch.receive_as::<Data<_>>().and_then(|data| data.to_scalar()) else {
+        return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "the sum of p" }));
+    };
+
+    log.write(String::from("V picks nonzero rho uniformly at random"));
+    let mut rho = E::ScalarField::zero();
+    while rho.is_zero() {
+        rho = E::ScalarField::rand(&mut rng);
+    }
+    let data = Data::Scalar(rho);
+    log.write(format!("V --> (rho = {})", data));
+    ch.send_as(data);
+
+    let masked_sum = claimed_sum + rho * mask_sum;
+    log.write(format!("V runs sum-check for the claim that g + rho p sums to H + rho P = {}", Data::Scalar(masked_sum)));
+    let degrees = vec![num_factors; params.kzg.num_vars];
+    let (point, check_value) = sum_check::verify_rounds(masked_sum, &degrees, ch, log)
+        .map_err(ArgumentFailure::SumCheck)?;
+    let data = Data::Scalar(point[0]);
+    log.write(format!("V --> (r_0 = {}) so that P can open at r", data));
+    ch.send_as(data);
+
+    let mut values = match ch.receive_as::<Data<_>>().and_then(|data| data.to_evaluations()) {
+        Some(values) if values.len() == num_factors + 1 => values,
+        _ => return Err(reject(ch, log, ArgumentFailure::Malformed { expected: "the value of each factor and of p at r" })),
+    };
+    let mask_value = values.pop().unwrap();
+    log.write(String::from("V checks final check value equals f_1(r) ... f_k(r) + rho p(r)"));
+    let evaluation = values.iter().product::<E::ScalarField>() + rho * mask_value;
+    if evaluation != check_value {
+        return Err(reject(ch, log, ArgumentFailure::Claims { evaluation, check_value }));
+    }
+
+    sum_check_argument::verify_openings(&params.kzg, &commitments, &point, &values, ch, log)?;
+    log.write(String::from("V checks the opening of p at r"));
+    let monomials = monomial_vector(&point, params.degree, params.mask_gens.len());
+    if !bulletproof::verify(&params.mask_gens, &monomials, mask_commitment, mask_value, ch, log) {
+        return Err(ArgumentFailure::Opening);
+    }
+    Ok(())
+}
+
+pub struct ZkSumCheckProver<E: Pairing> {
+    pub params: Arc<ZkSumCheckParams<E>>,
+    pub polynomial: Arc<ProductMLE<E::ScalarField>>,
+}
+
+impl<E: Pairing> IP<Message<E>> for ZkSumCheckProver<E> {
+    fn execute(&self, ch: Channel<Message<E>>, log: Log) {
+        prove(&self.params, &self.polynomial, &ch, &log);
+    }
+}
+
+pub struct ZkSumCheckVerifier<E: Pairing> {
+    pub params: Arc<ZkSumCheckParams<E>>,
+    pub claimed_sum: E::ScalarField,
+    pub num_factors: usize,
+}
+
+impl<E: Pairing> IP<Message<E>> for ZkSumCheckVerifier<E> {
+    fn execute(&self, ch: Channel<Message<E>>, log: Log) {
+        // failures are recorded in the log
+        let _ = verify(&self.params, self.claimed_sum, self.num_factors, &ch, &log);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use ark_test_curves::bls12_381::{Bls12_381,Fr};
+    use crate::multilinear::DenseMLE;
+    use crate::small_curves::SS67Pairing;
+    use crate::small_fields::{F13,F17};
+    use crate::test_utils;
+
+    fn run<E: Pairing>(polynomial: ProductMLE<E::ScalarField>, claimed_sum: E::ScalarField) -> Result<(), ArgumentFailure<E::ScalarField>> {
+        let num_factors = polynomial.factors.len();
+        let params = Arc::new(setup::<E>(polynomial.num_vars(), num_factors, &mut rand::thread_rng()));
+        let prover = ZkSumCheckProver { params: params.clone(), polynomial: Arc::new(polynomial) };
+        test_utils::run_against(prover, |ch, log| verify(&params, claimed_sum, num_factors, ch, log))
+    }
+
+    // the messages P, g_{v-1}, ..., g_0 which the honest prover sends for the
+    // polynomial with the given mask, for fixed verifier randomness
+    fn transcript<F: Field>(polynomial: &ProductMLE<F>, mask: &MaskingPolynomial<F>, rho: F, challenges: &[F]) -> Vec<Vec<F>> {
+        let mut transcript = vec![vec![mask.hypercube_sum()]];
+        let mut poly = MaskedPolynomial::new(polynomial.clone(), mask.clone(), rho);
+        for (j, r_j) in challenges.iter().enumerate().rev() {
+            transcript.push(poly.round_evaluations());
+            if j > 0 {
+                poly.fix_last_variable(*r_j);
+            }
+        }
+        transcript
+    }
+
+    // simulated messages from the claimed sum alone, using `tape` for the
+    // masked sum and for the values g_j(0), g_j(2), ..., g_j(d) of each round
+    // message, with g_j(1) fixed by the check value
+    fn simulate<F: Field>(claimed_sum: F, degree: usize, rho: F, challenges: &[F], tape: &mut impl Iterator<Item=F>) -> Vec<Vec<F>> {
+        let mask_sum = tape.next().unwrap();
+        let mut transcript = vec![vec![mask_sum]];
+        let mut check_value = claimed_sum + rho * mask_sum;
+        for r_j in challenges.iter().rev() {
+            let mut evals: Vec<F> = tape.take(degree).collect();
+            evals.insert(1, check_value - evals[0]);
+            check_value = polys::evaluate_from_evaluations(&evals, *r_j);
+            transcript.push(evals);
+        }
+        transcript
+    }
+
+    // all vectors of length n over F13
+    fn all_vectors(n: usize) -> Vec<Vec<F13>> {
+        (0..13usize.pow(n as u32))
+            .map(|mut idx| (0..n).map(|_| { let x = idx % 13; idx /= 13; F13::from(x as u64) }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_masking_polynomial() {
+        let mut rng = rand::thread_rng();
+        let mask: MaskingPolynomial<F17> = MaskingPolynomial::rand(3, 2, &mut rng);
+        let brute_force: F17 = (0..8).map(|idx| {
+            let point: Vec<F17> = crate::multilinear::hypercube_point(3, idx);
+            mask.oracle_evaluation(&point)
+        }).sum();
+        assert_eq!(mask.hypercube_sum(), brute_force);
+
+        let point: Vec<F17> = (0..3).map(|_| F17::rand(&mut rng)).collect();
+        let monomials = monomial_vector(&point, 2, 8);
+        assert_eq!(group_utils::msm(&mask.coefficient_vector(8), &monomials), mask.oracle_evaluation(&point));
+    }
+
+    #[test]
+    fn test_simulator() {
+        // two multilinear polynomials in 2 variables with the same sum
+        let f = DenseMLE::new(2, [1u64, 2, 3, 4].into_iter().map(F13::from).collect());
+        let g = DenseMLE::new(2, [10u64, 0, 0, 0].into_iter().map(F13::from).collect());
+        let (f, g) = (ProductMLE::new(vec![f]), ProductMLE::new(vec![g]));
+        let claimed_sum = f.hypercube_sum();
+        assert_eq!(claimed_sum, g.hypercube_sum());
+
+        let rho = F13::from(5u64);
+        let challenges = [F13::from(3u64), F13::from(7u64)];
+        // each mask a + c_0 x_0 + c_1 x_1 gives a different transcript, so the
+        // transcript is uniform over the same set as the simulator's output
+        let transcripts = |poly: &ProductMLE<F13>| -> BTreeSet<Vec<Vec<F13>>> {
+            all_vectors(3).into_iter()
+                .map(|c| MaskingPolynomial::new(c[0], vec![vec![c[1]], vec![c[2]]]))
+                .map(|mask| transcript(poly, &mask, rho, &challenges))
+                .collect()
+        };
+        let simulated: BTreeSet<_> = all_vectors(3).into_iter()
+            .map(|tape| simulate(claimed_sum, 1, rho, &challenges, &mut tape.into_iter()))
+            .collect();
+        assert_eq!(simulated.len(), 13usize.pow(3));
+        assert_eq!(transcripts(&f), simulated);
+        assert_eq!(transcripts(&g), simulated);
+    }
+
+    #[test]
+    fn test_zk_sum_check() {
+        let polynomial = ProductMLE::<F17>::rand(3, 2, &mut rand::thread_rng());
+        let sum = polynomial.hypercube_sum();
+        assert_eq!(run::<SS67Pairing>(polynomial, sum), Ok(()));
+
+        let polynomial = ProductMLE::<Fr>::rand(3, 2, &mut rand::thread_rng());
+        let sum = polynomial.hypercube_sum();
+        assert_eq!(run::<Bls12_381>(polynomial.clone(), sum), Ok(()));
+        let result = run::<Bls12_381>(polynomial, sum + Fr::from(1u64));
+        assert!(matches!(result, Err(ArgumentFailure::SumCheck(sum_check::SumCheckFailure::Sum { .. }))));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let mut rng = rand::thread_rng();
+        let params = setup::<SS67Pairing>(2, 2, &mut rng);
+        let verify = |messages| {
+            let prover = test_utils::ScriptedProver(messages);
+            test_utils::run_against(prover, |ch, log| verify(&params, F17::from(0u64), 2, ch, log))
+        };
+
+        // the mask sum in place of the commitment to p
+        let commitment = params.mask_gens[0];
+        let zero = F17::from(0u64);
+        let messages = vec![Message::Commitments(vec![commitment; 2]), Message::SumCheck(Data::Scalar(zero))];
+        assert_eq!(verify(messages), Err(ArgumentFailure::Malformed { expected: "a commitment to p" }));
+
+        // zero round polynomials pass sum-check for the masked sum 0 whatever
+        // the challenges, followed by values at r without p(r)
+        let messages = vec![
+            Message::Commitments(vec![commitment; 2]),
+            Message::Mask(bulletproof::Data::Commitment(commitment)),
+            Message::SumCheck(Data::Scalar(zero)),
+            Message::SumCheck(Data::Evaluations(vec![zero; 3])),
+            Message::SumCheck(Data::Evaluations(vec![zero; 3])),
+            Message::SumCheck(Data::Evaluations(vec![zero; 2])),
+        ];
+        assert_eq!(verify(messages), Err(ArgumentFailure::Malformed { expected: "the value of each factor and of p at r" }));
+    }
+}